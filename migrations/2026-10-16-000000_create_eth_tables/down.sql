@@ -0,0 +1,2 @@
+DROP TABLE IF EXISTS eth_transfer;
+DROP TABLE IF EXISTS eth_block;
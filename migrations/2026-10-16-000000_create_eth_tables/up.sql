@@ -0,0 +1,42 @@
+-- 基础表：eth_block / eth_transfer（后续迁移在此基础上增加列与索引）
+-- 使用 IF NOT EXISTS，已手工建表的库执行此迁移不会报错
+CREATE TABLE IF NOT EXISTS eth_block (
+    id               BIGSERIAL PRIMARY KEY,
+    block_number     BIGINT       NOT NULL,
+    block_hash       VARCHAR(66)  NOT NULL,
+    parent_hash      VARCHAR(66)  NOT NULL,
+    gas_used         NUMERIC      NOT NULL DEFAULT 0,
+    base_fee_per_gas NUMERIC      NOT NULL DEFAULT 0,
+    created_at       TIMESTAMP    DEFAULT now(),
+    timestamp        BIGINT       NOT NULL,
+    size             INT4         NOT NULL DEFAULT 0
+);
+
+-- BlockRepository 的 ON CONFLICT (block_number) 依赖该唯一索引
+CREATE UNIQUE INDEX IF NOT EXISTS uk_eth_block_block_number
+    ON eth_block (block_number);
+
+CREATE TABLE IF NOT EXISTS eth_transfer (
+    id               BIGSERIAL PRIMARY KEY,
+    block_number     BIGINT       NOT NULL,
+    tx_hash          VARCHAR(66)  NOT NULL,
+    from_address     VARCHAR(42)  NOT NULL,
+    to_address       VARCHAR(42)  NOT NULL,
+    amount           NUMERIC      NOT NULL DEFAULT 0,
+    contract_address VARCHAR(42),
+    timestamp        BIGINT       NOT NULL,
+    gas              NUMERIC      NOT NULL DEFAULT 0,
+    max_fee_per_gas  NUMERIC      NOT NULL DEFAULT 0,
+    -- 1=确认 2=确认中 3=失败
+    status           SMALLINT     NOT NULL DEFAULT 1,
+    created_at       TIMESTAMP    DEFAULT now(),
+    log_index        BIGINT       NOT NULL DEFAULT 0
+);
+
+-- TransactionRepository 的 ON CONFLICT (tx_hash, log_index) 依赖该唯一索引
+CREATE UNIQUE INDEX IF NOT EXISTS uk_eth_transfer_tx_hash_log_index
+    ON eth_transfer (tx_hash, log_index);
+
+COMMENT ON TABLE eth_block IS '以太坊区块表';
+COMMENT ON TABLE eth_transfer IS '以太坊交易转账表';
+COMMENT ON COLUMN eth_transfer.status IS '1=确认 2=确认中 3=失败';
@@ -0,0 +1,4 @@
+DROP INDEX IF EXISTS idx_eth_transfer_block_number;
+DROP INDEX IF EXISTS idx_eth_transfer_contract_address;
+DROP INDEX IF EXISTS idx_eth_transfer_to_address;
+DROP INDEX IF EXISTS idx_eth_transfer_from_address;
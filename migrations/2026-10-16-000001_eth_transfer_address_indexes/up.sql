@@ -0,0 +1,13 @@
+-- eth_transfer 按地址查询的索引
+-- from/to 分别建索引，配合 recent_transfers_for 中的 UNION 查询使用（OR 条件会导致全表扫描）
+CREATE INDEX IF NOT EXISTS idx_eth_transfer_from_address
+    ON eth_transfer (from_address, block_number DESC);
+
+CREATE INDEX IF NOT EXISTS idx_eth_transfer_to_address
+    ON eth_transfer (to_address, block_number DESC);
+
+CREATE INDEX IF NOT EXISTS idx_eth_transfer_contract_address
+    ON eth_transfer (contract_address);
+
+CREATE INDEX IF NOT EXISTS idx_eth_transfer_block_number
+    ON eth_transfer (block_number);
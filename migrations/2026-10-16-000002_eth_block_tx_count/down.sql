@@ -0,0 +1 @@
+ALTER TABLE eth_block DROP COLUMN IF EXISTS tx_count;
@@ -0,0 +1,8 @@
+-- size 之前记录的是交易数而不是区块字节大小：
+-- 新增 tx_count 列保存交易数，并把历史数据迁移过去；size 此后保存区块头中的字节大小
+ALTER TABLE eth_block ADD COLUMN IF NOT EXISTS tx_count INT4 NOT NULL DEFAULT 0;
+
+UPDATE eth_block SET tx_count = size;
+
+COMMENT ON COLUMN eth_block.size IS '区块大小（字节）';
+COMMENT ON COLUMN eth_block.tx_count IS '区块交易数';
@@ -0,0 +1,18 @@
+-- 持久化的区块处理队列：生产者写入已发现的安全高度，worker 领取、处理并在入库事务中确认（删除）
+CREATE TABLE IF NOT EXISTS block_queue (
+    block_number BIGINT PRIMARY KEY,
+    -- 0=待处理 1=处理中
+    status       SMALLINT    NOT NULL DEFAULT 0,
+    attempts     INT4        NOT NULL DEFAULT 0,
+    worker_id    VARCHAR(64),
+    locked_at    TIMESTAMP,
+    last_error   TEXT,
+    enqueued_at  TIMESTAMP   NOT NULL DEFAULT now()
+);
+
+CREATE INDEX IF NOT EXISTS idx_block_queue_pending
+    ON block_queue (block_number)
+    WHERE status = 0;
+
+COMMENT ON TABLE block_queue IS '区块处理队列';
+COMMENT ON COLUMN block_queue.status IS '0=待处理 1=处理中';
@@ -0,0 +1,4 @@
+-- 交易调用监控合约时记录 EIP-2930 访问列表 [{"address": "0x..", "storageKeys": ["0x.."]}]，legacy 交易为 NULL
+ALTER TABLE eth_transfer ADD COLUMN IF NOT EXISTS access_list JSONB;
+
+COMMENT ON COLUMN eth_transfer.access_list IS 'EIP-2930 访问列表（地址与存储槽）';
@@ -0,0 +1 @@
+ALTER TABLE eth_transfer DROP COLUMN IF EXISTS fee_paid;
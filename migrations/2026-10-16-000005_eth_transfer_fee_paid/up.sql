@@ -0,0 +1,4 @@
+-- 实际支付的手续费 gas_used × effective_gas_price（wei），历史数据为 0
+ALTER TABLE eth_transfer ADD COLUMN IF NOT EXISTS fee_paid NUMERIC NOT NULL DEFAULT 0;
+
+COMMENT ON COLUMN eth_transfer.fee_paid IS '实际支付的手续费（wei）';
@@ -0,0 +1,4 @@
+-- 节点返回的 from 为空且无法从签名恢复（未签名 / 系统交易）时为 true，此时 from_address 为零地址
+ALTER TABLE eth_transfer ADD COLUMN IF NOT EXISTS sender_unknown BOOLEAN NOT NULL DEFAULT FALSE;
+
+COMMENT ON COLUMN eth_transfer.sender_unknown IS '发送方未知（未签名/系统交易）';
@@ -0,0 +1,2 @@
+DROP INDEX IF EXISTS idx_eth_transfer_timestamp;
+DROP INDEX IF EXISTS idx_eth_block_timestamp;
@@ -0,0 +1,6 @@
+-- 按时间区间查询（find_transfers_by_time_range / find_blocks_by_time_range）
+CREATE INDEX IF NOT EXISTS idx_eth_transfer_timestamp
+    ON eth_transfer (timestamp DESC, block_number DESC);
+
+CREATE INDEX IF NOT EXISTS idx_eth_block_timestamp
+    ON eth_block (timestamp DESC);
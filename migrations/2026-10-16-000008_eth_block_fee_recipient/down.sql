@@ -0,0 +1,4 @@
+DROP INDEX IF EXISTS idx_eth_block_fee_recipient;
+ALTER TABLE eth_block DROP COLUMN IF EXISTS total_difficulty;
+ALTER TABLE eth_block DROP COLUMN IF EXISTS difficulty;
+ALTER TABLE eth_block DROP COLUMN IF EXISTS fee_recipient;
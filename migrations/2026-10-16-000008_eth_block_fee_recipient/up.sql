@@ -0,0 +1,12 @@
+-- 出块者 / 手续费接收地址（合并后 author 即 fee recipient），以及 PoW 时期的难度
+-- 历史区块为 NULL；合并后 difficulty 为 0，部分节点不再返回 total_difficulty
+ALTER TABLE eth_block ADD COLUMN IF NOT EXISTS fee_recipient VARCHAR(42);
+ALTER TABLE eth_block ADD COLUMN IF NOT EXISTS difficulty NUMERIC;
+ALTER TABLE eth_block ADD COLUMN IF NOT EXISTS total_difficulty NUMERIC;
+
+CREATE INDEX IF NOT EXISTS idx_eth_block_fee_recipient
+    ON eth_block (fee_recipient);
+
+COMMENT ON COLUMN eth_block.fee_recipient IS '手续费接收地址（miner / author）';
+COMMENT ON COLUMN eth_block.difficulty IS '区块难度（合并后为 0）';
+COMMENT ON COLUMN eth_block.total_difficulty IS '累计难度';
@@ -0,0 +1,15 @@
+-- 按地址汇总的活跃度统计，随转账入库在同一事务中 upsert，避免扫描 eth_transfer
+CREATE TABLE IF NOT EXISTS address_stats (
+    address          VARCHAR(42) NOT NULL,
+    chain_id         BIGINT      NOT NULL,
+    first_seen_block BIGINT      NOT NULL,
+    last_seen_block  BIGINT      NOT NULL,
+    total_transfers  BIGINT      NOT NULL DEFAULT 0,
+    updated_at       TIMESTAMP   NOT NULL DEFAULT now(),
+    PRIMARY KEY (address, chain_id)
+);
+
+COMMENT ON TABLE address_stats IS '地址活跃度汇总';
+COMMENT ON COLUMN address_stats.first_seen_block IS '首次出现的区块';
+COMMENT ON COLUMN address_stats.last_seen_block IS '最近一次出现的区块';
+COMMENT ON COLUMN address_stats.total_transfers IS '作为发送方或接收方的转账总数';
@@ -0,0 +1,21 @@
+-- 监控地址 / 工厂部署者通过合约创建交易部署的合约
+CREATE TABLE IF NOT EXISTS contract_deployment (
+    id               BIGSERIAL PRIMARY KEY,
+    deployer         VARCHAR(42) NOT NULL,
+    contract_address VARCHAR(42) NOT NULL,
+    tx_hash          VARCHAR(66) NOT NULL,
+    block_number     BIGINT      NOT NULL,
+    timestamp        BIGINT      NOT NULL,
+    created_at       TIMESTAMP   DEFAULT now()
+);
+
+-- 一个合约地址只会被部署一次，重复入库时忽略
+CREATE UNIQUE INDEX IF NOT EXISTS uk_contract_deployment_contract_address
+    ON contract_deployment (contract_address);
+
+CREATE INDEX IF NOT EXISTS idx_contract_deployment_deployer
+    ON contract_deployment (deployer);
+
+COMMENT ON TABLE contract_deployment IS '合约部署记录';
+COMMENT ON COLUMN contract_deployment.deployer IS '部署者（交易发送方）';
+COMMENT ON COLUMN contract_deployment.contract_address IS '新合约地址（receipt.contract_address）';
@@ -0,0 +1 @@
+DROP INDEX IF EXISTS idx_eth_transfer_confirming;
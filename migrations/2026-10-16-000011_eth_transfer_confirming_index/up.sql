@@ -0,0 +1,4 @@
+-- 确认中（status = 2）的转账由后台任务按区块号批量提升为已确认，部分索引只包含这部分数据
+CREATE INDEX IF NOT EXISTS idx_eth_transfer_confirming
+    ON eth_transfer (block_number)
+    WHERE status = 2;
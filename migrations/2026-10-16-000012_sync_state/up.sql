@@ -0,0 +1,9 @@
+-- 同步状态（单行）：记录写入现有数据的解析器版本，版本升级时据此决定是否重新处理
+CREATE TABLE IF NOT EXISTS sync_state (
+    id             SMALLINT  PRIMARY KEY DEFAULT 1 CHECK (id = 1),
+    parser_version INT4      NOT NULL,
+    updated_at     TIMESTAMP NOT NULL DEFAULT now()
+);
+
+COMMENT ON TABLE sync_state IS '同步状态';
+COMMENT ON COLUMN sync_state.parser_version IS '已入库数据对应的解析器版本';
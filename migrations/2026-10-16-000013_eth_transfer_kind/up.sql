@@ -0,0 +1,7 @@
+-- 转账类型：0=原生 ETH 1=ERC20 2=ERC721 3=ERC1155 4=内部转账 5=WETH 包装/解包
+-- 存量数据只能按 contract_address 推断：为空记为原生 ETH，否则记为 ERC20
+ALTER TABLE eth_transfer ADD COLUMN IF NOT EXISTS kind SMALLINT NOT NULL DEFAULT 0;
+
+UPDATE eth_transfer SET kind = 1 WHERE contract_address IS NOT NULL;
+
+COMMENT ON COLUMN eth_transfer.kind IS '转账类型 0=原生ETH 1=ERC20 2=ERC721 3=ERC1155 4=内部转账 5=WETH包装';
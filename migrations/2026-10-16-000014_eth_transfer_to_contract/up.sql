@@ -0,0 +1,4 @@
+-- 收款方是否为合约（eth_getCode 非空），未开启 detect_contract_recipients 时为空
+ALTER TABLE eth_transfer ADD COLUMN IF NOT EXISTS to_contract BOOLEAN;
+
+COMMENT ON COLUMN eth_transfer.to_contract IS '收款方是否为合约，未检测时为空';
@@ -0,0 +1,3 @@
+ALTER TABLE eth_block DROP COLUMN IF EXISTS state_root;
+ALTER TABLE eth_block DROP COLUMN IF EXISTS extra_data;
+ALTER TABLE eth_block DROP COLUMN IF EXISTS gas_limit;
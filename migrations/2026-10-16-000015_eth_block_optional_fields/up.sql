@@ -0,0 +1,8 @@
+-- 可选区块字段，由 ethereum.block_fields 控制是否写入，未配置保存时为 NULL
+ALTER TABLE eth_block ADD COLUMN IF NOT EXISTS gas_limit NUMERIC;
+ALTER TABLE eth_block ADD COLUMN IF NOT EXISTS extra_data TEXT;
+ALTER TABLE eth_block ADD COLUMN IF NOT EXISTS state_root VARCHAR(66);
+
+COMMENT ON COLUMN eth_block.gas_limit IS 'Gas 上限';
+COMMENT ON COLUMN eth_block.extra_data IS '区块头 extra_data（十六进制）';
+COMMENT ON COLUMN eth_block.state_root IS '状态根';
@@ -0,0 +1,10 @@
+-- 原始区块 JSON，ethereum.block_fields 包含 raw 时与 eth_block 在同一事务中写入
+CREATE TABLE IF NOT EXISTS eth_block_raw (
+    block_number BIGINT      PRIMARY KEY,
+    block_hash   VARCHAR(66) NOT NULL,
+    raw          JSONB       NOT NULL,
+    created_at   TIMESTAMP   DEFAULT now()
+);
+
+COMMENT ON TABLE eth_block_raw IS '节点返回的原始区块 JSON（审计用）';
+COMMENT ON COLUMN eth_block_raw.raw IS '完整区块 JSON，含全部交易';
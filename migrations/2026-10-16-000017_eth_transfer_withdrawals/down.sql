@@ -0,0 +1,2 @@
+ALTER TABLE eth_transfer DROP COLUMN IF EXISTS validator_index;
+COMMENT ON COLUMN eth_transfer.kind IS '转账类型 0=原生ETH 1=ERC20 2=ERC721 3=ERC1155 4=内部转账 5=WETH包装';
@@ -0,0 +1,5 @@
+-- 验证者提款（kind = 6）的验证者序号；提款的 tx_hash 为区块哈希，log_index 为提款序号
+ALTER TABLE eth_transfer ADD COLUMN IF NOT EXISTS validator_index BIGINT;
+
+COMMENT ON COLUMN eth_transfer.validator_index IS '验证者提款的验证者序号，其余类型为空';
+COMMENT ON COLUMN eth_transfer.kind IS '转账类型 0=原生ETH 1=ERC20 2=ERC721 3=ERC1155 4=内部转账 5=WETH包装 6=验证者提款';
@@ -0,0 +1,19 @@
+-- 因金额超出 amount 列精度而未写入 eth_transfer 的转账（amount_precision.on_overflow = skip），
+-- 与区块在同一事务中写入，保留原始金额供排查
+CREATE TABLE IF NOT EXISTS eth_transfer_rejected (
+    tx_hash          VARCHAR(66) NOT NULL,
+    log_index        BIGINT      NOT NULL,
+    block_number     BIGINT      NOT NULL,
+    contract_address VARCHAR(42),
+    amount           NUMERIC     NOT NULL,
+    reason           TEXT        NOT NULL,
+    created_at       TIMESTAMP   DEFAULT now(),
+    PRIMARY KEY (tx_hash, log_index)
+);
+
+CREATE INDEX IF NOT EXISTS idx_eth_transfer_rejected_block_number
+    ON eth_transfer_rejected (block_number);
+
+COMMENT ON TABLE eth_transfer_rejected IS '未能入库的转账（死信）';
+COMMENT ON COLUMN eth_transfer_rejected.amount IS '原始金额（不限精度）';
+COMMENT ON COLUMN eth_transfer_rejected.reason IS '未入库原因';
@@ -0,0 +1,6 @@
+UPDATE eth_block SET size = tx_count WHERE size IS NULL;
+
+ALTER TABLE eth_block ALTER COLUMN size SET DEFAULT 0;
+ALTER TABLE eth_block ALTER COLUMN size SET NOT NULL;
+
+COMMENT ON COLUMN eth_block.size IS '区块大小（字节）';
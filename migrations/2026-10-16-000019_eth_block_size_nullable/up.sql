@@ -0,0 +1,9 @@
+-- 000002 之前写入的行 size 实为交易数（已复制到 tx_count），置空，避免被当作字节大小统计。
+-- 之后写入的行 size 为区块头中的字节大小，区块头本身就有数百字节、每笔交易至少约一百字节，
+-- 不会与交易数相等，因此 size = tx_count 只命中迁移前的历史行
+ALTER TABLE eth_block ALTER COLUMN size DROP NOT NULL;
+ALTER TABLE eth_block ALTER COLUMN size DROP DEFAULT;
+
+UPDATE eth_block SET size = NULL WHERE size = tx_count;
+
+COMMENT ON COLUMN eth_block.size IS '区块大小（字节），tx_count 迁移之前写入的历史区块为 NULL';
@@ -0,0 +1,201 @@
+use crate::api::server::ApiState;
+use crate::errors::error::AppError;
+use crate::infrastructure::provider::EndpointLatency;
+use crate::models::Transfer;
+use crate::models::block_db::BlockRow;
+use crate::utils::time::{parse_timestamp, validate_time_range};
+use axum::Json;
+use axum::extract::{Query, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use ethers_core::types::H160;
+use serde::Deserialize;
+use serde_json::json;
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+use tokio::time::timeout;
+
+const DEFAULT_LIMIT: i64 = 50;
+const MAX_LIMIT: i64 = 500;
+/// /ready 单项检查超时，避免 RPC 重试拖住探针
+const READY_CHECK_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// 将 AppError 转为 JSON 错误响应
+pub struct ApiError(AppError);
+
+impl From<AppError> for ApiError {
+    fn from(err: AppError) -> Self {
+        ApiError(err)
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let status = match &self.0 {
+            AppError::InvalidAddress(_) | AppError::InvalidRequest(_) | AppError::Validation(_) => {
+                StatusCode::BAD_REQUEST
+            }
+            AppError::NotFound(_) => StatusCode::NOT_FOUND,
+            AppError::RateLimited(_) => StatusCode::SERVICE_UNAVAILABLE,
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+        (status, Json(json!({ "error": self.0.to_string() }))).into_response()
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TransfersQuery {
+    pub address: String,
+    pub limit: Option<i64>,
+}
+
+/// 时间区间查询参数：from / to 支持 unix 秒或 RFC3339
+#[derive(Debug, Deserialize)]
+pub struct TimeRangeQuery {
+    pub from: String,
+    pub to: String,
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+impl TimeRangeQuery {
+    /// 解析为 (from_ts, to_ts, limit, offset)
+    fn resolve(&self) -> Result<(i64, i64, i64, i64), AppError> {
+        let from_ts = parse_timestamp(&self.from)?;
+        let to_ts = parse_timestamp(&self.to)?;
+        validate_time_range(from_ts, to_ts)?;
+        let limit = self.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+        let offset = self.offset.unwrap_or(0).max(0);
+        Ok((from_ts, to_ts, limit, offset))
+    }
+}
+
+/// GET /health：进程存活即返回 200
+pub async fn health() -> Json<serde_json::Value> {
+    Json(json!({ "status": "ok" }))
+}
+
+/// GET /ready：数据库、RPC 均可达且同步延迟未超过阈值时返回 200，否则 503
+pub async fn ready(State(state): State<ApiState>) -> (StatusCode, Json<serde_json::Value>) {
+    let db = match timeout(READY_CHECK_TIMEOUT, state.db_service.health()).await {
+        Ok(Ok(())) => Ok(()),
+        Ok(Err(e)) => Err(e.to_string()),
+        Err(_) => Err("timeout".to_string()),
+    };
+    let rpc = match timeout(READY_CHECK_TIMEOUT, state.provider.get_last_block_number()).await {
+        Ok(Ok(head)) => Ok(head.as_u64()),
+        Ok(Err(e)) => Err(e.to_string()),
+        Err(_) => Err("timeout".to_string()),
+    };
+
+    let last_synced = state.last_synced.load(Ordering::Relaxed);
+    let lag = rpc.as_ref().ok().map(|head| head.saturating_sub(last_synced));
+    let lag_ok = lag.is_some_and(|l| l <= state.max_ready_lag_blocks);
+    let ready = db.is_ok() && rpc.is_ok() && lag_ok;
+
+    let body = json!({
+        "status": if ready { "ready" } else { "not_ready" },
+        "db": { "ok": db.is_ok(), "error": db.err() },
+        "rpc": { "ok": rpc.is_ok(), "head": rpc.as_ref().ok(), "error": rpc.as_ref().err() },
+        "sync": {
+            "ok": lag_ok,
+            "last_synced": last_synced,
+            "lag": lag,
+            "max_lag": state.max_ready_lag_blocks,
+        },
+    });
+    let status = if ready {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+    (status, Json(body))
+}
+
+/// GET /transfers?address=&limit=
+pub async fn list_transfers(
+    State(state): State<ApiState>,
+    Query(query): Query<TransfersQuery>,
+) -> Result<Json<Vec<Transfer>>, ApiError> {
+    let address = query
+        .address
+        .parse::<H160>()
+        .map_err(|_| AppError::InvalidAddress(query.address.clone()))?;
+    let limit = query.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+
+    let mut conn = state
+        .db_service
+        .query_pool
+        .get()
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+    let transfers = state
+        .transaction_repository
+        .recent_transfers_for(&mut conn, &address, limit)
+        .await?;
+    Ok(Json(transfers))
+}
+
+/// GET /blocks/latest
+pub async fn latest_block(State(state): State<ApiState>) -> Result<Json<BlockRow>, ApiError> {
+    let mut conn = state
+        .db_service
+        .query_pool
+        .get()
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+    let block = state
+        .block_repository
+        .get_last_block_number(&mut conn)
+        .await?
+        .ok_or_else(|| AppError::NotFound("尚未同步任何区块".to_string()))?;
+    Ok(Json(block))
+}
+
+/// GET /transfers/by-time?from=&to=&limit=&offset=
+pub async fn transfers_by_time(
+    State(state): State<ApiState>,
+    Query(query): Query<TimeRangeQuery>,
+) -> Result<Json<Vec<Transfer>>, ApiError> {
+    let (from_ts, to_ts, limit, offset) = query.resolve()?;
+    let mut conn = state
+        .db_service
+        .query_pool
+        .get()
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+    let transfers = state
+        .transaction_repository
+        .find_transfers_by_time_range(&mut conn, from_ts, to_ts, limit, offset)
+        .await?;
+    Ok(Json(transfers))
+}
+
+/// GET /blocks/by-time?from=&to=&limit=&offset=
+pub async fn blocks_by_time(
+    State(state): State<ApiState>,
+    Query(query): Query<TimeRangeQuery>,
+) -> Result<Json<Vec<BlockRow>>, ApiError> {
+    let (from_ts, to_ts, limit, offset) = query.resolve()?;
+    let mut conn = state
+        .db_service
+        .query_pool
+        .get()
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+    let blocks = state
+        .block_repository
+        .find_blocks_by_time_range(&mut conn, from_ts, to_ts, limit, offset)
+        .await?;
+    Ok(Json(blocks))
+}
+
+/// GET /metrics/providers：各 RPC 节点的响应延迟 EWMA 与冷却状态
+pub async fn provider_metrics(State(state): State<ApiState>) -> Json<Vec<EndpointLatency>> {
+    Json(state.provider.latency_stats())
+}
+
+/// GET /metrics/db：数据库事务因暂时性错误重试的累计次数
+pub async fn db_metrics(State(state): State<ApiState>) -> Json<serde_json::Value> {
+    Json(json!({ "commit_retries": state.db_service.commit_retries() }))
+}
@@ -0,0 +1,46 @@
+use crate::api::handlers;
+use crate::config::ServerConfig;
+use crate::database::diesel::DbService;
+use crate::errors::error::AppError;
+use crate::infrastructure::provider::ProviderTrait;
+use crate::log_info;
+use crate::repositories::block_repository::BlockRepository;
+use crate::repositories::transaction_repository::TransactionRepository;
+use axum::Router;
+use axum::routing::get;
+use std::sync::Arc;
+use std::sync::atomic::AtomicU64;
+
+/// HTTP 查询接口共享状态（只读，不参与同步流程）
+#[derive(Clone)]
+pub struct ApiState {
+    pub db_service: Arc<DbService>,
+    pub block_repository: Arc<BlockRepository>,
+    pub transaction_repository: Arc<TransactionRepository>,
+    pub provider: Arc<dyn ProviderTrait>,
+    /// 与 BlockService 共享的已同步高度
+    pub last_synced: Arc<AtomicU64>,
+    pub max_ready_lag_blocks: u64,
+}
+
+pub fn router(state: ApiState) -> Router {
+    Router::new()
+        .route("/health", get(handlers::health))
+        .route("/ready", get(handlers::ready))
+        .route("/transfers", get(handlers::list_transfers))
+        .route("/transfers/by-time", get(handlers::transfers_by_time))
+        .route("/blocks/latest", get(handlers::latest_block))
+        .route("/blocks/by-time", get(handlers::blocks_by_time))
+        .route("/metrics/providers", get(handlers::provider_metrics))
+        .route("/metrics/db", get(handlers::db_metrics))
+        .with_state(state)
+}
+
+/// 绑定 server.host:server.port 并启动只读查询接口
+pub async fn serve(config: &ServerConfig, state: ApiState) -> Result<(), AppError> {
+    let addr = format!("{}:{}", config.host, config.port);
+    let listener = tokio::net::TcpListener::bind(&addr).await?;
+    log_info!("🌐 查询接口已启动: http://{}", addr);
+    axum::serve(listener, router(state)).await?;
+    Ok(())
+}
@@ -1,7 +1,12 @@
 use config;
-use config::{ConfigError, File};
+use config::{ConfigError, Environment, File};
 use ethers::prelude::U64;
+use crate::infrastructure::provider::ConfirmationTarget;
+use crate::models::domain::transfer::TransferKind;
+use crate::services::tx::gas::gas_strategy::TxPriority;
+use ethers_core::types::{H160, H256};
 use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
 
 #[derive(Debug, Deserialize, Clone)]
 pub struct Config {
@@ -9,6 +14,20 @@ pub struct Config {
     pub redis: RedisConfig,
     pub server: ServerConfig,
     pub ethereum: EthereumConfig,
+    /// 告警阈值与通知渠道，默认关闭
+    #[serde(default)]
+    pub alert: AlertConfig,
+    /// 交易发送（优先级、gas 上限、确认策略、签名后端），默认不创建签名器
+    #[serde(default)]
+    pub tx: TxConfig,
+    /// 启动阶段每个网络步骤（数据库、Redis、RPC、签名器）的超时（秒），
+    /// 地址配错或服务不可达时及时失败而不是一直挂起
+    #[serde(default = "default_startup_timeout_secs")]
+    pub startup_timeout_secs: u64,
+}
+
+fn default_startup_timeout_secs() -> u64 {
+    30
 }
 
 /// PostgreSQL 连接配置（结构化管理）
@@ -22,14 +41,155 @@ pub struct DatabaseConfig {
     // 连接池优化参数
     pub max_connections: u32,
     pub min_connections: u32,
+    /// 查询接口（HTTP API）独立连接池的大小，与同步使用的池（max_connections）隔离，
+    /// 避免查询高峰占满连接拖慢同步；未配置时两者共用一个池
+    #[serde(default)]
+    pub query_max_connections: Option<u32>,
     pub connect_timeout_seconds: u64,
     pub idle_timeout_seconds: u64,
+    /// 每个连接的 search_path schema；多个索引器共用一个库时各自使用独立 schema 隔离 eth_block / eth_transfer
+    /// （开启 run_migrations 时自动创建并在该 schema 下执行迁移，否则需提前创建）
+    #[serde(default)]
+    pub db_schema: Option<String>,
+    /// 启动时执行内嵌的 migrations（建表、加列、建索引），由 DBA 统一管理表结构时可关闭
+    #[serde(default = "default_run_migrations")]
+    pub run_migrations: bool,
+    /// 写入前校验 eth_transfer.amount 是否超出列精度
+    #[serde(default)]
+    pub amount_precision: AmountPrecisionConfig,
+    /// 区块提交事务遇到可重试的数据库错误时的重试策略
+    #[serde(default)]
+    pub commit_retry: CommitRetryConfig,
+}
+
+/// 区块提交事务的重试：只针对序列化失败、死锁、连接断开等暂时性错误，
+/// 与 RPC 重试相互独立；约束冲突等其余错误直接返回
+#[derive(Debug, Deserialize, Clone)]
+pub struct CommitRetryConfig {
+    /// 最大重试次数（不含首次执行），0 为不重试
+    #[serde(default = "default_commit_max_retries")]
+    pub max_retries: u32,
+    /// 首次重试前的等待时间（毫秒），之后每次翻倍
+    #[serde(default = "default_commit_backoff_ms")]
+    pub backoff_ms: u64,
+}
+
+impl Default for CommitRetryConfig {
+    fn default() -> Self {
+        Self {
+            max_retries: default_commit_max_retries(),
+            backoff_ms: default_commit_backoff_ms(),
+        }
+    }
+}
+
+fn default_commit_max_retries() -> u32 {
+    3
+}
+
+fn default_commit_backoff_ms() -> u64 {
+    100
+}
+
+/// eth_transfer.amount 的精度校验：列被改为 NUMERIC(p, 0) 等有限精度时，
+/// 超出精度的值会让整批插入失败、整个区块无法入库
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct AmountPrecisionConfig {
+    /// 列的总位数（如 78）；未配置时不校验（迁移创建的 NUMERIC 无精度限制）
+    #[serde(default)]
+    pub precision: Option<u64>,
+    /// 超出精度时的处理方式
+    #[serde(default)]
+    pub on_overflow: AmountOverflowPolicy,
+}
+
+/// 金额超出列精度时的处理方式
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum AmountOverflowPolicy {
+    /// 返回 Validation 错误并指出交易哈希，该区块不入库
+    #[default]
+    Reject,
+    /// 该条转账不写入 eth_transfer，改为与区块在同一事务中写入 eth_transfer_rejected
+    Skip,
+}
+
+fn default_run_migrations() -> bool {
+    true
 }
 
 #[derive(Debug, Deserialize, Clone)]
 pub struct ServerConfig {
     pub host: String,
     pub port: u16,
+    /// 是否启动只读 HTTP 查询接口（纯同步部署可关闭，不占用端口）
+    #[serde(default)]
+    pub enabled: bool,
+    /// /ready 允许的最大同步延迟（区块数），超过则返回 503
+    #[serde(default = "default_max_ready_lag_blocks")]
+    pub max_ready_lag_blocks: u64,
+}
+
+fn default_max_ready_lag_blocks() -> u64 {
+    50
+}
+
+/// 告警配置：各阈值为 None 时不检查该项
+#[derive(Debug, Deserialize, Clone)]
+pub struct AlertConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Slack / Discord 风格的 webhook 地址，未配置时只写日志
+    #[serde(default)]
+    pub webhook_url: Option<String>,
+    /// 检查间隔（秒）
+    #[serde(default = "default_alert_interval_secs")]
+    pub interval_secs: u64,
+    /// 同一告警的最短重复间隔（秒），防止刷屏
+    #[serde(default = "default_alert_debounce_secs")]
+    pub debounce_secs: u64,
+    /// 同步延迟超过该区块数时告警
+    #[serde(default)]
+    pub max_sync_lag_blocks: Option<u64>,
+    /// RPC 连续失败（重试耗尽）达到该次数时告警
+    #[serde(default)]
+    pub max_consecutive_rpc_failures: Option<u64>,
+    /// 重组深度超过该区块数时告警
+    #[serde(default)]
+    pub max_reorg_depth: Option<u64>,
+    /// 需要监控余额下限的地址
+    #[serde(default)]
+    pub balance_floors: Vec<BalanceFloorConfig>,
+}
+
+/// 余额下限：address 的原生余额低于 floor_gwei 时告警
+#[derive(Debug, Deserialize, Clone)]
+pub struct BalanceFloorConfig {
+    pub address: H160,
+    pub floor_gwei: u64,
+}
+
+fn default_alert_interval_secs() -> u64 {
+    30
+}
+
+fn default_alert_debounce_secs() -> u64 {
+    600
+}
+
+impl Default for AlertConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            webhook_url: None,
+            interval_secs: default_alert_interval_secs(),
+            debounce_secs: default_alert_debounce_secs(),
+            max_sync_lag_blocks: None,
+            max_consecutive_rpc_failures: None,
+            max_reorg_depth: None,
+            balance_floors: Vec::new(),
+        }
+    }
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -39,26 +199,982 @@ pub struct RedisConfig {
     pub username: String,
     pub password: String,
     pub db: i64,
+    /// Redis 不可用时是否启动失败；为 false 时只告警，依赖 Redis 的功能（幂等发送等）被禁用
+    #[serde(default)]
+    pub required: bool,
 }
 
 #[derive(Debug, Deserialize, Clone)]
 pub struct EthereumConfig {
     pub rpc_url: String,
     pub chain_id: u64,
+    /// 以 URL 路径方式携带的 API key，逗号分隔；只使用 `rpc_endpoints` 时可留空
+    #[serde(default)]
     pub api_keys: String,
+    /// 本地无数据时的起始高度（显式回填历史区块时使用）
+    #[serde(default)]
     pub init_height: u64,
+    /// 本地无数据时从当前安全高度（链头 - delay）开始索引，忽略 init_height；已有数据时不影响续传
+    #[serde(default)]
+    pub start_from_head: bool,
+    /// 同步到该高度（含）后正常退出，配合 init_height 可一次性索引固定区间；不设置则持续运行
+    #[serde(default)]
+    pub stop_at_block: Option<u64>,
     pub delay: i16,
+    /// 追上安全高度后两次检查链头之间的间隔（毫秒）；有新区块可同步时不等待
+    #[serde(default = "default_poll_interval_ms")]
+    pub poll_interval_ms: u64,
+    /// 空闲时安全高度至少前进该区块数才恢复同步（死区），0 表示有新的安全区块即恢复
+    #[serde(default)]
+    pub idle_deadband_blocks: u64,
     pub max_retries: usize,
     pub base_delay_secs: u64,
+    /// 地址过滤模式，默认只索引白名单地址相关的转账
+    #[serde(default)]
+    pub filter_mode: FilterMode,
+    /// 多节点选择策略，默认轮询
+    #[serde(default)]
+    pub provider_selection: ProviderSelection,
+    /// 区块表中要保存的可选字段，未列出的字段写入 NULL；核心字段总是保存
+    #[serde(default = "default_block_fields")]
+    pub block_fields: HashSet<BlockField>,
+    /// 单次 RPC 请求超过该耗时（毫秒）时记录慢调用警告，0 表示不记录
+    #[serde(default = "default_slow_rpc_threshold_ms")]
+    pub slow_rpc_threshold_ms: u64,
+    /// 已知会重复发出 Transfer 事件的合约（代理 / 双发 bug）：同一交易内 (合约, from, to, value) 相同的日志只保留第一条。
+    /// 默认为空——正常情况下不同 log_index 代表不同的转账，不应去重
+    #[serde(default)]
+    pub dedup_double_emit_contracts: HashSet<H160>,
+    /// 是否只接受标准 ERC20 Transfer 日志（data 恰好 32 字节），默认宽松
+    #[serde(default)]
+    pub strict_erc20_data: bool,
+    /// 非严格模式下，data 为多个 32 字节时取哪一个作为金额
+    #[serde(default)]
+    pub erc20_value_word: Erc20ValueWord,
+    /// 发送方无法确定（from 为零地址且无法从签名恢复，如系统交易、L2 充值交易）的 ETH 转账如何处理
+    #[serde(default)]
+    pub unknown_sender: UnknownSenderPolicy,
+    /// 是否保存涉及监控地址的失败（revert）交易，status = 3，默认不保存；也可写作 index_failed_txs
+    #[serde(default, alias = "index_failed_txs")]
+    pub store_failed_txs: bool,
+    /// 是否用 eth_getCode 检测原生 ETH 转账的收款方是否为合约（结果按地址缓存），
+    /// 写入 to_contract；关闭时 to_contract 为空
+    #[serde(default)]
+    pub detect_contract_recipients: bool,
+    /// 历史区块缓存
+    #[serde(default)]
+    pub block_cache: BlockCacheConfig,
+    /// 限流（HTTP 429）时的退避与节点冷却策略
+    #[serde(default)]
+    pub rate_limit: RateLimitConfig,
+    /// 全局同时进行中的 RPC 请求上限（所有调用与重试共享），0 表示不限制；
+    /// 故障期间避免 调用数 × 重试次数 的请求量压垮节点或触发限流
+    #[serde(default)]
+    pub rpc_concurrency: usize,
+    /// 单个区块内并发获取交易收据的上限
+    #[serde(default = "default_receipt_concurrency")]
+    pub receipt_concurrency: usize,
+    /// 同时拉取并解析的区块数（滑动窗口），解析完成的区块仍严格按高度顺序入库；
+    /// 1 表示逐块串行。快速模式区间不使用流水线
+    #[serde(default = "default_pipeline_window")]
+    pub pipeline_window: usize,
+    /// 单次 sync_blocks 最多处理的区块数，达到后让出控制权再继续，0 表示不限制
+    #[serde(default = "default_sync_budget_blocks")]
+    pub sync_budget_blocks: u64,
+    /// 单次 sync_blocks 最长运行时间（秒），0 表示不限制
+    #[serde(default = "default_sync_budget_secs")]
+    pub sync_budget_secs: u64,
+    /// 落后安全高度超过该区块数时切换到 eth_getLogs 快速追赶模式，0 表示关闭。
+    /// 快速模式只按事件日志解析，不记录无日志的 ETH 转账、失败交易与合约部署，gas / 手续费记为 0；
+    /// 追到阈值以内后自动回到逐区块的完整解析路径
+    #[serde(default)]
+    pub fast_mode_threshold: u64,
+    /// 快速模式下单次 eth_getLogs 覆盖的区块数
+    #[serde(default = "default_fast_mode_range")]
+    pub fast_mode_range: u64,
+    /// 使用 eth_getBlockReceipts 批量获取收据（节点不支持时自动回退逐笔获取）
+    #[serde(default)]
+    pub use_block_receipts: bool,
+    /// 合并并发的相同区块 / 收据读请求，减少重复 RPC
+    #[serde(default)]
+    pub singleflight_reads: bool,
+    /// 重新探测节点 RPC 能力的间隔（秒），0 表示只在启动时探测一次
+    #[serde(default = "default_capability_probe_interval_secs")]
+    pub capability_probe_interval_secs: u64,
+    /// 自定义 RPC 请求头（如 Authorization、x-api-key），为空时行为与之前一致
+    #[serde(default)]
+    pub rpc_headers: HashMap<String, String>,
+    /// 额外的 RPC 节点：使用完整 URL，可单独配置请求头与认证（适用于请求头鉴权的服务商）
+    #[serde(default)]
+    pub rpc_endpoints: Vec<RpcEndpointConfig>,
+    /// 额外识别的类转账事件（如 Comet `Supply`、Aave `Mint`），与内置的 ERC-20/721/1155 事件一起生效
+    #[serde(default)]
+    pub transfer_events: Vec<TransferEventConfig>,
+    /// 启动时按 `delay` 个区块回查本地最新区块哈希，发现孤块则回滚到最后一个有效区块
+    #[serde(default)]
+    pub verify_on_startup: bool,
+    /// 编译进来的解析器版本高于已入库数据的版本时，启动时删除最近 `reprocess_window` 个区块并重新同步
+    #[serde(default)]
+    pub reprocess_on_version_bump: bool,
+    /// 版本升级时重新处理的区块数，0 表示从 init_height 起全部重新处理
+    #[serde(default = "default_reprocess_window")]
+    pub reprocess_window: u64,
+    /// 持久化区块队列（多 worker 并行解析、崩溃可恢复），默认关闭
+    #[serde(default)]
+    pub block_queue: BlockQueueConfig,
+    /// 区块时间戳合理性校验
+    #[serde(default)]
+    pub timestamp_check: TimestampCheckConfig,
+    /// 本机节点 IPC 路径（需启用 `ipc` feature），连接失败时回退到 HTTP
+    #[serde(default)]
+    pub ipc_path: Option<String>,
+    /// 已迁移到 `[tx.speed_up]`，仅在 `[tx]` 未配置 speed_up 时作为兼容回退
+    #[serde(default)]
+    pub speed_up: Option<SpeedUpConfig>,
+    /// 已入库区块的跨节点抽样一致性校验
+    #[serde(default)]
+    pub consistency_check: ConsistencyCheckConfig,
+    /// 两阶段转账状态：重组窗口内先记为确认中，超过最终确认深度后由后台任务提升为已确认
+    #[serde(default)]
+    pub transfer_status: TransferStatusConfig,
+    /// 采样索引（轻量监控部署），默认每个区块都解析
+    #[serde(default)]
+    pub sampling: SamplingConfig,
+    /// 记录监控地址部署的合约（receipt.contract_address）
+    #[serde(default)]
+    pub deployment_tracking: DeploymentTrackingConfig,
+    /// 已迁移到 `[tx.signer]`，仅在 `[tx]` 未配置 signer 时作为兼容回退
+    #[serde(default)]
+    pub signer: Option<SignerConfig>,
+}
+
+fn default_poll_interval_ms() -> u64 {
+    1_000
+}
+
+fn default_block_fields() -> HashSet<BlockField> {
+    HashSet::from([
+        BlockField::FeeRecipient,
+        BlockField::Difficulty,
+        BlockField::TotalDifficulty,
+    ])
+}
+
+fn default_slow_rpc_threshold_ms() -> u64 {
+    3_000
+}
+
+fn default_receipt_concurrency() -> usize {
+    8
+}
+
+fn default_pipeline_window() -> usize {
+    1
+}
+
+fn default_sync_budget_blocks() -> u64 {
+    500
+}
+
+fn default_sync_budget_secs() -> u64 {
+    30
+}
+
+fn default_reprocess_window() -> u64 {
+    10_000
+}
+
+fn default_fast_mode_range() -> u64 {
+    500
+}
+
+fn default_capability_probe_interval_secs() -> u64 {
+    6 * 3600
+}
+
+impl EthereumConfig {
+    /// 解析 IPC 端点：优先 ipc_path，其次 rpc_url 本身是 ipc:// 或文件路径
+    pub fn ipc_endpoint(&self) -> Option<&str> {
+        if let Some(path) = self.ipc_path.as_deref().filter(|p| !p.is_empty()) {
+            return Some(path);
+        }
+        let url = self.rpc_url.as_str();
+        (url.starts_with("ipc://") || url.starts_with('/') || url.ends_with(".ipc")).then_some(url)
+    }
+}
+
+/// 单个 RPC 节点配置
+///
+/// ```toml
+/// [[ethereum.rpc_endpoints]]
+/// url = "https://rpc.example.com"
+/// headers = { "X-API-Key" = "..." }
+/// auth = { type = "basic", username = "user", password = "pass" }
+/// ```
+#[derive(Debug, Deserialize, Clone)]
+pub struct RpcEndpointConfig {
+    pub url: String,
+    /// 该节点专用请求头，与全局 `rpc_headers` 合并（同名时以此为准）
+    #[serde(default)]
+    pub headers: HashMap<String, String>,
+    #[serde(default)]
+    pub auth: Option<RpcAuth>,
+}
+
+/// RPC 认证方式，写入 Authorization 请求头
+#[derive(Debug, Deserialize, Clone)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum RpcAuth {
+    Basic { username: String, password: String },
+    Bearer { token: String },
+}
+
+/// 转账过滤模式
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum FilterMode {
+    /// 发送方或接收方在 address.toml 中，代币转账还要求合约在 contracts.toml 中（原有行为）
+    #[default]
+    Whitelist,
+    /// 不做地址过滤，索引所有 ETH 转账与代币转账。
+    /// 主网每个区块可产生数百到上千条记录，表与索引增长很快，需评估存储与写入能力后再开启
+    All,
+    /// 只索引监控合约（contracts.toml 及配置事件的合约）上的代币转账，不限用户地址，不记录 ETH 转账。
+    /// 按日志发出者判断，经路由 / 多签等中间合约发生的转账同样记录；每个区块多一次 eth_getLogs
+    ContractsOnly,
+}
+
+/// 多节点选择策略
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ProviderSelection {
+    /// 依次轮询（原有行为）
+    #[default]
+    RoundRobin,
+    /// 按各节点响应延迟的 EWMA 加权随机选择，延迟越低被选中概率越高。
+    /// 流量会向最快的节点集中，需确认其额度足够
+    LatencyAware,
+}
+
+/// 非标准 Transfer 日志（data 为 32 字节整数倍）中金额所在的 32 字节
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Erc20ValueWord {
+    /// 取第一个 32 字节（尾部补齐）
+    #[default]
+    First,
+    /// 取最后一个 32 字节（头部补齐）
+    Last,
+}
+
+/// eth_block 的可选字段
+///
+/// 区块号、哈希、父哈希、gas_used、base_fee_per_gas、时间戳、大小与交易数总是保存，
+/// 这里列出的字段可按需开启或关闭：
+///
+/// ```toml
+/// [ethereum]
+/// block_fields = ["miner", "difficulty", "total_difficulty", "gas_limit"]
+/// ```
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum BlockField {
+    /// 出块者 / 手续费接收地址（fee_recipient 列）
+    #[serde(alias = "miner")]
+    FeeRecipient,
+    Difficulty,
+    TotalDifficulty,
+    GasLimit,
+    ExtraData,
+    StateRoot,
+    /// 节点返回的完整区块 JSON（含全部交易），与区块在同一事务中写入 eth_block_raw 表；
+    /// 用于审计与事后重新推导字段，存储量会成倍增加，默认关闭
+    Raw,
+}
+
+/// 发送方未知的 ETH 转账处理方式
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum UnknownSenderPolicy {
+    /// 照常入库，sender_unknown = true，from_address 为零地址
+    #[default]
+    Flag,
+    /// 不入库，避免出现看似由零地址发出的转账
+    Skip,
+}
+
+/// 类转账事件定义：topic0 → from / to / value 的提取方式
+///
+/// ```toml
+/// [[ethereum.transfer_events]]
+/// name = "aave_mint"
+/// topic = "0x458f5fa412d0f69b08dd84872b0215675cc67bc1d5b6fd93300a1c3878b86196"
+/// topic_count = 3
+/// contracts = ["0x..."]
+/// from = "zero"
+/// to = { topic = 2 }
+/// value = { data = 0 }
+///
+/// # WETH Deposit(address indexed dst, uint256 wad)：记为 weth_wrap
+/// [[ethereum.transfer_events]]
+/// name = "weth_deposit"
+/// topic = "0xe1fffcc4923d04b559f4d29a8bfc6cda04eb5b0d3c460751c2402c5c5cc9109c"
+/// topic_count = 2
+/// contracts = ["0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2"]
+/// from = "contract"
+/// to = { topic = 1 }
+/// value = { data = 0 }
+/// kind = "weth_wrap"
+/// ```
+///
+/// 也可以写在 `config/transfer_events.toml`（`[[events]]`，字段相同），该文件随地址库热重载
+#[derive(Debug, Deserialize, Clone)]
+pub struct TransferEventConfig {
+    pub name: String,
+    pub topic: H256,
+    /// 要求的 topics 数量（含 topic0），用于区分同签名不同 indexed 的事件（ERC-20 与 ERC-721）
+    #[serde(default)]
+    pub topic_count: Option<usize>,
+    /// 只匹配这些合约发出的事件；为空时沿用监控合约列表
+    #[serde(default)]
+    pub contracts: Vec<H160>,
+    pub from: AddressSource,
+    pub to: AddressSource,
+    pub value: ValueSource,
+    /// 记录的转账类型，默认 erc20
+    #[serde(default = "default_transfer_event_kind")]
+    pub kind: TransferKind,
+}
+
+fn default_transfer_event_kind() -> TransferKind {
+    TransferKind::Erc20
+}
+
+impl TransferEventConfig {
+    /// 校验 topic 布局是否自洽：topic 位置在 1..topic_count 内（topic 0 是事件签名），
+    /// from / to / value 不能取自同一个位置
+    pub fn validate(&self) -> Result<(), String> {
+        if self.topic.is_zero() {
+            return Err(format!("事件 {}: topic 不能为空", self.name));
+        }
+        let max_topics = match self.topic_count {
+            Some(n) if !(1..=4).contains(&n) => {
+                return Err(format!("事件 {}: topic_count 必须在 1..=4 之间，当前 {}", self.name, n));
+            }
+            Some(n) => n,
+            None => 4,
+        };
+        let check_topic = |field: &str, i: usize| {
+            if i == 0 || i >= max_topics {
+                Err(format!(
+                    "事件 {}: {} 取自 topic {}，有效范围为 1..{}",
+                    self.name, field, i, max_topics
+                ))
+            } else {
+                Ok(())
+            }
+        };
+        for (field, source) in [("from", self.from), ("to", self.to)] {
+            if let AddressSource::Topic(i) = source {
+                check_topic(field, i)?;
+            }
+        }
+        if let ValueSource::Topic(i) = self.value {
+            check_topic("value", i)?;
+        }
+
+        if self.from == self.to {
+            return Err(format!("事件 {}: from 与 to 的来源相同: {:?}", self.name, self.from));
+        }
+        let overlaps = |source: AddressSource| match (source, self.value) {
+            (AddressSource::Topic(a), ValueSource::Topic(v)) | (AddressSource::Data(a), ValueSource::Data(v)) => a == v,
+            _ => false,
+        };
+        if overlaps(self.from) || overlaps(self.to) {
+            return Err(format!("事件 {}: value 与地址字段取自同一位置: {:?}", self.name, self.value));
+        }
+        Ok(())
+    }
+}
+
+/// 事件中地址字段的来源
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum AddressSource {
+    /// 第 n 个 topic（indexed 参数）
+    Topic(usize),
+    /// data 中第 n 个 32 字节
+    Data(usize),
+    /// 零地址（铸造 / 销毁类事件）
+    Zero,
+    /// 发出事件的合约地址
+    Contract,
+}
+
+/// 事件中金额字段的来源
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ValueSource {
+    Topic(usize),
+    /// data 中第 n 个 32 字节；第 0 个按 `strict_erc20_data` / `erc20_value_word` 规则解析
+    Data(usize),
+    /// 固定金额（如 ERC-721 每次转移记为 1）
+    Constant(u64),
+}
+
+/// 区块队列配置
+#[derive(Debug, Deserialize, Clone)]
+pub struct BlockQueueConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// 并行处理的 worker 数
+    #[serde(default = "default_queue_workers")]
+    pub workers: usize,
+    /// 队列中最多积压的区块数
+    #[serde(default = "default_queue_max_pending")]
+    pub max_pending: usize,
+    /// 领取租约（秒），超时未确认的区块会被重新领取
+    #[serde(default = "default_queue_lease_secs")]
+    pub lease_secs: u64,
+    /// 队列为空或等待前序区块时的轮询间隔（毫秒）
+    #[serde(default = "default_queue_poll_interval_ms")]
+    pub poll_interval_ms: u64,
+}
+
+fn default_queue_workers() -> usize {
+    4
+}
+
+fn default_queue_max_pending() -> usize {
+    256
+}
+
+fn default_queue_lease_secs() -> u64 {
+    120
+}
+
+fn default_queue_poll_interval_ms() -> u64 {
+    500
+}
+
+impl Default for BlockQueueConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            workers: default_queue_workers(),
+            max_pending: default_queue_max_pending(),
+            lease_secs: default_queue_lease_secs(),
+            poll_interval_ms: default_queue_poll_interval_ms(),
+        }
+    }
+}
+
+/// 跨节点一致性校验配置：定期从已入库且足够深的区块中抽样，逐个节点比对区块哈希
+#[derive(Debug, Deserialize, Clone)]
+pub struct ConsistencyCheckConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// 抽样间隔（秒）
+    #[serde(default = "default_consistency_interval_secs")]
+    pub interval_secs: u64,
+    /// 每次抽样的区块数
+    #[serde(default = "default_consistency_sample_size")]
+    pub sample_size: usize,
+    /// 只抽样距本地最新高度至少这么深的区块（视为已最终确认）
+    #[serde(default = "default_consistency_min_depth")]
+    pub min_depth: u64,
+    /// 抽样范围：min_depth 之下最近的区块数
+    #[serde(default = "default_consistency_window")]
+    pub window: u64,
+    /// 所有节点一致认为本地哈希错误时，回滚到该区块之前重新同步（队列模式下不生效）
+    #[serde(default)]
+    pub reprocess_on_mismatch: bool,
+}
+
+fn default_consistency_interval_secs() -> u64 {
+    600
+}
+
+fn default_consistency_sample_size() -> usize {
+    3
+}
+
+fn default_consistency_min_depth() -> u64 {
+    64
 }
+
+fn default_consistency_window() -> u64 {
+    1000
+}
+
+impl Default for ConsistencyCheckConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            interval_secs: default_consistency_interval_secs(),
+            sample_size: default_consistency_sample_size(),
+            min_depth: default_consistency_min_depth(),
+            window: default_consistency_window(),
+            reprocess_on_mismatch: false,
+        }
+    }
+}
+
+/// 签名后端选择，`backend` 字段区分类型；私钥、口令、助记词只从环境变量读取，不写入配置文件
+#[derive(Debug, Deserialize, Clone)]
+#[serde(tag = "backend", rename_all = "snake_case")]
+pub enum SignerConfig {
+    /// 十六进制私钥
+    Local {
+        #[serde(default = "default_private_key_env")]
+        private_key_env: String,
+    },
+    /// 加密的 JSON keystore 文件
+    Keystore {
+        path: String,
+        #[serde(default = "default_keystore_password_env")]
+        password_env: String,
+    },
+    /// AWS KMS 托管的 secp256k1 密钥（需启用 `kms` feature）
+    Kms {
+        key_id: String,
+        /// AWS 区域，未配置时使用 AWS_DEFAULT_REGION / AWS_REGION
+        #[serde(default)]
+        region: Option<String>,
+    },
+    /// BIP-39 助记词派生（默认路径 m/44'/60'/0'/0/{index}）
+    Hd {
+        #[serde(default = "default_mnemonic_env")]
+        mnemonic_env: String,
+        #[serde(default)]
+        index: u32,
+        /// 自定义派生路径，设置后忽略 index
+        #[serde(default)]
+        derivation_path: Option<String>,
+    },
+}
+
+fn default_private_key_env() -> String {
+    "PRIVATE_KEY".to_string()
+}
+
+fn default_keystore_password_env() -> String {
+    "KEYSTORE_PASSWORD".to_string()
+}
+
+fn default_mnemonic_env() -> String {
+    "MNEMONIC".to_string()
+}
+
+/// 加价重发配置：确认超时后以相同 nonce、更高费用重新广播
+#[derive(Debug, Deserialize, Clone)]
+pub struct SpeedUpConfig {
+    /// 最多加价次数，0 表示不加价（超时直接返回错误）
+    #[serde(default)]
+    pub max_speed_up_attempts: u32,
+    /// max_fee_per_gas 的绝对上限（gwei），加价后超过该值即停止
+    #[serde(default = "default_max_bump_gwei")]
+    pub max_bump_gwei: u64,
+    /// 每次加价的比例（百分比），节点替换交易通常要求至少 110
+    #[serde(default = "default_bump_percent")]
+    pub bump_percent: u64,
+}
+
+fn default_max_bump_gwei() -> u64 {
+    500
+}
+
+fn default_bump_percent() -> u64 {
+    125
+}
+
+impl Default for SpeedUpConfig {
+    fn default() -> Self {
+        Self {
+            max_speed_up_attempts: 0,
+            max_bump_gwei: default_max_bump_gwei(),
+            bump_percent: default_bump_percent(),
+        }
+    }
+}
+
+/// 区块时间戳校验配置
+#[derive(Debug, Deserialize, Clone)]
+pub struct TimestampCheckConfig {
+    /// 链的创世时间（秒），早于该时间的区块视为异常；默认 0 即只拦截负数
+    #[serde(default)]
+    pub genesis_timestamp: i64,
+    /// 允许区块时间超前本机时钟的最大秒数
+    #[serde(default = "default_max_future_drift_secs")]
+    pub max_future_drift_secs: u64,
+}
+
+fn default_max_future_drift_secs() -> u64 {
+    300
+}
+
+impl Default for TimestampCheckConfig {
+    fn default() -> Self {
+        Self {
+            genesis_timestamp: 0,
+            max_future_drift_secs: default_max_future_drift_secs(),
+        }
+    }
+}
+
+/// 两阶段转账状态配置
+///
+/// 开启后，距链头不足 `finality_depth` 的区块中成功的转账以 status = 2（确认中）入库，
+/// 后台任务每 `interval_secs` 秒将已超过该深度的转账更新为 status = 1（已确认）；
+/// 重组回滚时这些转账随区块一起删除。关闭时与之前一致，直接写入 status = 1
+#[derive(Debug, Deserialize, Clone)]
+pub struct TransferStatusConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// 最终确认深度（区块数）
+    #[serde(default = "default_transfer_finality_depth")]
+    pub finality_depth: u64,
+    /// 提升任务的执行间隔（秒）
+    #[serde(default = "default_transfer_status_interval_secs")]
+    pub interval_secs: u64,
+}
+
+fn default_transfer_finality_depth() -> u64 {
+    64
+}
+
+fn default_transfer_status_interval_secs() -> u64 {
+    30
+}
+
+impl Default for TransferStatusConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            finality_depth: default_transfer_finality_depth(),
+            interval_secs: default_transfer_status_interval_secs(),
+        }
+    }
+}
+
+/// 采样配置：只解析部分区块的转账，适用于只需统计样本的轻量部署
+///
+/// 未被采样的区块仍写入 eth_block（保持父哈希校验与断点续传），但不解析交易、不写入转账，
+/// 因此 eth_transfer 中的数据是有缺口的，不能用于余额核对或完整的地址历史
+#[derive(Debug, Deserialize, Clone)]
+pub struct SamplingConfig {
+    /// 每 N 个区块解析一个（区块号能被 N 整除时解析），1 表示不采样
+    #[serde(default = "default_sample_rate")]
+    pub sample_rate: u64,
+    /// 只解析交易数不少于该值的区块，0 表示不限制
+    #[serde(default)]
+    pub min_block_txs: usize,
+}
+
+fn default_sample_rate() -> u64 {
+    1
+}
+
+impl Default for SamplingConfig {
+    fn default() -> Self {
+        Self {
+            sample_rate: default_sample_rate(),
+            min_block_txs: 0,
+        }
+    }
+}
+
+impl SamplingConfig {
+    /// 该区块是否需要解析转账
+    pub fn is_sampled(&self, block_number: u64, tx_count: usize) -> bool {
+        block_number % self.sample_rate.max(1) == 0 && tx_count >= self.min_block_txs
+    }
+}
+
+/// 合约部署跟踪配置
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct DeploymentTrackingConfig {
+    /// 监控地址（address.toml）发起合约创建交易时写入 contract_deployment 表，默认关闭
+    #[serde(default)]
+    pub enabled: bool,
+    /// 工厂部署者：这些地址部署的合约同样被记录，并自动加入监控合约列表
+    #[serde(default)]
+    pub auto_monitor_deployers: HashSet<H160>,
+    /// 通过事件创建子合约的工厂（不要求开启 enabled）
+    #[serde(default)]
+    pub factories: Vec<FactoryConfig>,
+}
+
+impl DeploymentTrackingConfig {
+    /// 其部署 / 创建的合约需要自动加入监控列表的地址：工厂部署者与工厂合约
+    pub fn auto_monitor_sources(&self) -> HashSet<H160> {
+        self.auto_monitor_deployers
+            .iter()
+            .copied()
+            .chain(self.factories.iter().map(|f| f.address))
+            .collect()
+    }
+}
+
+/// 通过事件创建子合约的工厂（如 Uniswap V2 PairCreated、V3 PoolCreated）
+///
+/// 工厂发出该事件时，子合约写入 contract_deployment（deployer 为工厂地址）并自动加入监控合约列表，
+/// 重启后从 contract_deployment 恢复：
+///
+/// ```toml
+/// # PairCreated(address indexed token0, address indexed token1, address pair, uint256)
+/// [[ethereum.deployment_tracking.factories]]
+/// name = "uniswap_v2"
+/// address = "0x5C69bEe701ef814a2B6a3EDD4B1652CB9cc5aA6f"
+/// topic = "0x0d3648bd0f6ba80134a33ba9275ac585d9d315f0ad8355cddefde31afa28d0e9"
+/// child = { data = 0 }
+///
+/// # PoolCreated(address indexed token0, address indexed token1, uint24 indexed fee, int24 tickSpacing, address pool)
+/// [[ethereum.deployment_tracking.factories]]
+/// name = "uniswap_v3"
+/// address = "0x1F98431c8aD98523631AE4a59f267346ea31F984"
+/// topic = "0x783cca1c0412dd0d695e784568c96da2e9c22ff989357a2e8b1d9b2b4e6b7118"
+/// child = { data = 1 }
+/// ```
+#[derive(Debug, Deserialize, Clone)]
+pub struct FactoryConfig {
+    pub name: String,
+    pub address: H160,
+    /// 创建事件的 topic0
+    pub topic: H256,
+    /// 子合约地址在事件中的位置
+    pub child: AddressSource,
+}
+
+/// 限流退避配置：429 与普通错误分开处理，等待更久，并让频繁限流的节点暂时退出轮询
+#[derive(Debug, Deserialize, Clone)]
+pub struct RateLimitConfig {
+    /// 首次限流的等待时间（毫秒），之后每次翻倍；服务端返回 Retry-After 时以其为准
+    #[serde(default = "default_rate_limit_base_delay_ms")]
+    pub base_delay_ms: u64,
+    /// 单次等待上限（毫秒），同样约束 Retry-After
+    #[serde(default = "default_rate_limit_max_delay_ms")]
+    pub max_delay_ms: u64,
+    /// 同一节点连续限流达到该次数后进入冷却，0 表示不冷却
+    #[serde(default = "default_rate_limit_cooldown_after")]
+    pub cooldown_after: u32,
+    /// 冷却时长（秒），期间轮询跳过该节点（所有节点都在冷却时仍会使用）
+    #[serde(default = "default_rate_limit_cooldown_secs")]
+    pub cooldown_secs: u64,
+}
+
+fn default_rate_limit_base_delay_ms() -> u64 {
+    5_000
+}
+
+fn default_rate_limit_max_delay_ms() -> u64 {
+    60_000
+}
+
+fn default_rate_limit_cooldown_after() -> u32 {
+    3
+}
+
+fn default_rate_limit_cooldown_secs() -> u64 {
+    30
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        Self {
+            base_delay_ms: default_rate_limit_base_delay_ms(),
+            max_delay_ms: default_rate_limit_max_delay_ms(),
+            cooldown_after: default_rate_limit_cooldown_after(),
+            cooldown_secs: default_rate_limit_cooldown_secs(),
+        }
+    }
+}
+
+/// 历史区块缓存配置（默认关闭）
+#[derive(Debug, Deserialize, Clone)]
+pub struct BlockCacheConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// 最多缓存的区块数
+    #[serde(default = "default_block_cache_capacity")]
+    pub capacity: usize,
+    /// 缓存过期时间（秒），0 表示不过期
+    #[serde(default)]
+    pub ttl_secs: u64,
+    /// 最终性深度：距链头小于该深度的区块存在重组风险，不缓存
+    #[serde(default = "default_finality_depth")]
+    pub finality_depth: u64,
+}
+
+fn default_block_cache_capacity() -> usize {
+    1024
+}
+
+fn default_finality_depth() -> u64 {
+    64
+}
+
+impl Default for BlockCacheConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            capacity: default_block_cache_capacity(),
+            ttl_secs: 0,
+            finality_depth: default_finality_depth(),
+        }
+    }
+}
+
+/// 交易发送配置：构造 TxService / GasService / 签名器时读取，发送行为只需修改这里
+#[derive(Debug, Deserialize, Clone)]
+pub struct TxConfig {
+    /// 调用方未传 TxOptions 时的默认优先级（Low / Normal / High / Urgent）
+    #[serde(default = "default_tx_priority")]
+    pub default_priority: TxPriority,
+    /// 全局 tip 调整百分比（100 = 无调整，110 = +10%）
+    #[serde(default = "default_base_tip_percent")]
+    pub base_tip_percent: u128,
+    /// max_fee_per_gas 的绝对上限（gwei），未配置时只按优先级倍率限制
+    #[serde(default)]
+    pub max_fee_cap_gwei: Option<u64>,
+    /// gas limit 在估算值上的放大百分比（120 = +20%）
+    #[serde(default = "default_gas_limit_buffer")]
+    pub gas_limit_buffer: u64,
+    /// 默认确认数，未配置时按交易优先级取值（Low 3，其余 1）
+    #[serde(default)]
+    pub confirmations: Option<u64>,
+    /// 等待确认的默认超时（秒）
+    #[serde(default = "default_tx_timeout_secs")]
+    pub timeout_secs: u64,
+    /// 默认确认策略，未配置时为 Blocks(确认数)
+    #[serde(default)]
+    pub confirmation_target: Option<ConfirmationTarget>,
+    /// 广播前是否校验余额
+    #[serde(default = "default_check_balance")]
+    pub check_balance: bool,
+    /// calldata 的字节数上限，超过时在模拟前返回 Validation 错误；各链 / 节点的限制不同，按部署的链配置
+    #[serde(default = "default_max_calldata_bytes")]
+    pub max_calldata_bytes: usize,
+    /// 确认超时后的加价重发（speed-up）策略
+    #[serde(default)]
+    pub speed_up: Option<SpeedUpConfig>,
+    /// 交易签名后端，未配置时不创建签名器（纯索引部署）
+    #[serde(default)]
+    pub signer: Option<SignerConfig>,
+}
+
+fn default_tx_priority() -> TxPriority {
+    TxPriority::Normal
+}
+
+fn default_base_tip_percent() -> u128 {
+    100
+}
+
+fn default_gas_limit_buffer() -> u64 {
+    120
+}
+
+fn default_tx_timeout_secs() -> u64 {
+    300
+}
+
+fn default_check_balance() -> bool {
+    true
+}
+
+/// geth 默认的交易大小上限为 128 KiB
+fn default_max_calldata_bytes() -> usize {
+    128 * 1024
+}
+
+impl Default for TxConfig {
+    fn default() -> Self {
+        Self {
+            default_priority: default_tx_priority(),
+            base_tip_percent: default_base_tip_percent(),
+            max_fee_cap_gwei: None,
+            gas_limit_buffer: default_gas_limit_buffer(),
+            confirmations: None,
+            timeout_secs: default_tx_timeout_secs(),
+            confirmation_target: None,
+            check_balance: default_check_balance(),
+            max_calldata_bytes: default_max_calldata_bytes(),
+            speed_up: None,
+            signer: None,
+        }
+    }
+}
+
+impl TxConfig {
+    /// 校验取值范围，加载配置时调用
+    pub fn validate(&self) -> Result<(), String> {
+        if self.base_tip_percent == 0 {
+            return Err("tx.base_tip_percent 必须大于 0".to_string());
+        }
+        if self.max_fee_cap_gwei == Some(0) {
+            return Err("tx.max_fee_cap_gwei 必须大于 0".to_string());
+        }
+        if self.gas_limit_buffer < 100 {
+            return Err(format!(
+                "tx.gas_limit_buffer 不能小于 100（当前 {}），否则 gas limit 低于估算值",
+                self.gas_limit_buffer
+            ));
+        }
+        if self.confirmations == Some(0) {
+            return Err("tx.confirmations 必须大于 0".to_string());
+        }
+        if self.timeout_secs == 0 {
+            return Err("tx.timeout_secs 必须大于 0".to_string());
+        }
+        if self.max_calldata_bytes == 0 {
+            return Err("tx.max_calldata_bytes 必须大于 0".to_string());
+        }
+        if let Some(speed_up) = &self.speed_up {
+            if speed_up.max_bump_gwei == 0 {
+                return Err("tx.speed_up.max_bump_gwei 必须大于 0".to_string());
+            }
+            if speed_up.max_speed_up_attempts > 0 && speed_up.bump_percent < 110 {
+                return Err(format!(
+                    "tx.speed_up.bump_percent 不能小于 110（当前 {}），节点会拒绝替换交易",
+                    speed_up.bump_percent
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
 impl Config {
+    /// 依次加载 config/default.toml、config/{APP_ENVIRONMENT}.toml，最后由 `APP__` 前缀的环境变量覆盖
+    /// 嵌套字段用 `__` 分隔，如 `APP__DATABASE__PASSWORD` 覆盖 `[database] password`
     pub fn load() -> Result<Self, ConfigError> {
         let environment = std::env::var("APP_ENVIRONMENT").unwrap_or_else(|_| "development".into());
 
         config::Config::builder()
             .add_source(File::with_name("config/default"))
             .add_source(File::with_name(&format!("config/{}", environment)).required(false))
+            .add_source(
+                Environment::with_prefix("APP")
+                    .prefix_separator("__")
+                    .separator("__")
+                    .try_parsing(true),
+            )
             .build()?
-            .try_deserialize()
+            .try_deserialize::<Config>()
+            .and_then(|mut config| {
+                // 兼容旧配置：[ethereum] 下的 signer / speed_up 在 [tx] 未配置时沿用
+                if config.tx.signer.is_none() {
+                    config.tx.signer = config.ethereum.signer.take();
+                }
+                if config.tx.speed_up.is_none() {
+                    config.tx.speed_up = config.ethereum.speed_up.take();
+                }
+                config.tx.validate().map_err(ConfigError::Message)?;
+                if config.startup_timeout_secs == 0 {
+                    return Err(ConfigError::Message("startup_timeout_secs 必须大于 0".to_string()));
+                }
+                Ok(config)
+            })
     }
 }
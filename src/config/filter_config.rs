@@ -1,25 +1,67 @@
+use crate::config::TransferEventConfig;
 use crate::{log_error, log_info};
 use arc_swap::ArcSwap;
 use ethers_core::types::H160;
 use notify::{Config as NotifyConfig, RecursiveMode, Watcher};
 use serde::Deserialize;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::path::Path;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
+
+/// 收款白名单文件，可选
+const ALLOWED_RECIPIENTS_PATH: &str = "config/allowed_recipients.toml";
+/// 垃圾 / 诈骗代币合约黑名单文件，可选
+const BLOCKED_CONTRACTS_PATH: &str = "config/blocked_contracts.toml";
+/// 热重载的类转账事件定义文件，可选
+const TRANSFER_EVENTS_PATH: &str = "config/transfer_events.toml";
+/// 按合约覆盖的确认数，可选
+const CONTRACT_CONFIRMATIONS_PATH: &str = "config/contract_confirmations.toml";
 
 #[derive(Debug, Deserialize)]
 struct AddressList {
     addresses: Vec<String>,
 }
+
+#[derive(Debug, Deserialize)]
+struct TransferEventList {
+    #[serde(default)]
+    events: Vec<TransferEventConfig>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ContractConfirmationList {
+    #[serde(default)]
+    contracts: Vec<ContractConfirmation>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ContractConfirmation {
+    address: H160,
+    confirmations: u64,
+}
+
+#[derive(Clone)]
 pub struct FilterConfig {
     pub contracts: HashSet<H160>,
     pub addresses: HashSet<H160>,
+    /// TxService 允许的收款地址（config/allowed_recipients.toml），文件不存在时为 None，表示不限制
+    pub allowed_recipients: Option<HashSet<H160>>,
+    /// 不索引的代币合约（config/blocked_contracts.toml），优先级高于 contracts：同时出现时按屏蔽处理
+    pub blocked_contracts: HashSet<H160>,
+    /// config/transfer_events.toml 中的类转账事件，与 `ethereum.transfer_events` 合并生效
+    pub transfer_events: Arc<Vec<TransferEventConfig>>,
+    /// 合约 → 转账入库前所需的确认数（config/contract_confirmations.toml）；
+    /// 只有大于全局 `delay` 的值才有效果，未列出的合约按 `delay` 入库。
+    /// 这些合约的转账比区块本身晚 (确认数 - delay) 个区块左右入库（队列模式同样生效）
+    pub contract_confirmations: HashMap<H160, u64>,
 }
 
 pub struct FilterConfigContainer {
     // 使用 ArcSwap 存储当前的配置，支持无锁替换
     current: ArcSwap<FilterConfig>,
+    /// 运行时加入的监控合约（如工厂部署的新合约），热重载配置文件后合并回 contracts
+    extra_contracts: Mutex<HashSet<H160>>,
 }
 
 impl FilterConfigContainer {
@@ -27,6 +69,7 @@ impl FilterConfigContainer {
         let initial = Arc::new(FilterConfig::load());
         let container = Arc::new(Self {
             current: ArcSwap::from(initial),
+            extra_contracts: Mutex::new(HashSet::new()),
         });
 
         // 启动后台监听线程
@@ -43,6 +86,20 @@ impl FilterConfigContainer {
         self.current.load_full()
     }
 
+    /// 运行时加入监控合约，返回新增的数量；配置文件热重载后仍然保留
+    pub fn add_contracts(&self, contracts: impl IntoIterator<Item = H160>) -> usize {
+        let mut extra = self.extra_contracts.lock().unwrap_or_else(|e| e.into_inner());
+        let added: Vec<H160> = contracts.into_iter().filter(|c| extra.insert(*c)).collect();
+        if !added.is_empty() {
+            self.current.rcu(|current| {
+                let mut next = FilterConfig::clone(current);
+                next.contracts.extend(added.iter().copied());
+                next
+            });
+        }
+        added.len()
+    }
+
     fn watch_config(&self) {
         let (tx, rx) = std::sync::mpsc::channel();
 
@@ -63,8 +120,11 @@ impl FilterConfigContainer {
                     // 仅当文件修改或重命名时触发加载
                     if event.kind.is_modify() || event.kind.is_create() {
                         log_info!("🔄 检测到配置变动，正在重新加载地址库...");
-                        let new_config = Arc::new(FilterConfig::load());
-                        self.current.store(new_config);
+                        let mut new_config = FilterConfig::load();
+                        let extra = self.extra_contracts.lock().unwrap_or_else(|e| e.into_inner());
+                        new_config.contracts.extend(extra.iter().copied());
+                        self.current.store(Arc::new(new_config));
+                        drop(extra);
                         log_info!("✅ 地址库已动态更新！");
                     }
                 }
@@ -78,12 +138,73 @@ impl FilterConfig {
     pub fn load() -> Self {
         let contracts = Self::load_file("config/contracts.toml");
         let addresses = Self::load_file("config/address.toml");
+        let allowed_recipients = Path::new(ALLOWED_RECIPIENTS_PATH)
+            .exists()
+            .then(|| Self::load_file(ALLOWED_RECIPIENTS_PATH));
+        let blocked_contracts = if Path::new(BLOCKED_CONTRACTS_PATH).exists() {
+            Self::load_file(BLOCKED_CONTRACTS_PATH)
+        } else {
+            HashSet::new()
+        };
         Self {
             contracts,
             addresses,
+            allowed_recipients,
+            blocked_contracts,
+            transfer_events: Arc::new(Self::load_transfer_events()),
+            contract_confirmations: Self::load_contract_confirmations(),
+        }
+    }
+
+    /// 文件不存在时为空；解析失败时记录错误并按空处理
+    fn load_contract_confirmations() -> HashMap<H160, u64> {
+        let Ok(content) = fs::read_to_string(CONTRACT_CONFIRMATIONS_PATH) else {
+            return HashMap::new();
+        };
+        match toml::from_str::<ContractConfirmationList>(&content) {
+            Ok(list) => list
+                .contracts
+                .into_iter()
+                .map(|c| (c.address, c.confirmations))
+                .collect(),
+            Err(e) => {
+                log_error!("解析 {} 失败，忽略按合约配置的确认数: {}", CONTRACT_CONFIRMATIONS_PATH, e);
+                HashMap::new()
+            }
+        }
+    }
+
+    /// 合约单独配置的确认数，未配置时为 None
+    pub fn required_confirmations(&self, contract: &H160) -> Option<u64> {
+        self.contract_confirmations.get(contract).copied()
+    }
+
+    /// 文件不存在时为空；解析失败时记录错误并按空处理，避免热重载时因格式错误中断索引
+    fn load_transfer_events() -> Vec<TransferEventConfig> {
+        let Ok(content) = fs::read_to_string(TRANSFER_EVENTS_PATH) else {
+            return vec![];
+        };
+        match toml::from_str::<TransferEventList>(&content) {
+            Ok(list) => list.events,
+            Err(e) => {
+                log_error!("解析 {} 失败，忽略文件中的事件定义: {}", TRANSFER_EVENTS_PATH, e);
+                vec![]
+            }
         }
     }
 
+    /// 合约是否在黑名单中
+    pub fn is_contract_blocked(&self, contract: &H160) -> bool {
+        self.blocked_contracts.contains(contract)
+    }
+
+    /// 收款地址是否在白名单中；未配置白名单时一律允许
+    pub fn is_recipient_allowed(&self, to: &H160) -> bool {
+        self.allowed_recipients
+            .as_ref()
+            .is_none_or(|allowed| allowed.contains(to))
+    }
+
     fn load_file(path: &str) -> HashSet<H160> {
         let content = fs::read_to_string(path).unwrap_or_else(|e| {
             panic!(
@@ -99,3 +220,24 @@ impl FilterConfig {
             .collect()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn blocklist_takes_precedence_over_monitored_contracts() {
+        let token = H160::repeat_byte(0xaa);
+        let config = FilterConfig {
+            contracts: HashSet::from([token]),
+            addresses: HashSet::new(),
+            allowed_recipients: None,
+            blocked_contracts: HashSet::from([token]),
+            transfer_events: Arc::new(vec![]),
+            contract_confirmations: HashMap::new(),
+        };
+        assert!(config.contracts.contains(&token));
+        assert!(config.is_contract_blocked(&token));
+        assert!(!config.is_contract_blocked(&H160::repeat_byte(0xbb)));
+    }
+}
@@ -1,24 +1,72 @@
-use crate::config::DatabaseConfig;
+use crate::config::{CommitRetryConfig, DatabaseConfig};
 use crate::errors::error::AppError;
-use diesel_async::AsyncConnection;
+use diesel_async::{AsyncConnection, RunQueryDsl};
 use diesel_async::pg::AsyncPgConnection;
-use diesel_async::pooled_connection::AsyncDieselConnectionManager;
+use bb8::CustomizeConnection;
+use diesel_async::pooled_connection::{AsyncDieselConnectionManager, PoolError};
 use diesel_async::pooled_connection::bb8::Pool;
 use diesel_async::scoped_futures::ScopedFutureExt;
+use diesel_async::AsyncMigrationHarness;
+use diesel_migrations::{EmbeddedMigrations, MigrationHarness, embed_migrations};
 use futures_util::future::BoxFuture;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
 
 // 定义异步池类型
 pub type AsyncDbPool = Pool<AsyncPgConnection>;
 
-pub async fn create_async_db_pool(config: &DatabaseConfig) -> Result<AsyncDbPool, AppError> {
-    let database_url = format!(
+/// 编译期内嵌的 migrations/ 目录
+pub const MIGRATIONS: EmbeddedMigrations = embed_migrations!("migrations");
+
+fn database_url(config: &DatabaseConfig) -> String {
+    format!(
         "postgresql://{}:{}@{}:{}/{}",
         config.username, config.password, config.host, config.port, config.database_name
-    );
+    )
+}
 
-    let manager = AsyncDieselConnectionManager::<AsyncPgConnection>::new(database_url);
-    let pool = Pool::builder()
-        .max_size(config.max_connections as u32)
+/// 执行尚未应用的内嵌 migrations，返回本次应用的版本数
+/// 使用独立连接（而不是连接池），配置了 db_schema 时先创建 schema 并设置 search_path
+pub async fn run_migrations(config: &DatabaseConfig) -> Result<usize, AppError> {
+    let mut conn = AsyncPgConnection::establish(&database_url(config))
+        .await
+        .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+    if let Some(schema) = &config.db_schema {
+        let customizer = SearchPathCustomizer::new(schema)?;
+        diesel::sql_query(format!("CREATE SCHEMA IF NOT EXISTS \"{}\"", schema))
+            .execute(&mut conn)
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+        diesel::sql_query(&customizer.statement)
+            .execute(&mut conn)
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+    }
+
+    // 迁移执行是同步的（内部 block_in_place），需要多线程 runtime
+    let mut harness = AsyncMigrationHarness::new(conn);
+    let applied = harness
+        .run_pending_migrations(MIGRATIONS)
+        .map_err(|e| AppError::DatabaseError(format!("执行数据库迁移失败: {}", e)))?;
+    for version in &applied {
+        tracing::info!("已应用数据库迁移 {}", version);
+    }
+    Ok(applied.len())
+}
+
+pub async fn create_async_db_pool(
+    config: &DatabaseConfig,
+    max_size: u32,
+) -> Result<AsyncDbPool, AppError> {
+    let manager = AsyncDieselConnectionManager::<AsyncPgConnection>::new(database_url(config));
+    let mut builder = Pool::builder().max_size(max_size);
+    if let Some(schema) = &config.db_schema {
+        builder = builder.connection_customizer(Box::new(SearchPathCustomizer::new(schema)?));
+        tracing::info!("数据库连接 search_path 设置为 {}", schema);
+    }
+    let pool = builder
         .build(manager)
         .await
         .map_err(|e| AppError::Internal(e.to_string()))?;
@@ -26,6 +74,42 @@ pub async fn create_async_db_pool(config: &DatabaseConfig) -> Result<AsyncDbPool
     Ok(pool)
 }
 
+/// 新建连接时设置 search_path，使 diesel 生成的无 schema 前缀表名解析到配置的 schema
+#[derive(Debug)]
+struct SearchPathCustomizer {
+    statement: String,
+}
+
+impl SearchPathCustomizer {
+    fn new(schema: &str) -> Result<Self, AppError> {
+        // search_path 无法参数化绑定，只接受普通标识符，防止拼接注入
+        let valid = !schema.is_empty()
+            && schema.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+            && !schema.starts_with(|c: char| c.is_ascii_digit());
+        if !valid {
+            return Err(AppError::Validation(format!("无效的 db_schema: {}", schema)));
+        }
+        Ok(Self {
+            statement: format!("SET search_path TO \"{}\", public", schema),
+        })
+    }
+}
+
+impl CustomizeConnection<AsyncPgConnection, PoolError> for SearchPathCustomizer {
+    fn on_acquire<'a>(
+        &'a self,
+        conn: &'a mut AsyncPgConnection,
+    ) -> Pin<Box<dyn Future<Output = Result<(), PoolError>> + Send + 'a>> {
+        Box::pin(async move {
+            diesel::sql_query(&self.statement)
+                .execute(conn)
+                .await
+                .map(|_| ())
+                .map_err(PoolError::QueryError)
+        })
+    }
+}
+
 #[async_trait::async_trait]
 pub trait TransactionExecutor: Send + Sync {
     /// 执行异步事务的闭包接口
@@ -37,7 +121,99 @@ pub trait TransactionExecutor: Send + Sync {
 }
 
 pub struct DbService {
+    /// 同步、事务写入使用的连接池
     pub pool: AsyncDbPool,
+    /// 只读查询（HTTP API 等）使用的连接池；单池模式下与 `pool` 是同一个池
+    pub query_pool: AsyncDbPool,
+    /// `execute_tx_retry` 的重试策略
+    commit_retry: CommitRetryConfig,
+    /// 累计的事务重试次数
+    commit_retries: AtomicU64,
+}
+
+impl DbService {
+    /// 单池模式：同步与查询共用一个连接池
+    pub fn new(pool: AsyncDbPool) -> Self {
+        Self {
+            query_pool: pool.clone(),
+            pool,
+            commit_retry: CommitRetryConfig::default(),
+            commit_retries: AtomicU64::new(0),
+        }
+    }
+
+    /// 查询使用独立的连接池
+    pub fn with_query_pool(mut self, query_pool: AsyncDbPool) -> Self {
+        self.query_pool = query_pool;
+        self
+    }
+
+    /// 设置事务遇到暂时性错误时的重试策略
+    pub fn with_commit_retry(mut self, commit_retry: CommitRetryConfig) -> Self {
+        self.commit_retry = commit_retry;
+        self
+    }
+
+    /// 启动以来因暂时性错误重试事务的次数
+    pub fn commit_retries(&self) -> u64 {
+        AtomicU64::load(&self.commit_retries, Ordering::Relaxed)
+    }
+
+    /// 与 `execute_tx` 相同，但遇到暂时性错误（序列化失败、死锁、连接断开、取连接失败）时
+    /// 回滚后按指数退避重新执行整个事务；闭包需可重复调用，其余错误直接返回
+    pub async fn execute_tx_retry<F, T>(&self, f: F) -> Result<T, AppError>
+    where
+        T: Send,
+        F: for<'a> Fn(&'a mut AsyncPgConnection) -> BoxFuture<'a, Result<T, AppError>> + Send + Sync,
+    {
+        let mut attempt = 0;
+        loop {
+            let result = match self.pool.get().await {
+                Ok(mut conn) => conn
+                    .transaction::<T, AppError, _>(|c| f(c).scope_boxed())
+                    .await
+                    .map_err(|e| {
+                        let retryable = e.is_retryable_db();
+                        (e, retryable)
+                    }),
+                Err(e) => Err((AppError::Internal(e.to_string()), true)),
+            };
+            match result {
+                Ok(value) => return Ok(value),
+                Err((e, true)) if attempt < self.commit_retry.max_retries => {
+                    let backoff = self
+                        .commit_retry
+                        .backoff_ms
+                        .saturating_mul(1 << attempt.min(10));
+                    attempt += 1;
+                    self.commit_retries.fetch_add(1, Ordering::Relaxed);
+                    tracing::warn!(
+                        "数据库事务遇到暂时性错误，{}ms 后第 {}/{} 次重试: {}",
+                        backoff,
+                        attempt,
+                        self.commit_retry.max_retries,
+                        e
+                    );
+                    tokio::time::sleep(Duration::from_millis(backoff)).await;
+                }
+                Err((e, _)) => return Err(e),
+            }
+        }
+    }
+
+    /// 健康检查：从池中取连接并执行 SELECT 1
+    pub async fn health(&self) -> Result<(), AppError> {
+        let mut conn = self
+            .pool
+            .get()
+            .await
+            .map_err(|e| AppError::Internal(e.to_string()))?;
+        diesel::sql_query("SELECT 1")
+            .execute(&mut conn)
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+        Ok(())
+    }
 }
 
 #[async_trait::async_trait]
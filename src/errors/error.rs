@@ -1,6 +1,8 @@
 use diesel::result::Error as DieselError;
-use ethers_providers::ProviderError;
+use ethers_core::abi::{ParamType, decode};
+use ethers_providers::{ProviderError, RpcError};
 use redis::RedisError;
+use std::time::Duration;
 use thiserror::Error;
 use tokio::task::JoinError;
 
@@ -79,6 +81,34 @@ pub enum AppError {
     #[error("区块链RPC错误: {0}")]
     BlockchainError(String),
 
+    /// 节点不支持该 RPC 方法（JSON-RPC -32601）
+    #[error("RPC 方法不受支持: {0}")]
+    MethodNotSupported(String),
+
+    /// 节点限流（HTTP 429 / JSON-RPC -32005），重试耗尽后仍被限流
+    #[error("RPC 被限流: {0}")]
+    RateLimited(String),
+
+    /// 调用或交易执行被 revert（JSON-RPC code 3 / "execution reverted"），reason 为解码后的 revert 原因
+    #[error("执行被 revert: {reason}")]
+    Reverted { reason: String },
+
+    /// RPC 参数无效（JSON-RPC -32602），重试没有意义
+    #[error("RPC 参数无效: {0}")]
+    RpcInvalidParams(String),
+
+    /// 交易广播失败，节点未接收该交易（不在 mempool 中），nonce 与 idempotency_key 可以释放
+    #[error("交易广播失败: {0}")]
+    BroadcastFailed(String),
+
+    /// 交易已广播，但在超时时间内未确认（可能仍在 mempool 中）
+    #[error("交易确认超时: {0}")]
+    ConfirmationTimeout(String),
+
+    /// 相同 idempotency_key 的交易已广播、尚未确认，携带其交易哈希
+    #[error("交易已广播，等待确认: {0}")]
+    TxPending(String),
+
     #[error("无效的地址: {0}")]
     InvalidAddress(String),
 
@@ -88,10 +118,46 @@ pub enum AppError {
         local: String,
         network: String,
     },
+
+    /// 启动阶段的网络步骤超时（数据库 / Redis / RPC 不可达或地址配置错误）
+    #[error("启动步骤「{step}」超过 {secs} 秒未完成，请检查对应服务的地址与网络连通性")]
+    StartupTimeout { step: String, secs: u64 },
+
+    /// 同步某个区块失败，携带区块号与失败阶段
+    #[error(transparent)]
+    Sync(#[from] SyncError),
 }
 
+/// 由仓库层转成字符串的数据库错误中，代表暂时性故障的消息片段（小写）
+const TRANSIENT_DB_MESSAGES: &[&str] = &[
+    "could not serialize access",
+    "deadlock detected",
+    "connection closed",
+    "server closed the connection",
+    "terminating connection",
+];
+
 impl AppError {
+    /// 是否为可重试的暂时性数据库错误（序列化失败、死锁、连接断开）
+    pub fn is_retryable_db(&self) -> bool {
+        use diesel::result::DatabaseErrorKind;
+        match self {
+            AppError::DatabaseQuery(DieselError::DatabaseError(
+                DatabaseErrorKind::SerializationFailure
+                | DatabaseErrorKind::ClosedConnection
+                | DatabaseErrorKind::UnableToSendCommand,
+                _,
+            )) => true,
+            AppError::DatabaseQuery(e) => is_transient_db_message(&e.to_string()),
+            AppError::DatabaseError(msg) => is_transient_db_message(msg),
+            _ => false,
+        }
+    }
+}
 
+fn is_transient_db_message(msg: &str) -> bool {
+    let msg = msg.to_lowercase();
+    TRANSIENT_DB_MESSAGES.iter().any(|m| msg.contains(m))
 }
 
 impl AppError {}
@@ -106,6 +172,46 @@ pub enum SyncError {
 
     #[error("连续失败次数过多: {count}")]
     TooManyFailures { count: u32 },
+
+    /// 获取区块失败（RPC 错误或节点尚未同步到该高度）
+    #[error("获取区块 {block} 失败: {error}")]
+    FetchBlock { block: u64, error: Box<AppError> },
+
+    /// 解析区块中的交易 / 日志失败
+    #[error("解析区块 {block} 失败: {error}")]
+    ParseBlock { block: u64, error: Box<AppError> },
+
+    /// 区块与转账写入数据库失败，transfers 为本次要提交的转账笔数
+    #[error("提交区块 {block} 失败（已解析 {transfers} 笔转账）: {error}")]
+    CommitBlock {
+        block: u64,
+        transfers: usize,
+        error: Box<AppError>,
+    },
+}
+
+impl SyncError {
+    /// 用于 `map_err`：把获取阶段的错误包装为 `FetchBlock`
+    pub fn fetch(block: u64) -> impl FnOnce(AppError) -> AppError {
+        move |error| SyncError::FetchBlock { block, error: Box::new(error) }.into()
+    }
+
+    /// 用于 `map_err`：把解析阶段的错误包装为 `ParseBlock`
+    pub fn parse(block: u64) -> impl FnOnce(AppError) -> AppError {
+        move |error| SyncError::ParseBlock { block, error: Box::new(error) }.into()
+    }
+
+    /// 用于 `map_err`：把提交阶段的错误包装为 `CommitBlock`
+    pub fn commit(block: u64, transfers: usize) -> impl FnOnce(AppError) -> AppError {
+        move |error| {
+            SyncError::CommitBlock {
+                block,
+                transfers,
+                error: Box::new(error),
+            }
+            .into()
+        }
+    }
 }
 
 impl AppError {
@@ -116,10 +222,116 @@ impl AppError {
 
 impl From<ProviderError> for AppError {
     fn from(err: ProviderError) -> Self {
+        if is_method_not_found(&err) {
+            return AppError::MethodNotSupported(err.to_string());
+        }
+        if is_rate_limited(&err) {
+            return AppError::RateLimited(err.to_string());
+        }
+        if is_reverted(&err) {
+            return AppError::Reverted {
+                reason: revert_reason(&err),
+            };
+        }
+        if is_invalid_params(&err) {
+            return AppError::RpcInvalidParams(err.to_string());
+        }
         AppError::ProviderError(err.to_string())
     }
 }
 
+/// 重试无法改变结果的错误：方法不存在、revert、参数无效
+pub fn is_non_retryable(err: &ProviderError) -> bool {
+    is_method_not_found(err) || is_reverted(err) || is_invalid_params(err)
+}
+
+/// 判断是否为执行 revert：JSON-RPC code 3（geth 带 revert data），或 message 中的 "execution reverted"
+pub fn is_reverted(err: &ProviderError) -> bool {
+    if err.as_error_response().is_some_and(|e| e.code == 3) {
+        return true;
+    }
+    err.to_string().to_lowercase().contains("execution reverted")
+}
+
+/// 判断是否为参数无效错误：标准错误码 -32602
+pub fn is_invalid_params(err: &ProviderError) -> bool {
+    err.as_error_response().is_some_and(|e| e.code == -32602)
+}
+
+/// 提取 revert 原因：error data 为标准 `Error(string)` 编码时解码出字符串，否则使用 message
+pub fn revert_reason(err: &ProviderError) -> String {
+    let Some(resp) = err.as_error_response() else {
+        return err.to_string();
+    };
+    let decoded = resp
+        .data
+        .as_ref()
+        .and_then(|d| d.as_str())
+        .and_then(|s| hex::decode(s.trim_start_matches("0x")).ok())
+        .filter(|bytes| bytes.len() > 4 && bytes[..4] == ERROR_STRING_SELECTOR)
+        .and_then(|bytes| decode(&[ParamType::String], &bytes[4..]).ok())
+        .and_then(|tokens| tokens.into_iter().next())
+        .and_then(|token| token.into_string());
+    decoded.unwrap_or_else(|| resp.message.clone())
+}
+
+/// `Error(string)` 的函数选择器
+const ERROR_STRING_SELECTOR: [u8; 4] = [0x08, 0xc3, 0x79, 0xa0];
+
+/// 判断是否为“方法不存在”错误：标准错误码 -32601，部分节点只在 message 中体现
+pub fn is_method_not_found(err: &ProviderError) -> bool {
+    if err.as_error_response().is_some_and(|e| e.code == -32601) {
+        return true;
+    }
+    let msg = err.to_string().to_lowercase();
+    msg.contains("method not found")
+        || msg.contains("does not exist/is not available")
+        || msg.contains("method not supported")
+}
+
+/// 判断是否为限流错误：HTTP 429（Too Many Requests）、JSON-RPC 429 / -32005，或 message 中的限流字样
+pub fn is_rate_limited(err: &ProviderError) -> bool {
+    if err
+        .as_error_response()
+        .is_some_and(|e| e.code == 429 || e.code == -32005)
+    {
+        return true;
+    }
+    let msg = err.to_string().to_lowercase();
+    msg.contains("too many requests")
+        || msg.contains("rate limit")
+        || msg.contains("rate-limit")
+        || (msg.contains("exceeded") && msg.contains("capacity"))
+}
+
+/// 提取服务端建议的等待时间：error data 中的 `retry_after` / `backoff_seconds`，
+/// 或 message 中的 "retry after N" / "try again in Ns"（单位秒）；结果不超过 `max`，
+/// 无法表示为 Duration 的值（inf、超大数）视为未给出
+pub fn rate_limit_retry_after(err: &ProviderError, max: Duration) -> Option<Duration> {
+    let to_duration = |secs: f64| Duration::try_from_secs_f64(secs.max(0.0)).ok();
+    if let Some(data) = err.as_error_response().and_then(|e| e.data.as_ref()) {
+        let secs = ["retry_after", "retryAfter", "backoff_seconds"]
+            .iter()
+            .find_map(|k| data.get(k).and_then(|v| v.as_f64()));
+        if let Some(secs) = secs {
+            return to_duration(secs).map(|d| d.min(max));
+        }
+    }
+    let msg = err.to_string().to_lowercase();
+    ["retry-after:", "retry after", "try again in"]
+        .iter()
+        .find_map(|pat| {
+            let rest = msg.split_once(pat)?.1.trim_start();
+            let num: String = rest
+                .chars()
+                .take_while(|c| c.is_ascii_digit() || *c == '.')
+                .collect();
+            num.parse::<f64>().ok()
+        })
+        .and_then(to_duration)
+        .map(|d| d.min(max))
+}
+
 impl From<anyhow::Error> for AppError {
     fn from(err: anyhow::Error) -> Self {
         AppError::Internal(err.to_string())
@@ -153,3 +365,48 @@ impl From<std::num::ParseFloatError> for AppError {
         AppError::Internal(err.to_string())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers_providers::{JsonRpcError, MockError};
+
+    const MAX: Duration = Duration::from_secs(60);
+
+    fn rate_limited(message: &str, data: Option<serde_json::Value>) -> ProviderError {
+        ProviderError::JsonRpcClientError(Box::new(MockError::JsonRpcError(JsonRpcError {
+            code: 429,
+            message: message.to_string(),
+            data,
+        })))
+    }
+
+    #[test]
+    fn retry_after_is_read_from_data_and_message() {
+        let err = rate_limited("Too Many Requests", Some(serde_json::json!({ "retry_after": 2.5 })));
+        assert_eq!(rate_limit_retry_after(&err, MAX), Some(Duration::from_millis(2_500)));
+        let err = rate_limited("rate limited, try again in 3s", None);
+        assert_eq!(rate_limit_retry_after(&err, MAX), Some(Duration::from_secs(3)));
+        let err = rate_limited("Too Many Requests", None);
+        assert_eq!(rate_limit_retry_after(&err, MAX), None);
+    }
+
+    #[test]
+    fn retry_after_is_clamped_to_max() {
+        let err = rate_limited("retry after 86400", None);
+        assert_eq!(rate_limit_retry_after(&err, MAX), Some(MAX));
+        let err = rate_limited("Too Many Requests", Some(serde_json::json!({ "retryAfter": 1e9 })));
+        assert_eq!(rate_limit_retry_after(&err, MAX), Some(MAX));
+    }
+
+    #[test]
+    fn unrepresentable_retry_after_does_not_panic() {
+        let err = rate_limited("Too Many Requests", Some(serde_json::json!({ "retry_after": 1e300 })));
+        assert_eq!(rate_limit_retry_after(&err, MAX), None);
+        // 超长数字串解析为 inf
+        let err = rate_limited(&format!("retry after {}", "9".repeat(400)), None);
+        assert_eq!(rate_limit_retry_after(&err, MAX), None);
+        let err = rate_limited("retry after -5", None);
+        assert_eq!(rate_limit_retry_after(&err, MAX), None);
+    }
+}
@@ -0,0 +1,65 @@
+use crate::errors::error::AppError;
+use crate::log_warn;
+use async_trait::async_trait;
+use std::fmt;
+
+/// 告警类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AlertKind {
+    SyncLag,
+    RpcFailures,
+    ReorgDepth,
+    LowBalance,
+}
+
+impl fmt::Display for AlertKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            AlertKind::SyncLag => "同步延迟",
+            AlertKind::RpcFailures => "RPC 连续失败",
+            AlertKind::ReorgDepth => "链重组",
+            AlertKind::LowBalance => "余额不足",
+        };
+        f.write_str(name)
+    }
+}
+
+/// 一条告警；`key` 用于去抖，同一类型下区分不同对象（如不同地址的余额告警）
+#[derive(Debug, Clone)]
+pub struct Alert {
+    pub kind: AlertKind,
+    pub key: String,
+    pub message: String,
+}
+
+impl Alert {
+    pub fn new(kind: AlertKind, message: impl Into<String>) -> Self {
+        Self {
+            kind,
+            key: kind.to_string(),
+            message: message.into(),
+        }
+    }
+
+    pub fn with_key(mut self, key: impl Into<String>) -> Self {
+        self.key = key.into();
+        self
+    }
+}
+
+/// 告警通知渠道
+#[async_trait]
+pub trait Alerter: Send + Sync {
+    async fn send(&self, alert: &Alert) -> Result<(), AppError>;
+}
+
+/// 只写日志的告警渠道
+pub struct LogAlerter;
+
+#[async_trait]
+impl Alerter for LogAlerter {
+    async fn send(&self, alert: &Alert) -> Result<(), AppError> {
+        log_warn!("🚨 [{}] {}", alert.kind, alert.message);
+        Ok(())
+    }
+}
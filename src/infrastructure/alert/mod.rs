@@ -0,0 +1,5 @@
+pub mod alerter;
+pub mod webhook;
+
+pub use alerter::{Alert, AlertKind, Alerter, LogAlerter};
+pub use webhook::WebhookAlerter;
@@ -0,0 +1,48 @@
+use super::alerter::{Alert, Alerter};
+use crate::errors::error::AppError;
+use async_trait::async_trait;
+use reqwest::header::CONTENT_TYPE;
+use std::time::Duration;
+
+/// Slack / Discord 风格的 webhook 告警
+/// 消息体同时带 `text`（Slack）与 `content`（Discord）字段，两种 webhook 都能直接接收
+pub struct WebhookAlerter {
+    url: String,
+    client: reqwest::Client,
+}
+
+impl WebhookAlerter {
+    pub fn new(url: impl Into<String>) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(10))
+            .build()
+            .expect("Failed to build webhook http client");
+        Self {
+            url: url.into(),
+            client,
+        }
+    }
+}
+
+#[async_trait]
+impl Alerter for WebhookAlerter {
+    async fn send(&self, alert: &Alert) -> Result<(), AppError> {
+        let text = format!("🚨 [{}] {}", alert.kind, alert.message);
+        let body = serde_json::json!({ "text": text, "content": text });
+        let response = self
+            .client
+            .post(&self.url)
+            .header(CONTENT_TYPE, "application/json")
+            .body(body.to_string())
+            .send()
+            .await
+            .map_err(|e| AppError::Internal(format!("webhook 请求失败: {}", e)))?;
+        if !response.status().is_success() {
+            return Err(AppError::Internal(format!(
+                "webhook 返回异常状态码: {}",
+                response.status()
+            )));
+        }
+        Ok(())
+    }
+}
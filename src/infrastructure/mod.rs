@@ -1,4 +1,5 @@
 pub mod provider;
 pub mod protocol;
 pub mod abi;
-pub mod parser;
\ No newline at end of file
+pub mod parser;
+pub mod alert;
\ No newline at end of file
@@ -1,3 +1,6 @@
 pub mod parser;
 
-pub use parser::EventParser;
\ No newline at end of file
+pub use parser::EventParser;
+
+/// 解析器版本：解析逻辑变化会影响已入库结果时递增，启动时与 sync_state 中的版本比较
+pub const PARSER_VERSION: i32 = 1;
\ No newline at end of file
@@ -1,22 +1,88 @@
 use crate::errors::error::AppError;
-use crate::infrastructure::provider::ProviderTrait;
+use crate::infrastructure::protocol::contract_parsers::ContractParserRegistry;
+use crate::infrastructure::protocol::transfer_events::{TransferEventRegistry, extract_address};
+use crate::infrastructure::provider::{ProviderTrait, RpcMethod};
 use crate::models::Transfer;
-use crate::utils::is_target_transaction;
-use crate::{log_error, log_warn};
-use ethers_core::types::{Transaction, U64};
-use std::sync::Arc;
+use crate::models::domain::transfer::{TransferContext, TransferKind};
+use crate::models::domain::deployment::ContractDeployment;
+use crate::utils::{is_target_transaction, recover_sender};
+use crate::{log_debug, log_error, log_warn};
+use ethers_core::types::{Filter, H160, H256, Log, Transaction, TransactionReceipt, U64};
+use futures_util::{StreamExt, stream};
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicU64, Ordering};
+use tracing::{Span, instrument};
+use crate::config::{EthereumConfig, FilterMode, TransferEventConfig};
 use crate::config::filter_config::FilterConfig;
 
+/// 收款方是否为合约的缓存上限，超过后整体清空重建
+const CONTRACT_CODE_CACHE_LIMIT: usize = 100_000;
+
 pub struct EventParser {
     provider: Arc<dyn ProviderTrait>,
+    config: Arc<EthereumConfig>,
+    /// 内置 + 配置的类转账事件定义；热重载文件中的事件变化后重建
+    /// （缓存构建时使用的文件事件列表，按指针比较是否变化）
+    transfer_events: Mutex<(Arc<Vec<TransferEventConfig>>, Arc<TransferEventRegistry>)>,
+    /// 非标准代币的按合约解析规则
+    contract_parsers: ContractParserRegistry,
+    /// 累计因合约在黑名单中而丢弃的日志数
+    blocked_logs: AtomicU64,
+    /// 地址 → 是否为合约（eth_getCode 非空），字节码极少变化，按地址长期缓存
+    contract_code: Mutex<HashMap<H160, bool>>,
 }
 
 impl EventParser {
-    pub fn new(provider: Arc<dyn ProviderTrait>) -> Self {
-        Self { provider }
+    pub fn new(provider: Arc<dyn ProviderTrait>, config: Arc<EthereumConfig>) -> Self {
+        Self {
+            provider,
+            transfer_events: Mutex::new((
+                Arc::new(vec![]),
+                Arc::new(TransferEventRegistry::new(&config.transfer_events)),
+            )),
+            contract_parsers: ContractParserRegistry::new(),
+            blocked_logs: AtomicU64::new(0),
+            contract_code: Mutex::new(HashMap::new()),
+            config,
+        }
+    }
+
+    /// 当前生效的事件注册表：`ethereum.transfer_events` + 热重载文件中的事件
+    fn transfer_events(&self, filter_config: &FilterConfig) -> Arc<TransferEventRegistry> {
+        let mut cached = self.transfer_events.lock().unwrap_or_else(|e| e.into_inner());
+        if !Arc::ptr_eq(&cached.0, &filter_config.transfer_events) {
+            let events: Vec<TransferEventConfig> = self
+                .config
+                .transfer_events
+                .iter()
+                .chain(filter_config.transfer_events.iter())
+                .cloned()
+                .collect();
+            *cached = (
+                Arc::clone(&filter_config.transfer_events),
+                Arc::new(TransferEventRegistry::new(&events)),
+            );
+        }
+        Arc::clone(&cached.1)
+    }
+
+    /// 累计被黑名单屏蔽的日志数（用于监控）
+    pub fn blocked_count(&self) -> u64 {
+        self.blocked_logs.load(Ordering::Relaxed)
+    }
+
+    /// 设置按合约的自定义解析规则
+    pub fn with_contract_parsers(mut self, contract_parsers: ContractParserRegistry) -> Self {
+        self.contract_parsers = contract_parsers;
+        self
     }
 
     /// 解析单个区块中的目标转账事件
+    /// 候选交易的收据优先通过 eth_getBlockReceipts 批量获取（需开启），否则以 `receipt_concurrency`
+    /// 为上限并发逐笔获取；结果按交易在区块中的顺序组装，单笔收据失败只计入跳过数，不影响其他交易
+    #[instrument(skip_all, fields(block = block_number, candidates))]
     pub async fn parse_transfers_from_block(
         &self,
         block: &ethers_core::types::Block<Transaction>,
@@ -24,55 +90,477 @@ impl EventParser {
         block_timestamp: i64,
         filter_config: &FilterConfig,
     ) -> Result<(Vec<Transfer>, usize), AppError> {
-        let mut transfers = Vec::new();
         let mut skipped_count = 0;
+        let mut blocked_count = 0;
+        let mut candidates = Vec::new();
+        let transfer_events = self.transfer_events(filter_config);
 
-        for tx in &block.transactions {
+        // from 为零地址的交易先从签名恢复发送方；都正常时不复制交易列表
+        let transactions: Cow<'_, [Transaction]> =
+            if block.transactions.iter().any(|tx| tx.from.is_zero()) {
+                let mut owned = block.transactions.clone();
+                owned.iter_mut().for_each(|tx| {
+                    recover_sender(tx);
+                });
+                Cow::Owned(owned)
+            } else {
+                Cow::Borrowed(&block.transactions)
+            };
+
+        let emitters = match self.config.filter_mode {
+            FilterMode::ContractsOnly => {
+                self.monitored_emitter_txs(block, filter_config, &transfer_events)
+                    .await?
+            }
+            _ => HashSet::new(),
+        };
+        for (position, tx) in transactions.iter().enumerate() {
             if !is_target_transaction(tx) {
                 skipped_count += 1;
                 continue;
             }
 
-            let is_potential_target = filter_config.addresses.contains(&tx.from)
-                || tx.to.map_or(false, |to| filter_config.addresses.contains(&to))
-                || tx.to.map_or(false, |to| filter_config.contracts.contains(&to));
+            let watches_contract = tx.to.is_some_and(|to| {
+                filter_config.contracts.contains(&to) || transfer_events.watches_contract(&to)
+            });
+            let is_potential_target = match self.config.filter_mode {
+                FilterMode::All => true,
+                // 经路由 / 聚合器 / 多签转出的监控代币，tx.to 不是监控合约，按日志发出者判断
+                FilterMode::ContractsOnly => watches_contract || emitters.contains(&tx.hash),
+                FilterMode::Whitelist => {
+                    filter_config.addresses.contains(&tx.from)
+                        || tx.to.is_some_and(|to| filter_config.addresses.contains(&to))
+                        || watches_contract
+                }
+            };
 
             if !is_potential_target {
                 skipped_count += 1;
                 continue;
             }
+            candidates.push((position, tx));
+        }
 
-            let receipt = match self.provider.get_transaction_receipt(tx.hash).await {
-                Ok(Some(r)) => r,
-                Ok(None) => {
-                    log_warn!("交易 {:?} 收据未找到，跳过", tx.hash);
-                    skipped_count += 1;
-                    continue;
-                }
-                Err(e) => {
-                    log_error!("交易 {:?} 获取收据失败（已重试）: {:?}", tx.hash, e);
-                    skipped_count += 1;
-                    continue;
-                }
+        Span::current().record("candidates", candidates.len());
+        let block_height = block_number as u64;
+        let mut receipts = match self.fetch_block_receipts(block_height, &candidates).await {
+            Some(receipts) => receipts,
+            None => self.fetch_receipts_per_tx(candidates).await,
+        };
+
+        // 并发获取的返回顺序不确定，按交易在区块中的位置恢复顺序，
+        // 保证同一区块的转账按 (交易顺序, log_index) 稳定输出
+        receipts.sort_by_key(|(position, _, _)| *position);
+
+        let ctx = TransferContext {
+            block_number,
+            timestamp: block_timestamp,
+            filter: filter_config,
+            events: &transfer_events,
+            parsers: &self.contract_parsers,
+            config: &self.config,
+        };
+        let mut transfers = Vec::new();
+        for (_, tx, receipt) in receipts {
+            let Some(receipt) = receipt else {
+                skipped_count += 1;
+                continue;
             };
 
             if receipt.status != Some(U64::from(1)) {
+                // 开启 store_failed_txs 时，记录涉及监控地址的失败交易（status = 3）
+                if self.config.store_failed_txs {
+                    if let Some(failed) = Transfer::from_failed_tx(
+                        tx,
+                        &receipt,
+                        block_number,
+                        block_timestamp,
+                        filter_config,
+                        self.config.filter_mode,
+                    ) {
+                        log_warn!("交易 {:?} 执行失败，按失败记录入库", tx.hash);
+                        transfers.push(failed);
+                        continue;
+                    }
+                }
                 log_warn!("交易 {:?} 执行失败 (status=0{:?})，跳过", tx.hash,receipt.status.unwrap_or_default().as_ref());
                 skipped_count += 1;
                 continue;
             }
 
-            // 这里可以扩展为解析多种事件，目前只解析 Transfer
-            let mut tx_transfers = Transfer::process_transaction(
-                tx.clone(),
-                receipt,
-                block_number,
-                block_timestamp,
-                filter_config,
-            );
+            // 黑名单合约的日志在 process_transaction 中丢弃，这里单独计数
+            let blocked = receipt
+                .logs
+                .iter()
+                .filter(|log| filter_config.is_contract_blocked(&log.address))
+                .count();
+            if blocked > 0 {
+                blocked_count += blocked;
+                self.blocked_logs.fetch_add(blocked as u64, Ordering::Relaxed);
+            }
+
+            // 解析 ETH 转账及注册表中的类转账事件
+            let mut tx_transfers = Transfer::process_transaction(tx.clone(), receipt, &ctx);
 
             transfers.append(&mut tx_transfers);
         }
+        transfers.extend(Transfer::from_withdrawals(
+            block,
+            block_number,
+            block_timestamp,
+            filter_config,
+            self.config.filter_mode,
+        ));
+        if blocked_count > 0 {
+            log_debug!("区块 {} 屏蔽黑名单合约日志 {} 条", block_number, blocked_count);
+        }
+        if self.config.detect_contract_recipients {
+            self.tag_contract_recipients(&mut transfers).await;
+        }
         Ok((transfers, skipped_count))
     }
+
+    /// ContractsOnly 模式：区块内由监控合约（含事件定义中指定的合约）发出过日志的交易哈希
+    ///
+    /// 以区块哈希调用一次 eth_getLogs 只查询监控合约，据此筛选需要获取收据的交易；
+    /// 收据中的日志仍由 process_transaction 按 log.address 逐条过滤
+    async fn monitored_emitter_txs(
+        &self,
+        block: &ethers_core::types::Block<Transaction>,
+        filter_config: &FilterConfig,
+        transfer_events: &TransferEventRegistry,
+    ) -> Result<HashSet<H256>, AppError> {
+        let contracts: Vec<H160> = filter_config
+            .contracts
+            .iter()
+            .chain(transfer_events.contracts())
+            .copied()
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .collect();
+        let Some(block_hash) = block.hash.filter(|_| !contracts.is_empty()) else {
+            return Ok(HashSet::new());
+        };
+        let filter = Filter::new().at_block_hash(block_hash).address(contracts);
+        let logs = self.provider.get_logs(&filter).await?;
+        Ok(logs
+            .iter()
+            .filter(|log| log.removed != Some(true))
+            .filter_map(|log| log.transaction_hash)
+            .collect())
+    }
+
+    /// 标记原生 ETH 转账的收款方是否为合约；未缓存的地址以 `receipt_concurrency` 为上限并发查询，
+    /// 查询失败的地址 to_contract 保持 None，不影响入库
+    async fn tag_contract_recipients(&self, transfers: &mut [Transfer]) {
+        let recipients: HashSet<H160> = transfers
+            .iter()
+            .filter(|t| t.kind == TransferKind::NativeEth)
+            .filter_map(|t| t.to_address.as_str().parse::<H160>().ok())
+            .filter(|to| !to.is_zero())
+            .collect();
+        let mut known: HashMap<H160, bool> = {
+            let cache = self.contract_code.lock().unwrap_or_else(|e| e.into_inner());
+            recipients
+                .iter()
+                .filter_map(|to| cache.get(to).map(|c| (*to, *c)))
+                .collect()
+        };
+        let missing: Vec<H160> = recipients
+            .into_iter()
+            .filter(|to| !known.contains_key(to))
+            .collect();
+        if !missing.is_empty() {
+            let fetched: Vec<(H160, bool)> = stream::iter(missing)
+                .map(|to| async move {
+                    match self.provider.get_code(to, None).await {
+                        Ok(code) => Some((to, !code.is_empty())),
+                        Err(e) => {
+                            log_warn!("查询地址 {:?} 字节码失败: {}", to, e);
+                            None
+                        }
+                    }
+                })
+                .buffer_unordered(self.config.receipt_concurrency.max(1))
+                .filter_map(|r| async move { r })
+                .collect()
+                .await;
+            let mut cache = self.contract_code.lock().unwrap_or_else(|e| e.into_inner());
+            if cache.len() + fetched.len() > CONTRACT_CODE_CACHE_LIMIT {
+                cache.clear();
+            }
+            cache.extend(fetched.iter().copied());
+            known.extend(fetched);
+        }
+        for transfer in transfers.iter_mut().filter(|t| t.kind == TransferKind::NativeEth) {
+            transfer.to_contract = transfer
+                .to_address
+                .as_str()
+                .parse::<H160>()
+                .ok()
+                .and_then(|to| known.get(&to).copied());
+        }
+    }
+
+    /// 解析区块中由监控地址或工厂部署者发起的合约创建交易（需开启 deployment_tracking），
+    /// 以及配置的工厂通过事件创建的子合约
+    ///
+    /// 创建交易不属于转账候选，这里单独获取收据读取 contract_address；
+    /// 收据获取失败时返回错误，由调用方重试整个区块，避免漏记部署
+    pub async fn parse_deployments(
+        &self,
+        block: &ethers_core::types::Block<Transaction>,
+        block_number: i64,
+        block_timestamp: i64,
+        filter_config: &FilterConfig,
+    ) -> Result<Vec<ContractDeployment>, AppError> {
+        let mut deployments = self
+            .parse_factory_children(block, block_number, block_timestamp)
+            .await?;
+        let tracking = &self.config.deployment_tracking;
+        if !tracking.enabled {
+            return Ok(deployments);
+        }
+        for tx in block.transactions.iter().filter(|tx| tx.to.is_none()) {
+            let mut tx = tx.clone();
+            recover_sender(&mut tx);
+            if !filter_config.addresses.contains(&tx.from)
+                && !tracking.auto_monitor_deployers.contains(&tx.from)
+            {
+                continue;
+            }
+            let Some(receipt) = self.provider.get_transaction_receipt(tx.hash).await? else {
+                log_warn!("合约创建交易 {:?} 收据未找到，跳过", tx.hash);
+                continue;
+            };
+            if let Some(deployment) =
+                ContractDeployment::from_receipt(&tx, &receipt, block_number, block_timestamp)
+            {
+                deployments.push(deployment);
+            }
+        }
+        Ok(deployments)
+    }
+
+    /// 按 `deployment_tracking.factories` 解析工厂创建子合约的事件，子合约记为由工厂部署
+    ///
+    /// 以区块哈希调用一次 eth_getLogs，只查询工厂地址与对应 topic；未配置工厂时不发请求
+    pub async fn parse_factory_children(
+        &self,
+        block: &ethers_core::types::Block<Transaction>,
+        block_number: i64,
+        block_timestamp: i64,
+    ) -> Result<Vec<ContractDeployment>, AppError> {
+        let factories = &self.config.deployment_tracking.factories;
+        let Some(block_hash) = block.hash.filter(|_| !factories.is_empty()) else {
+            return Ok(vec![]);
+        };
+        let filter = Filter::new()
+            .at_block_hash(block_hash)
+            .address(factories.iter().map(|f| f.address).collect::<Vec<_>>())
+            .topic0(factories.iter().map(|f| f.topic).collect::<Vec<_>>());
+        let logs = self.provider.get_logs(&filter).await?;
+        Ok(logs
+            .iter()
+            .filter(|log| log.removed != Some(true))
+            .filter_map(|log| {
+                let factory = factories
+                    .iter()
+                    .find(|f| f.address == log.address && log.topics.first() == Some(&f.topic))?;
+                let child = extract_address(log, factory.child).filter(|c| !c.is_zero())?;
+                log_debug!("工厂 {} 创建子合约 {:?}", factory.name, child);
+                Some(ContractDeployment {
+                    deployer: factory.address.into(),
+                    contract_address: child.into(),
+                    tx_hash: log.transaction_hash?.into(),
+                    block_number,
+                    timestamp: block_timestamp,
+                })
+            })
+            .collect())
+    }
+
+    /// 快速模式：用一次 eth_getLogs 拉取 [from, to] 区间内的类转账事件日志
+    ///
+    /// 非 All 模式只查询监控合约与事件定义中指定的合约；注册了自定义解析器时不按 topic 过滤，
+    /// 以便解析器仍能拿到非标准事件。已被节点标记为 removed 的日志直接丢弃
+    pub async fn fetch_transfer_logs(
+        &self,
+        from: u64,
+        to: u64,
+        filter_config: &FilterConfig,
+    ) -> Result<Vec<Log>, AppError> {
+        let transfer_events = self.transfer_events(filter_config);
+        let mut filter = Filter::new().from_block(from).to_block(to);
+        if self.contract_parsers.is_empty() {
+            filter = filter.topic0(transfer_events.topics());
+        }
+        if self.config.filter_mode != FilterMode::All {
+            let contracts: HashSet<H160> = filter_config
+                .contracts
+                .iter()
+                .chain(transfer_events.contracts())
+                .copied()
+                .collect();
+            if contracts.is_empty() {
+                return Ok(vec![]);
+            }
+            filter = filter.address(contracts.into_iter().collect::<Vec<_>>());
+        }
+        let logs = self.provider.get_logs(&filter).await?;
+        Ok(logs
+            .into_iter()
+            .filter(|log| log.removed != Some(true))
+            .collect())
+    }
+
+    /// 快速模式：用区块交易与 eth_getLogs 取回的日志拼出转账，不获取收据
+    ///
+    /// 产生日志的交易必然执行成功，按 status = 1 构造只含日志的收据；收据中的 gas 信息缺失，
+    /// 因此 gas / 手续费记为 0。没有日志的交易（纯 ETH 转账、失败交易）计入跳过数
+    pub fn parse_transfers_from_logs(
+        &self,
+        block: &ethers_core::types::Block<Transaction>,
+        block_number: i64,
+        block_timestamp: i64,
+        logs: Vec<Log>,
+        filter_config: &FilterConfig,
+    ) -> (Vec<Transfer>, usize) {
+        let mut by_tx: HashMap<H256, Vec<Log>> = HashMap::new();
+        for log in logs {
+            if let Some(tx_hash) = log.transaction_hash {
+                by_tx.entry(tx_hash).or_default().push(log);
+            }
+        }
+
+        let transfer_events = self.transfer_events(filter_config);
+        let ctx = TransferContext {
+            block_number,
+            timestamp: block_timestamp,
+            filter: filter_config,
+            events: &transfer_events,
+            parsers: &self.contract_parsers,
+            config: &self.config,
+        };
+        let mut skipped_count = 0;
+        let mut blocked_count = 0;
+        let mut transfers = Vec::new();
+        for tx in &block.transactions {
+            let Some(mut tx_logs) = by_tx.remove(&tx.hash) else {
+                skipped_count += 1;
+                continue;
+            };
+            if !is_target_transaction(tx) {
+                skipped_count += 1;
+                continue;
+            }
+            tx_logs.sort_by_key(|log| log.log_index);
+
+            let blocked = tx_logs
+                .iter()
+                .filter(|log| filter_config.is_contract_blocked(&log.address))
+                .count();
+            blocked_count += blocked;
+
+            let mut tx = tx.clone();
+            recover_sender(&mut tx);
+            let receipt = TransactionReceipt {
+                transaction_hash: tx.hash,
+                transaction_index: tx.transaction_index.unwrap_or_default(),
+                block_hash: block.hash,
+                block_number: block.number,
+                from: tx.from,
+                to: tx.to,
+                logs: tx_logs,
+                status: Some(U64::from(1)),
+                ..Default::default()
+            };
+            let mut tx_transfers = Transfer::process_transaction(tx, receipt, &ctx);
+            transfers.append(&mut tx_transfers);
+        }
+        transfers.extend(Transfer::from_withdrawals(
+            block,
+            block_number,
+            block_timestamp,
+            filter_config,
+            self.config.filter_mode,
+        ));
+        if blocked_count > 0 {
+            self.blocked_logs
+                .fetch_add(blocked_count as u64, Ordering::Relaxed);
+            log_debug!("区块 {} 屏蔽黑名单合约日志 {} 条", block_number, blocked_count);
+        }
+        (transfers, skipped_count)
+    }
+
+    /// 开启 `use_block_receipts` 时通过 eth_getBlockReceipts 一次取回整个区块的收据
+    /// 节点不支持该方法时由 provider 能力表记录，之后不再尝试；返回 None 表示需要回退到逐笔获取
+    async fn fetch_block_receipts<'a>(
+        &self,
+        block_number: u64,
+        candidates: &[(usize, &'a Transaction)],
+    ) -> Option<Vec<(usize, &'a Transaction, Option<TransactionReceipt>)>> {
+        if !self.config.use_block_receipts
+            || candidates.is_empty()
+            || !self.provider.supports(RpcMethod::BlockReceipts)
+        {
+            return None;
+        }
+
+        let block_receipts = match self.provider.get_block_receipts(block_number).await {
+            Ok(r) => r,
+            Err(AppError::MethodNotSupported(e)) => {
+                log_warn!("节点不支持 eth_getBlockReceipts，回退为逐笔获取收据: {}", e);
+                return None;
+            }
+            Err(e) => {
+                log_warn!("区块 {} eth_getBlockReceipts 失败，本区块回退为逐笔获取: {:?}", block_number, e);
+                return None;
+            }
+        };
+
+        let mut by_hash: HashMap<H256, TransactionReceipt> = block_receipts
+            .into_iter()
+            .map(|r| (r.transaction_hash, r))
+            .collect();
+        Some(
+            candidates
+                .iter()
+                .map(|&(position, tx)| {
+                    let receipt = by_hash.remove(&tx.hash);
+                    if receipt.is_none() {
+                        log_warn!("交易 {:?} 不在区块收据中，跳过", tx.hash);
+                    }
+                    (position, tx, receipt)
+                })
+                .collect(),
+        )
+    }
+
+    /// 通过 `get_transaction_receipts` 批量获取收据（并发上限为 `receipt_concurrency`，RetryAdapter 负责单次请求的重试）
+    /// 节点没有收据（pending / 已丢弃）或获取失败的交易对应 None，由调用方计入跳过数
+    async fn fetch_receipts_per_tx<'a>(
+        &self,
+        candidates: Vec<(usize, &'a Transaction)>,
+    ) -> Vec<(usize, &'a Transaction, Option<TransactionReceipt>)> {
+        let hashes: Vec<H256> = candidates.iter().map(|(_, tx)| tx.hash).collect();
+        let receipts = match self.provider.get_transaction_receipts(&hashes).await {
+            Ok(receipts) => receipts,
+            Err(e) => {
+                log_error!("批量获取 {} 笔收据全部失败（已重试）: {:?}", hashes.len(), e);
+                vec![]
+            }
+        };
+        let mut by_hash: HashMap<H256, Option<TransactionReceipt>> = receipts.into_iter().collect();
+        candidates
+            .into_iter()
+            .map(|(position, tx)| {
+                let receipt = by_hash.remove(&tx.hash).flatten();
+                if receipt.is_none() {
+                    log_warn!("交易 {:?} 收据缺失（pending / 已丢弃或获取失败），跳过", tx.hash);
+                }
+                (position, tx, receipt)
+            })
+            .collect()
+    }
 }
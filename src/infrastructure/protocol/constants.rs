@@ -11,4 +11,12 @@ lazy_static! {
     pub static ref ERC20_TRANSFER_TOPIC: H256 =
         H256::from_str("0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef")
             .expect("Invalid ERC20 Transfer Topic hash");
+    // ERC1155 TransferSingle(address,address,address,uint256,uint256)
+    pub static ref ERC1155_TRANSFER_SINGLE_TOPIC: H256 =
+        H256::from_str("0xc3d58168c5ae7397731d063d5bbf3d657854427343f4c083240f7aacaa2d0f62")
+            .expect("Invalid ERC1155 TransferSingle Topic hash");
+    // EIP-1967 实现合约槽位: bytes32(uint256(keccak256("eip1967.proxy.implementation")) - 1)
+    pub static ref EIP1967_IMPLEMENTATION_SLOT: H256 =
+        H256::from_str("0x360894a13ba1a3210667c828492db98dca3e2076cc3735a920a3ca505d382bbc")
+            .expect("Invalid EIP-1967 implementation slot");
 }
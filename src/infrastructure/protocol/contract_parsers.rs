@@ -0,0 +1,63 @@
+use crate::models::Transfer;
+use ethers_core::types::{H160, Log, Transaction, TransactionReceipt};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// 单个合约的自定义解析规则，用于转账金额与日志不一致（rebasing、fee-on-transfer）
+/// 或会额外发出事件的非标准代币
+///
+/// 注册了解析器的合约，其所有日志都只交给解析器处理，不再走默认的事件注册表：
+/// 返回 `None` 表示忽略该日志
+pub trait ContractParser: Send + Sync {
+    fn parse(
+        &self,
+        tx: &Transaction,
+        log: &Log,
+        receipt: &TransactionReceipt,
+        block_number: i64,
+        block_timestamp: i64,
+    ) -> Option<Transfer>;
+}
+
+/// 闭包可直接作为解析器注册
+impl<F> ContractParser for F
+where
+    F: Fn(&Transaction, &Log, &TransactionReceipt, i64, i64) -> Option<Transfer> + Send + Sync,
+{
+    fn parse(
+        &self,
+        tx: &Transaction,
+        log: &Log,
+        receipt: &TransactionReceipt,
+        block_number: i64,
+        block_timestamp: i64,
+    ) -> Option<Transfer> {
+        self(tx, log, receipt, block_number, block_timestamp)
+    }
+}
+
+/// 合约地址 → 自定义解析器，默认为空（全部走默认解析逻辑）
+#[derive(Clone, Default)]
+pub struct ContractParserRegistry {
+    parsers: HashMap<H160, Arc<dyn ContractParser>>,
+}
+
+impl ContractParserRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 注册解析器，同一合约重复注册时覆盖之前的
+    pub fn register(&mut self, contract: H160, parser: Arc<dyn ContractParser>) -> &mut Self {
+        self.parsers.insert(contract, parser);
+        self
+    }
+
+    pub fn get(&self, contract: &H160) -> Option<&Arc<dyn ContractParser>> {
+        self.parsers.get(contract)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.parsers.is_empty()
+    }
+}
@@ -1 +1,3 @@
 pub mod constants;
+pub mod contract_parsers;
+pub mod transfer_events;
@@ -0,0 +1,108 @@
+use crate::config::{AddressSource, TransferEventConfig, ValueSource};
+use crate::infrastructure::protocol::constants::{
+    ERC1155_TRANSFER_SINGLE_TOPIC, ERC20_TRANSFER_TOPIC,
+};
+use crate::log_error;
+use crate::models::domain::transfer::TransferKind;
+use ethers_core::types::{H160, H256, Log};
+use std::collections::{HashMap, HashSet};
+
+/// 类转账事件注册表：按 topic0 索引内置的 ERC-20/721/1155 事件与配置中的协议事件
+pub struct TransferEventRegistry {
+    events: HashMap<H256, Vec<TransferEventConfig>>,
+    /// 事件定义中显式指定的合约，交易预筛选时也视为监控目标
+    contracts: HashSet<H160>,
+}
+
+impl TransferEventRegistry {
+    /// 布局不自洽的事件定义记录错误后忽略，不影响其他事件
+    pub fn new(extra: &[TransferEventConfig]) -> Self {
+        let mut events: HashMap<H256, Vec<TransferEventConfig>> = HashMap::new();
+        let mut contracts = HashSet::new();
+        for event in Self::standard().into_iter().chain(extra.iter().cloned()) {
+            if let Err(e) = event.validate() {
+                log_error!("忽略无效的类转账事件定义: {}", e);
+                continue;
+            }
+            contracts.extend(event.contracts.iter().copied());
+            events.entry(event.topic).or_default().push(event);
+        }
+        Self { events, contracts }
+    }
+
+    /// 内置事件定义
+    /// ERC1155 TransferBatch 的 data 为动态数组，无法按固定位置提取，暂不支持
+    pub fn standard() -> Vec<TransferEventConfig> {
+        vec![
+            // Transfer(address indexed from, address indexed to, uint256 value)
+            TransferEventConfig {
+                name: "erc20_transfer".into(),
+                topic: *ERC20_TRANSFER_TOPIC,
+                topic_count: Some(3),
+                contracts: vec![],
+                from: AddressSource::Topic(1),
+                to: AddressSource::Topic(2),
+                value: ValueSource::Data(0),
+                kind: TransferKind::Erc20,
+            },
+            // Transfer(address indexed from, address indexed to, uint256 indexed tokenId)
+            TransferEventConfig {
+                name: "erc721_transfer".into(),
+                topic: *ERC20_TRANSFER_TOPIC,
+                topic_count: Some(4),
+                contracts: vec![],
+                from: AddressSource::Topic(1),
+                to: AddressSource::Topic(2),
+                value: ValueSource::Constant(1),
+                kind: TransferKind::Erc721,
+            },
+            // TransferSingle(address indexed operator, address indexed from, address indexed to, uint256 id, uint256 value)
+            TransferEventConfig {
+                name: "erc1155_transfer_single".into(),
+                topic: *ERC1155_TRANSFER_SINGLE_TOPIC,
+                topic_count: Some(4),
+                contracts: vec![],
+                from: AddressSource::Topic(2),
+                to: AddressSource::Topic(3),
+                value: ValueSource::Data(1),
+                kind: TransferKind::Erc1155,
+            },
+        ]
+    }
+
+    /// 查找与日志匹配的事件定义（topic0、topics 数量、限定合约均需满足）
+    pub fn matching(&self, log: &Log) -> Option<&TransferEventConfig> {
+        let topic0 = log.topics.first()?;
+        self.events.get(topic0)?.iter().find(|event| {
+            event.topic_count.is_none_or(|n| n == log.topics.len())
+                && (event.contracts.is_empty() || event.contracts.contains(&log.address))
+        })
+    }
+
+    pub fn watches_contract(&self, address: &H160) -> bool {
+        self.contracts.contains(address)
+    }
+
+    /// 所有已注册事件的 topic0，用于 eth_getLogs 过滤
+    pub fn topics(&self) -> Vec<H256> {
+        self.events.keys().copied().collect()
+    }
+
+    /// 事件定义中显式指定的合约
+    pub fn contracts(&self) -> &HashSet<H160> {
+        &self.contracts
+    }
+}
+
+/// 按配置从日志中提取地址，topic / data 越界时返回 None
+pub fn extract_address(log: &Log, source: AddressSource) -> Option<H160> {
+    match source {
+        AddressSource::Topic(i) => log.topics.get(i).map(|t| H160::from(*t)),
+        AddressSource::Data(i) => log
+            .data
+            .get(i * 32..(i + 1) * 32)
+            .map(|word| H160::from_slice(&word[12..])),
+        AddressSource::Zero => Some(H160::zero()),
+        AddressSource::Contract => Some(log.address),
+    }
+}
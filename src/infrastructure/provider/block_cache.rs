@@ -0,0 +1,61 @@
+use crate::config::BlockCacheConfig;
+use ethers_core::types::{Block, Transaction};
+use hashlink::LruCache;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+/// 已最终化区块的本地 LRU 缓存
+/// 最终化区块不可变，因此只做容量淘汰/TTL 过期，不做失效处理
+pub struct BlockCache {
+    blocks: Mutex<LruCache<u64, (Instant, Block<Transaction>)>>,
+    ttl: Option<Duration>,
+    finality_depth: u64,
+    /// 最近一次观测到的链头高度，用于判断区块是否已脱离重组窗口
+    latest_head: AtomicU64,
+}
+
+impl BlockCache {
+    pub fn new(config: &BlockCacheConfig) -> Self {
+        Self {
+            blocks: Mutex::new(LruCache::new(config.capacity.max(1))),
+            ttl: (config.ttl_secs > 0).then(|| Duration::from_secs(config.ttl_secs)),
+            finality_depth: config.finality_depth,
+            latest_head: AtomicU64::new(0),
+        }
+    }
+
+    /// 记录链头高度（只增不减）
+    pub fn observe_head(&self, head: u64) {
+        self.latest_head.fetch_max(head, Ordering::Relaxed);
+    }
+
+    /// 区块是否已脱离重组风险窗口
+    pub fn is_cacheable(&self, number: u64) -> bool {
+        let head = self.latest_head.load(Ordering::Relaxed);
+        head > 0 && number.saturating_add(self.finality_depth) <= head
+    }
+
+    pub fn get(&self, number: u64) -> Option<Block<Transaction>> {
+        let mut blocks = self.blocks.lock().unwrap();
+        let expired = match blocks.get(&number) {
+            None => return None,
+            Some((inserted_at, _)) => self.ttl.is_some_and(|ttl| inserted_at.elapsed() > ttl),
+        };
+        if expired {
+            blocks.remove(&number);
+            return None;
+        }
+        blocks.get(&number).map(|(_, block)| block.clone())
+    }
+
+    pub fn insert(&self, number: u64, block: &Block<Transaction>) {
+        if !self.is_cacheable(number) {
+            return;
+        }
+        self.blocks
+            .lock()
+            .unwrap()
+            .insert(number, (Instant::now(), block.clone()));
+    }
+}
@@ -0,0 +1,149 @@
+use super::confirmation::ConfirmationTarget;
+use super::ethereum_provider::ProviderTrait;
+use crate::errors::error::is_method_not_found;
+use crate::{log_info, log_warn};
+use ethers::prelude::BlockNumber;
+use ethers_providers::{JsonRpcClient, Middleware, Provider, ProviderError, RpcError};
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use tokio::task::JoinHandle;
+
+/// 节点不支持 finalized 标签时，Finalized 确认策略退化为等待的区块数
+const FINALIZED_FALLBACK_BLOCKS: u64 = 64;
+
+/// 需要探测支持情况的可选 RPC 能力
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RpcMethod {
+    /// eth_getBlockReceipts
+    BlockReceipts,
+    /// eth_feeHistory
+    FeeHistory,
+    /// debug_traceBlockByNumber
+    DebugTraceBlock,
+    /// eth_getBlockByNumber("finalized")
+    FinalizedTag,
+}
+
+impl RpcMethod {
+    pub const ALL: [RpcMethod; 4] = [
+        RpcMethod::BlockReceipts,
+        RpcMethod::FeeHistory,
+        RpcMethod::DebugTraceBlock,
+        RpcMethod::FinalizedTag,
+    ];
+}
+
+/// 节点能力表：启动时探测写入，运行中遇到 method not found 时也会更新
+/// 未探测过的能力视为支持，保持与探测前一致的行为
+#[derive(Default)]
+pub struct ProviderCapabilities {
+    supported: RwLock<HashMap<RpcMethod, bool>>,
+}
+
+impl ProviderCapabilities {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn supports(&self, method: RpcMethod) -> bool {
+        self.supported
+            .read()
+            .unwrap()
+            .get(&method)
+            .copied()
+            .unwrap_or(true)
+    }
+
+    pub fn record(&self, method: RpcMethod, supported: bool) {
+        let previous = self.supported.write().unwrap().insert(method, supported);
+        if previous != Some(supported) {
+            log_info!("RPC 能力 {:?}: {}", method, if supported { "支持" } else { "不支持" });
+        }
+    }
+
+    /// 节点不支持 finalized 标签时，Finalized 策略退化为等待固定区块数
+    pub fn resolve_confirmation(&self, target: ConfirmationTarget) -> ConfirmationTarget {
+        if target == ConfirmationTarget::Finalized && !self.supports(RpcMethod::FinalizedTag) {
+            log_warn!(
+                "节点不支持 finalized 标签，改为等待 {} 个区块确认",
+                FINALIZED_FALLBACK_BLOCKS
+            );
+            return ConfirmationTarget::Blocks(FINALIZED_FALLBACK_BLOCKS);
+        }
+        target
+    }
+
+    /// 对所有节点探测全部能力；任一节点明确不支持即记为不支持（轮询可能命中任意节点）
+    /// 网络错误等无法判断的结果不覆盖已有记录
+    pub(crate) async fn probe_all<P: JsonRpcClient>(&self, providers: &[Arc<Provider<P>>]) {
+        for method in RpcMethod::ALL {
+            let mut result = None;
+            for provider in providers {
+                match probe_method(provider, method).await {
+                    Some(false) => {
+                        result = Some(false);
+                        break;
+                    }
+                    Some(true) => result = result.or(Some(true)),
+                    None => {}
+                }
+            }
+            match result {
+                Some(supported) => self.record(method, supported),
+                None => log_warn!("RPC 能力 {:?} 探测失败，保留原有记录", method),
+            }
+        }
+    }
+}
+
+/// 发起一次开销很小的探测请求：Some(true) 支持，Some(false) 不支持，None 无法判断
+async fn probe_method<P: JsonRpcClient>(provider: &Provider<P>, method: RpcMethod) -> Option<bool> {
+    let result: Result<Value, ProviderError> = match method {
+        RpcMethod::BlockReceipts => {
+            provider
+                .request("eth_getBlockReceipts", [BlockNumber::Latest])
+                .await
+        }
+        RpcMethod::FeeHistory => provider
+            .fee_history(1, BlockNumber::Latest, &[])
+            .await
+            .map(|_| Value::Null),
+        // 创世区块没有交易，追踪开销可以忽略
+        RpcMethod::DebugTraceBlock => {
+            provider
+                .request(
+                    "debug_traceBlockByNumber",
+                    (BlockNumber::Number(0.into()), json!({ "tracer": "callTracer" })),
+                )
+                .await
+        }
+        RpcMethod::FinalizedTag => {
+            provider
+                .request("eth_getBlockByNumber", (BlockNumber::Finalized, false))
+                .await
+        }
+    };
+
+    match result {
+        Ok(value) => Some(method != RpcMethod::FinalizedTag || !value.is_null()),
+        Err(e) if is_method_not_found(&e) => Some(false),
+        // 节点能识别方法但拒绝 finalized 参数，说明不支持该标签
+        Err(e) if method == RpcMethod::FinalizedTag && e.as_error_response().is_some() => Some(false),
+        Err(e) => {
+            log_warn!("RPC 能力 {:?} 探测出错: {}", method, e);
+            None
+        }
+    }
+}
+
+/// 后台定期重新探测，节点被替换后能力表随之更新
+pub fn spawn_capability_probe(provider: Arc<dyn ProviderTrait>, interval: Duration) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        loop {
+            tokio::time::sleep(interval).await;
+            provider.probe_capabilities().await;
+        }
+    })
+}
@@ -0,0 +1,104 @@
+use ethers::prelude::BlockNumber;
+use ethers_core::types::{TransactionReceipt, U64};
+use ethers_providers::{JsonRpcClient, Middleware, PendingTransaction, Provider, ProviderError};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use tokio::time::sleep;
+
+/// 交易确认策略
+/// - `Blocks(n)`：打包后再等待 n 个区块（原 confirmations 语义）
+/// - `Duration(secs)`：打包后再等待一段墙钟时间，适合出块很快的 L2
+/// - `Finalized`：等待交易所在区块被 `finalized` 标签覆盖（主网最终性）
+/// - `Either`：区块数或墙钟时间任一满足即返回，拥堵时延迟有上限
+/// - `Both`：区块数与墙钟时间都满足才返回，安全性优先
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConfirmationTarget {
+    Blocks(u64),
+    Duration(u64),
+    Finalized,
+    Either { blocks: u64, secs: u64 },
+    Both { blocks: u64, secs: u64 },
+}
+
+impl Default for ConfirmationTarget {
+    fn default() -> Self {
+        ConfirmationTarget::Blocks(1)
+    }
+}
+
+/// 按确认策略等待已广播的交易
+/// 返回 `Ok(None)` 表示交易被丢弃或在等待期间被重组移出
+pub(crate) async fn wait_for_confirmation<P: JsonRpcClient>(
+    provider: &Provider<P>,
+    pending_tx: PendingTransaction<'_, P>,
+    target: ConfirmationTarget,
+) -> Result<Option<TransactionReceipt>, ProviderError> {
+    let tx_hash = *pending_tx;
+    match target {
+        ConfirmationTarget::Blocks(n) => pending_tx.confirmations(n as usize).await,
+        ConfirmationTarget::Duration(secs) => {
+            if pending_tx.confirmations(1).await?.is_none() {
+                return Ok(None);
+            }
+            sleep(Duration::from_secs(secs)).await;
+            // 等待结束后重新取回执，确认交易仍在链上
+            provider.get_transaction_receipt(tx_hash).await
+        }
+        ConfirmationTarget::Finalized => {
+            let Some(receipt) = pending_tx.confirmations(1).await? else {
+                return Ok(None);
+            };
+            let included_at = receipt.block_number.unwrap_or_default();
+            loop {
+                let finalized = provider
+                    .get_block(BlockNumber::Finalized)
+                    .await?
+                    .and_then(|b| b.number)
+                    .unwrap_or_default();
+                if finalized >= included_at {
+                    break;
+                }
+                sleep(provider.get_interval()).await;
+            }
+            // 最终化后重新取回执，防止等待期间被重组到其他区块
+            provider.get_transaction_receipt(tx_hash).await
+        }
+        ConfirmationTarget::Either { blocks, secs } => {
+            let Some(receipt) = pending_tx.confirmations(1).await? else {
+                return Ok(None);
+            };
+            let included_at = receipt.block_number.unwrap_or_default();
+            tokio::select! {
+                res = wait_for_depth(provider, included_at, blocks) => res?,
+                _ = sleep(Duration::from_secs(secs)) => {}
+            }
+            provider.get_transaction_receipt(tx_hash).await
+        }
+        ConfirmationTarget::Both { blocks, secs } => {
+            let Some(receipt) = pending_tx.confirmations(1).await? else {
+                return Ok(None);
+            };
+            let included_at = receipt.block_number.unwrap_or_default();
+            let (depth, _) = tokio::join!(
+                wait_for_depth(provider, included_at, blocks),
+                sleep(Duration::from_secs(secs))
+            );
+            depth?;
+            provider.get_transaction_receipt(tx_hash).await
+        }
+    }
+}
+
+/// 轮询链头，直到 `included_at` 所在区块获得 `blocks` 个确认（含自身，与 confirmations 语义一致）
+async fn wait_for_depth<P: JsonRpcClient>(
+    provider: &Provider<P>,
+    included_at: U64,
+    blocks: u64,
+) -> Result<(), ProviderError> {
+    let target = included_at + U64::from(blocks.saturating_sub(1));
+    while provider.get_block_number().await? < target {
+        sleep(provider.get_interval()).await;
+    }
+    Ok(())
+}
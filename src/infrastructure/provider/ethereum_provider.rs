@@ -1,14 +1,28 @@
-use crate::config::EthereumConfig;
+use super::capabilities::{ProviderCapabilities, RpcMethod};
+use super::confirmation::{ConfirmationTarget, wait_for_confirmation};
+use crate::config::{EthereumConfig, ProviderSelection, RateLimitConfig, RpcAuth};
 use crate::errors::error::AppError;
-use crate::log_info;
+use crate::{log_error, log_info, log_warn};
 use async_trait::async_trait;
+use futures_util::{StreamExt, stream};
 use ethers::addressbook::Address;
-use ethers::prelude::{H256, U64, U256};
+use ethers::prelude::{BlockNumber, H256, U64, U256};
 use ethers_core::types::transaction::eip2718::TypedTransaction;
-use ethers_core::types::{Block, Bytes, Transaction, TransactionReceipt};
-use ethers_providers::{Http, Middleware, PendingTransaction, Provider, ProviderError};
+use ethers_core::types::{
+    Block, Bytes, Filter, Log, SyncingStatus, Transaction, TransactionReceipt,
+};
+#[cfg(feature = "ipc")]
+use ethers_providers::Ipc;
+use ethers_providers::{Authorization, Http, JsonRpcClient, Middleware, Provider, ProviderError};
+use reqwest::header::{AUTHORIZATION, HeaderMap, HeaderName, HeaderValue};
+use std::collections::HashMap;
 use std::sync::Arc;
-use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::atomic::{AtomicU32, AtomicU64, AtomicUsize, Ordering};
+use rand::Rng;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use std::future::Future;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use tokio::time::timeout;
 use url::Url;
 
@@ -17,12 +31,35 @@ pub trait ProviderTrait: Send + Sync {
     async fn get_last_block_number(&self) -> Result<U64, AppError>;
     async fn get_block_with_txs(&self, number: u64)
     -> Result<Option<Block<Transaction>>, AppError>;
+    /// 按区块哈希获取区块（含完整交易），用于沿 parent_hash 回溯分叉或校验外部引用的区块
+    /// 节点不认识该哈希（孤块已被清理或从未见过）时返回 None
+    async fn get_block_by_hash(&self, hash: H256) -> Result<Option<Block<Transaction>>, AppError>;
     async fn get_transaction_receipt(
         &self,
         tx_hash: H256,
     ) -> Result<Option<TransactionReceipt>, AppError>;
+    /// 批量获取收据，按传入顺序返回每个哈希的结果，None 表示节点上没有该收据（pending / 已丢弃）
+    /// 单笔请求失败同样记为 None 并记录错误日志；全部失败时返回最后一个错误
+    async fn get_transaction_receipts(
+        &self,
+        hashes: &[H256],
+    ) -> Result<Vec<(H256, Option<TransactionReceipt>)>, AppError>;
+    /// eth_getLogs：按区块区间、合约与 topic 过滤日志
+    async fn get_logs(&self, filter: &Filter) -> Result<Vec<Log>, AppError>;
+    /// eth_getBlockReceipts：一次取回整个区块的收据，节点不支持时返回 AppError::MethodNotSupported
+    async fn get_block_receipts(&self, number: u64) -> Result<Vec<TransactionReceipt>, AppError>;
     async fn get_chain_id(&self) -> Result<U256, AppError>;
     async fn get_transaction_count(&self, address: &str) -> Result<U256, AppError>;
+    /// pending 状态下的 nonce（包含内存池中尚未打包的交易）
+    async fn get_pending_transaction_count(&self, address: &str) -> Result<U256, AppError>;
+    /// eth_syncing：节点仍在同步时返回进度，已同步返回 None
+    async fn is_node_syncing(&self) -> Result<Option<SyncStatus>, AppError>;
+    /// 地址的原生代币余额（latest）
+    async fn get_balance(&self, address: Address) -> Result<U256, AppError>;
+    /// eth_getStorageAt（最新区块）
+    async fn get_storage_at(&self, address: Address, slot: H256) -> Result<H256, AppError>;
+    /// eth_getCode：地址在指定区块（None 为 latest）的字节码，EOA 返回空
+    async fn get_code(&self, address: Address, block: Option<BlockNumber>) -> Result<Bytes, AppError>;
 
     async fn estimate_eip1559_fees(
         &self,
@@ -32,20 +69,208 @@ pub trait ProviderTrait: Send + Sync {
         &self,
         rlp: Bytes,
         timeout_secs: u64,
-        confirmations: usize,
+        target: ConfirmationTarget,
     ) -> Result<TransactionReceipt, AppError>;
     async fn call(&self, tx: &TypedTransaction) -> Result<Bytes, AppError>;
     async fn estimate_gas(&self, tx: &TypedTransaction) -> Result<U256, AppError>;
+    /// 透传任意 JSON-RPC 方法，返回原始 JSON；一般通过 `raw_request` 调用
+    async fn raw_request_value(
+        &self,
+        method: &str,
+        params: serde_json::Value,
+    ) -> Result<serde_json::Value, AppError>;
+    /// 节点是否支持可选 RPC 能力（未探测时视为支持）
+    fn supports(&self, method: RpcMethod) -> bool;
+    /// 探测所有节点的能力并更新能力表
+    async fn probe_capabilities(&self);
+    /// 逐个节点查询区块哈希（不走轮询与缓存），用于跨节点一致性校验
+    /// 按节点顺序返回，节点请求失败或没有该区块时为 None
+    async fn block_hash_per_provider(&self, number: u64) -> Vec<Option<H256>>;
+    /// 当前连续失败（重试耗尽）的 RPC 调用次数，任一调用成功即清零；不带重试的实现恒为 0
+    fn consecutive_failures(&self) -> u64 {
+        0
+    }
+    /// 各节点的响应延迟统计
+    fn latency_stats(&self) -> Vec<EndpointLatency> {
+        Vec::new()
+    }
+}
+
+/// 保留 revert / 限流等结构化错误，其余错误带上调用说明
+fn with_context(err: ProviderError, context: &str) -> AppError {
+    match AppError::from(err) {
+        AppError::ProviderError(msg) => AppError::ProviderError(format!("{}: {}", context, msg)),
+        other => other,
+    }
+}
+
+impl dyn ProviderTrait {
+    /// 调用 ProviderTrait 未覆盖的 RPC 方法（如 `trace_transaction`、`erigon_*`），结果反序列化为 `T`
+    ///
+    /// 与其他方法一样经过节点轮询与重试。返回结构取决于节点实现：不同客户端对同名方法的
+    /// 支持与返回格式可能不同，节点不支持时返回 AppError::MethodNotSupported
+    pub async fn raw_request<T: DeserializeOwned>(
+        &self,
+        method: &str,
+        params: serde_json::Value,
+    ) -> Result<T, AppError> {
+        let value = self.raw_request_value(method, params).await?;
+        serde_json::from_value(value)
+            .map_err(|e| AppError::ParserError(format!("{} 返回值解析失败: {}", method, e)))
+    }
 }
 
-pub struct EthereumProvider {
-    providers: Vec<Arc<Provider<Http>>>,
+/// 节点同步进度（eth_syncing 返回值）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SyncStatus {
+    pub starting_block: u64,
+    pub current_block: u64,
+    pub highest_block: u64,
+}
+
+impl SyncStatus {
+    pub(crate) fn from_syncing(status: SyncingStatus) -> Option<Self> {
+        match status {
+            SyncingStatus::IsFalse => None,
+            SyncingStatus::IsSyncing(progress) => Some(SyncStatus {
+                starting_block: progress.starting_block.as_u64(),
+                current_block: progress.current_block.as_u64(),
+                highest_block: progress.highest_block.as_u64(),
+            }),
+        }
+    }
+}
+
+/// 多节点轮询的 Provider，传输层默认 HTTP，启用 `ipc` feature 后可使用本地 IPC
+pub struct EthereumProvider<P = Http> {
+    providers: Vec<Arc<Provider<P>>>,
     index: AtomicUsize,
+    capabilities: ProviderCapabilities,
+    /// 各节点的限流与延迟状态，与 providers 一一对应
+    endpoints: Vec<EndpointState>,
+    /// 脱敏后的节点 URL，用于日志与监控输出，与 providers 一一对应
+    labels: Vec<String>,
+    selection: ProviderSelection,
+    /// 单次请求超过该耗时时记录慢调用警告，None 表示不记录
+    slow_call_threshold: Option<Duration>,
+}
+
+/// 单个节点的连续限流次数、冷却截止时间（unix 毫秒，0 表示未冷却）、响应延迟 EWMA（微秒，0 表示尚无样本）
+/// 与遵循 Retry-After 等待的次数
+#[derive(Default)]
+struct EndpointState {
+    streak: AtomicU32,
+    cooldown_until_ms: AtomicU64,
+    latency_ewma_us: AtomicU64,
+    retry_after_honored: AtomicU64,
+    /// 按 LATENCY_BUCKETS_MS 划分的请求耗时计数，最后一个桶为超过最大上界的请求
+    latency_buckets: [AtomicU64; LATENCY_BUCKETS_MS.len() + 1],
+}
+
+/// 延迟直方图各桶的上界（毫秒）
+const LATENCY_BUCKETS_MS: [u64; 7] = [50, 100, 250, 500, 1_000, 2_500, 5_000];
+
+/// 延迟直方图的一个桶
+#[derive(Debug, Clone, Serialize)]
+pub struct LatencyBucket {
+    /// 桶上界（毫秒），None 表示 +Inf
+    pub le_ms: Option<u64>,
+    /// 耗时不超过上界的累计请求数（与 Prometheus histogram 一致）
+    pub count: u64,
+}
+
+/// 单个节点的延迟统计（供监控接口输出）
+#[derive(Debug, Clone, Serialize)]
+pub struct EndpointLatency {
+    pub index: usize,
+    /// 脱敏后的节点 URL
+    pub url: String,
+    /// 响应延迟 EWMA（毫秒），尚无样本时为 None
+    pub latency_ewma_ms: Option<f64>,
+    pub cooling_down: bool,
+    /// 因该节点返回 Retry-After 而等待的次数
+    pub retry_after_honored: u64,
+    /// 请求耗时直方图
+    pub latency_histogram: Vec<LatencyBucket>,
+}
+
+/// EWMA 平滑系数：新样本权重 1/5
+const LATENCY_EWMA_WEIGHT: u64 = 5;
+
+/// 批量获取收据时的默认并发上限
+pub(crate) const DEFAULT_RECEIPT_CONCURRENCY: usize = 8;
+
+/// 以 `concurrency` 为上限并发获取多笔收据，结果保持 `hashes` 的顺序（`get_transaction_receipts` 的公共实现）
+pub(crate) async fn fetch_receipts_concurrently<F, Fut>(
+    hashes: &[H256],
+    concurrency: usize,
+    fetch: F,
+) -> Result<Vec<(H256, Option<TransactionReceipt>)>, AppError>
+where
+    F: Fn(H256) -> Fut,
+    Fut: std::future::Future<Output = Result<Option<TransactionReceipt>, AppError>>,
+{
+    let results: Vec<(H256, Result<Option<TransactionReceipt>, AppError>)> =
+        stream::iter(hashes.iter().copied())
+            .map(|hash| {
+                let fut = fetch(hash);
+                async move { (hash, fut.await) }
+            })
+            .buffered(concurrency.max(1))
+            .collect()
+            .await;
+
+    let mut last_error = None;
+    let mut receipts = Vec::with_capacity(results.len());
+    for (hash, result) in results {
+        match result {
+            Ok(receipt) => receipts.push((hash, receipt)),
+            Err(e) => {
+                log_error!("交易 {:?} 获取收据失败: {:?}", hash, e);
+                last_error = Some(e);
+                receipts.push((hash, None));
+            }
+        }
+    }
+    match last_error {
+        Some(e) if receipts.iter().all(|(_, r)| r.is_none()) => Err(e),
+        _ => Ok(receipts),
+    }
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_millis() as u64)
+}
+
+/// 按 1 / 延迟加权，用 [0, 1) 内的 roll 落在哪段权重区间决定位置；0 表示尚无样本，按已知最快的延迟计
+fn weighted_by_latency(latencies: &[u64], roll: f64) -> Option<usize> {
+    if latencies.is_empty() {
+        return None;
+    }
+    let fastest = latencies.iter().copied().filter(|l| *l > 0).min().unwrap_or(1);
+    let weights: Vec<f64> = latencies
+        .iter()
+        .map(|&l| 1.0 / (if l == 0 { fastest } else { l }) as f64)
+        .collect();
+    let total: f64 = weights.iter().sum();
+    let mut target = roll * total;
+    for (pos, w) in weights.iter().enumerate() {
+        if target < *w {
+            return Some(pos);
+        }
+        target -= w;
+    }
+    Some(latencies.len() - 1)
 }
 
-impl EthereumProvider {
+impl EthereumProvider<Http> {
     pub fn new(config: &EthereumConfig) -> Self {
-        let providers = config
+        // 配置了自定义请求头时，所有节点共用一个带默认请求头的 reqwest::Client
+        let client = (!config.rpc_headers.is_empty())
+            .then(|| build_http_client(&config.rpc_headers, None));
+        let mut providers = config
             .api_keys
             .split(',')
             .map(|k| k.trim())
@@ -58,29 +283,279 @@ impl EthereumProvider {
                     url =
                         Url::parse(&format!("{}{}", config.rpc_url, key)).expect("Invalid RPC URL");
                 }
-                Arc::new(Provider::<Http>::try_from(url.as_str()).expect("Invalid RPC URL"))
+                match &client {
+                    Some(client) => Arc::new(Provider::new(Http::new_with_client(url, client.clone()))),
+                    None => Arc::new(
+                        Provider::<Http>::try_from(url.as_str()).expect("Invalid RPC URL"),
+                    ),
+                }
             })
             .collect::<Vec<_>>();
+        let mut labels: Vec<String> = providers.iter().map(|p| mask_rpc_url(p.url())).collect();
+
+        // 完整 URL 的节点，各自携带请求头 / 认证
+        for endpoint in &config.rpc_endpoints {
+            let url = Url::parse(&endpoint.url).expect("Invalid RPC endpoint URL");
+            labels.push(mask_rpc_url(&url));
+            let mut headers = config.rpc_headers.clone();
+            headers.extend(endpoint.headers.clone());
+            let client = build_http_client(&headers, endpoint.auth.as_ref());
+            providers.push(Arc::new(Provider::new(Http::new_with_client(url, client))));
+        }
 
         log_info!("成功初始化 {} 个RPC Provider", providers.len());
-        assert!(!providers.is_empty(), "No valid api keys provided");
+        assert!(!providers.is_empty(), "No valid api keys or rpc endpoints provided");
 
         Self {
+            endpoints: (0..providers.len()).map(|_| EndpointState::default()).collect(),
             providers,
+            labels,
             index: AtomicUsize::new(0),
+            capabilities: ProviderCapabilities::new(),
+            selection: config.provider_selection,
+            slow_call_threshold: None,
         }
+        .with_slow_call_threshold(config.slow_rpc_threshold_ms)
     }
 
-    pub fn get_provider(&self) -> Arc<Provider<Http>> {
-        let i = self.index.fetch_add(1, Ordering::Relaxed);
-        self.providers[i % self.providers.len()].clone()
+}
+
+/// 节点 URL 的脱敏形式：只保留 scheme、host 与端口，路径 / 查询中常带 API key
+fn mask_rpc_url(url: &Url) -> String {
+    let port = url.port().map(|p| format!(":{}", p)).unwrap_or_default();
+    let hidden = if url.path().len() > 1 || url.query().is_some() {
+        "/***"
+    } else {
+        ""
+    };
+    format!(
+        "{}://{}{}{}",
+        url.scheme(),
+        url.host_str().unwrap_or_default(),
+        port,
+        hidden
+    )
+}
+
+/// 根据配置的请求头与认证方式构建 HTTP 客户端
+fn build_http_client(headers: &HashMap<String, String>, auth: Option<&RpcAuth>) -> reqwest::Client {
+    let mut header_map = HeaderMap::new();
+    for (name, value) in headers {
+        let name = HeaderName::from_bytes(name.as_bytes())
+            .unwrap_or_else(|_| panic!("Invalid RPC header name: {}", name));
+        let mut value = HeaderValue::from_str(value)
+            .unwrap_or_else(|_| panic!("Invalid RPC header value for {}", name));
+        // 避免认证信息出现在 Debug 输出中
+        value.set_sensitive(true);
+        header_map.insert(name, value);
+    }
+    if let Some(auth) = auth {
+        let auth = match auth {
+            RpcAuth::Basic { username, password } => Authorization::basic(username, password),
+            RpcAuth::Bearer { token } => Authorization::bearer(token),
+        };
+        let mut value = HeaderValue::from_str(&auth.to_string())
+            .expect("Invalid RPC authorization value");
+        value.set_sensitive(true);
+        header_map.insert(AUTHORIZATION, value);
     }
+    log_info!("RPC 请求附加 {} 个自定义请求头", header_map.len());
+    reqwest::Client::builder()
+        .default_headers(header_map)
+        .build()
+        .expect("Failed to build RPC http client")
 }
+
+#[cfg(feature = "ipc")]
+impl EthereumProvider<Ipc> {
+    /// 连接本机节点（reth/geth）的 IPC socket，单个 IPC 端点视为唯一的 provider
+    pub async fn connect_ipc(path: &str) -> Result<Self, AppError> {
+        let path = path.strip_prefix("ipc://").unwrap_or(path);
+        let provider = Provider::<Ipc>::connect_ipc(path)
+            .await
+            .map_err(|e| AppError::ProviderError(format!("IPC 连接失败 {}: {}", path, e)))?;
+        log_info!("成功连接 IPC Provider: {}", path);
+        Ok(Self {
+            providers: vec![Arc::new(provider)],
+            index: AtomicUsize::new(0),
+            capabilities: ProviderCapabilities::new(),
+            endpoints: vec![EndpointState::default()],
+            labels: vec!["ipc".to_string()],
+            selection: ProviderSelection::RoundRobin,
+            slow_call_threshold: None,
+        })
+    }
+}
+
+impl<P: JsonRpcClient> EthereumProvider<P> {
+    /// 由现成的传输层构建（测试中注入 MockProvider），按轮询选择节点
+    #[cfg(test)]
+    pub(crate) fn from_transports(transports: Vec<P>) -> Self {
+        let len = transports.len();
+        Self {
+            providers: transports.into_iter().map(|t| Arc::new(Provider::new(t))).collect(),
+            index: AtomicUsize::new(0),
+            capabilities: ProviderCapabilities::new(),
+            endpoints: (0..len).map(|_| EndpointState::default()).collect(),
+            labels: (0..len).map(|i| format!("mock#{}", i)).collect(),
+            selection: ProviderSelection::RoundRobin,
+            slow_call_threshold: None,
+        }
+    }
+
+    pub fn get_provider(&self) -> Arc<Provider<P>> {
+        self.next_provider().1
+    }
+
+    /// 设置慢调用阈值（毫秒），0 表示不记录慢调用
+    pub fn with_slow_call_threshold(mut self, threshold_ms: u64) -> Self {
+        self.slow_call_threshold = (threshold_ms > 0).then(|| Duration::from_millis(threshold_ms));
+        self
+    }
+
+    /// 在下一个节点上执行一次请求并记录耗时
+    async fn timed<T, Fut>(&self, f: impl FnOnce(Arc<Provider<P>>) -> Fut) -> Result<T, ProviderError>
+    where
+        Fut: Future<Output = Result<T, ProviderError>>,
+    {
+        let (idx, p) = self.next_provider();
+        let started = Instant::now();
+        let result = f(p).await;
+        self.record_latency(idx, started.elapsed());
+        result
+    }
+
+    /// 取下一个节点及其下标，跳过冷却中的节点；所有节点都在冷却时按原顺序返回
+    /// 按配置轮询，或按延迟加权选择
+    pub fn next_provider(&self) -> (usize, Arc<Provider<P>>) {
+        let len = self.providers.len();
+        let start = self.index.fetch_add(1, Ordering::Relaxed);
+        let now = now_ms();
+        let available = |i: &usize| self.endpoints[*i].cooldown_until_ms.load(Ordering::Relaxed) <= now;
+        let idx = match self.selection {
+            ProviderSelection::LatencyAware if len > 1 => {
+                let candidates: Vec<usize> = (0..len).filter(available).collect();
+                self.pick_by_latency(&candidates).unwrap_or(start % len)
+            }
+            _ => (0..len)
+                .map(|offset| (start + offset) % len)
+                .find(available)
+                .unwrap_or(start % len),
+        };
+        (idx, self.providers[idx].clone())
+    }
+
+    /// 按 1 / 延迟加权随机选择；还没有样本的节点按已知最快的延迟计，保证新节点也能被探测到
+    fn pick_by_latency(&self, candidates: &[usize]) -> Option<usize> {
+        let latencies: Vec<u64> = candidates
+            .iter()
+            .map(|&i| self.endpoints[i].latency_ewma_us.load(Ordering::Relaxed))
+            .collect();
+        let roll = rand::thread_rng().gen_range(0.0..1.0);
+        weighted_by_latency(&latencies, roll).map(|pos| candidates[pos])
+    }
+
+    /// 记录一次请求耗时：更新该节点的延迟 EWMA 与直方图，超过慢调用阈值时记录警告
+    pub fn record_latency(&self, idx: usize, elapsed: Duration) {
+        let endpoint = &self.endpoints[idx];
+        let elapsed_ms = elapsed.as_millis() as u64;
+        let bucket = LATENCY_BUCKETS_MS
+            .iter()
+            .position(|le| elapsed_ms <= *le)
+            .unwrap_or(LATENCY_BUCKETS_MS.len());
+        endpoint.latency_buckets[bucket].fetch_add(1, Ordering::Relaxed);
+        if self.slow_call_threshold.is_some_and(|t| elapsed > t) {
+            log_warn!("节点 #{} ({}) 请求耗时 {:?}，超过慢调用阈值", idx, self.labels[idx], elapsed);
+        }
+
+        let sample = (elapsed.as_micros() as u64).max(1);
+        let state = &endpoint.latency_ewma_us;
+        let _ = state.fetch_update(Ordering::Relaxed, Ordering::Relaxed, |old| {
+            Some(if old == 0 {
+                sample
+            } else {
+                (old * (LATENCY_EWMA_WEIGHT - 1) + sample) / LATENCY_EWMA_WEIGHT
+            })
+        });
+    }
+
+    /// 各节点的延迟统计
+    pub fn latency_stats(&self) -> Vec<EndpointLatency> {
+        let now = now_ms();
+        self.endpoints
+            .iter()
+            .enumerate()
+            .map(|(index, state)| {
+                let ewma = state.latency_ewma_us.load(Ordering::Relaxed);
+                let mut cumulative = 0;
+                let latency_histogram = state
+                    .latency_buckets
+                    .iter()
+                    .enumerate()
+                    .map(|(i, count)| {
+                        cumulative += count.load(Ordering::Relaxed);
+                        LatencyBucket {
+                            le_ms: LATENCY_BUCKETS_MS.get(i).copied(),
+                            count: cumulative,
+                        }
+                    })
+                    .collect();
+                EndpointLatency {
+                    index,
+                    url: self.labels[index].clone(),
+                    latency_histogram,
+                    latency_ewma_ms: (ewma > 0).then(|| ewma as f64 / 1000.0),
+                    cooling_down: state.cooldown_until_ms.load(Ordering::Relaxed) > now,
+                    retry_after_honored: state.retry_after_honored.load(Ordering::Relaxed),
+                }
+            })
+            .collect()
+    }
+
+    /// 记录一次限流；连续达到 `cooldown_after` 次后该节点冷却 `cooldown_secs` 秒
+    pub fn record_rate_limited(&self, idx: usize, config: &RateLimitConfig) {
+        let state = &self.endpoints[idx];
+        let streak = state.streak.fetch_add(1, Ordering::Relaxed) + 1;
+        if config.cooldown_after > 0 && streak >= config.cooldown_after && self.providers.len() > 1 {
+            state.streak.store(0, Ordering::Relaxed);
+            state.cooldown_until_ms.fetch_max(
+                now_ms().saturating_add(config.cooldown_secs.saturating_mul(1000)),
+                Ordering::Relaxed,
+            );
+            log_warn!(
+                "节点 #{} 连续 {} 次被限流，冷却 {} 秒",
+                idx,
+                streak,
+                config.cooldown_secs
+            );
+        }
+    }
+
+    /// 节点返回了 Retry-After：在此之前不再选择该节点（其它节点都在冷却时除外），并计数；
+    /// 冷却时长不超过 `max_delay_ms`，避免服务端给出的超长等待让节点长期下线
+    pub fn record_retry_after(&self, idx: usize, retry_after: Duration, config: &RateLimitConfig) {
+        let state = &self.endpoints[idx];
+        state.retry_after_honored.fetch_add(1, Ordering::Relaxed);
+        let cooldown_ms = retry_after.as_millis().min(u128::from(config.max_delay_ms)) as u64;
+        state
+            .cooldown_until_ms
+            .fetch_max(now_ms().saturating_add(cooldown_ms), Ordering::Relaxed);
+    }
+
+    /// 请求成功，清零该节点的连续限流次数
+    pub fn record_success(&self, idx: usize) {
+        self.endpoints[idx].streak.store(0, Ordering::Relaxed);
+    }
+
+    pub fn capabilities(&self) -> &ProviderCapabilities {
+        &self.capabilities
+    }
+}
+
 #[async_trait]
-impl ProviderTrait for EthereumProvider {
+impl<P: JsonRpcClient + 'static> ProviderTrait for EthereumProvider<P> {
     async fn get_last_block_number(&self) -> Result<U64, AppError> {
-        self.get_provider()
-            .get_block_number()
+        self.timed(|p| async move { p.get_block_number().await })
             .await
             .map_err(AppError::from)
     }
@@ -89,8 +564,29 @@ impl ProviderTrait for EthereumProvider {
         &self,
         number: u64,
     ) -> Result<Option<Block<Transaction>>, AppError> {
-        self.get_provider()
-            .get_block_with_txs(number)
+        self.timed(|p| async move { p.get_block_with_txs(number).await })
+            .await
+            .map_err(AppError::from)
+    }
+
+    async fn get_block_by_hash(&self, hash: H256) -> Result<Option<Block<Transaction>>, AppError> {
+        self.timed(|p| async move { p.get_block_with_txs(hash).await })
+            .await
+            .map_err(AppError::from)
+    }
+
+    async fn get_logs(&self, filter: &Filter) -> Result<Vec<Log>, AppError> {
+        self.timed(|p| async move { p.get_logs(filter).await })
+            .await
+            .map_err(AppError::from)
+    }
+
+    async fn raw_request_value(
+        &self,
+        method: &str,
+        params: serde_json::Value,
+    ) -> Result<serde_json::Value, AppError> {
+        self.timed(|p| async move { p.request(method, params).await })
             .await
             .map_err(AppError::from)
     }
@@ -99,15 +595,34 @@ impl ProviderTrait for EthereumProvider {
         &self,
         tx_hash: H256,
     ) -> Result<Option<TransactionReceipt>, AppError> {
-        self.get_provider()
-            .get_transaction_receipt(tx_hash)
+        self.timed(|p| async move { p.get_transaction_receipt(tx_hash).await })
             .await
             .map_err(AppError::from)
     }
 
+    async fn get_transaction_receipts(
+        &self,
+        hashes: &[H256],
+    ) -> Result<Vec<(H256, Option<TransactionReceipt>)>, AppError> {
+        fetch_receipts_concurrently(hashes, DEFAULT_RECEIPT_CONCURRENCY, |hash| {
+            self.get_transaction_receipt(hash)
+        })
+        .await
+    }
+
+    async fn get_block_receipts(&self, number: u64) -> Result<Vec<TransactionReceipt>, AppError> {
+        let result = self
+            .timed(|p| async move { p.get_block_receipts(number).await })
+            .await
+            .map_err(AppError::from);
+        if let Err(AppError::MethodNotSupported(_)) = &result {
+            self.capabilities.record(RpcMethod::BlockReceipts, false);
+        }
+        result
+    }
+
     async fn get_chain_id(&self) -> Result<U256, AppError> {
-        self.get_provider()
-            .get_chainid()
+        self.timed(|p| async move { p.get_chainid().await })
             .await
             .map_err(AppError::from)
     }
@@ -116,8 +631,44 @@ impl ProviderTrait for EthereumProvider {
         let addr = address
             .parse::<Address>()
             .map_err(|_| AppError::InvalidAddress(address.to_string()))?;
-        self.get_provider()
-            .get_transaction_count(addr, None)
+        self.timed(|p| async move { p.get_transaction_count(addr, None).await })
+            .await
+            .map_err(AppError::from)
+    }
+
+    async fn get_pending_transaction_count(&self, address: &str) -> Result<U256, AppError> {
+        let addr = address
+            .parse::<Address>()
+            .map_err(|_| AppError::InvalidAddress(address.to_string()))?;
+        self.timed(|p| async move {
+            p.get_transaction_count(addr, Some(BlockNumber::Pending.into()))
+                .await
+        })
+        .await
+        .map_err(AppError::from)
+    }
+
+    async fn is_node_syncing(&self) -> Result<Option<SyncStatus>, AppError> {
+        self.timed(|p| async move { p.syncing().await })
+            .await
+            .map(SyncStatus::from_syncing)
+            .map_err(AppError::from)
+    }
+
+    async fn get_balance(&self, address: Address) -> Result<U256, AppError> {
+        self.timed(|p| async move { p.get_balance(address, None).await })
+            .await
+            .map_err(AppError::from)
+    }
+
+    async fn get_storage_at(&self, address: Address, slot: H256) -> Result<H256, AppError> {
+        self.timed(|p| async move { p.get_storage_at(address, slot, None).await })
+            .await
+            .map_err(AppError::from)
+    }
+
+    async fn get_code(&self, address: Address, block: Option<BlockNumber>) -> Result<Bytes, AppError> {
+        self.timed(|p| async move { p.get_code(address, block.map(Into::into)).await })
             .await
             .map_err(AppError::from)
     }
@@ -126,8 +677,7 @@ impl ProviderTrait for EthereumProvider {
         &self,
         estimator: Option<fn(U256, Vec<Vec<U256>>) -> (U256, U256)>,
     ) -> Result<(U256, U256), AppError> {
-        self.get_provider()
-            .estimate_eip1559_fees(estimator)
+        self.timed(|p| async move { p.estimate_eip1559_fees(estimator).await })
             .await
             .map_err(|e| AppError::ProviderError(format!("EIP1559 费用估算失败: {}", e)))
     }
@@ -136,24 +686,26 @@ impl ProviderTrait for EthereumProvider {
         &self,
         rlp: Bytes,
         timeout_secs: u64,
-        confirmations: usize,
+        target: ConfirmationTarget,
     ) -> Result<TransactionReceipt, AppError> {
+        let target = self.capabilities.resolve_confirmation(target);
         // 1. 先获取并持有 provider 的所有权 (Arc).确保在整个 await 期间，对应的 Http Client 不会被释放
-        let provider = self.get_provider();
-        // 2. 广播交易
-        let pending_tx = provider
-            .send_raw_transaction(rlp)
-            .await
-            .map_err(|e| AppError::ProviderError(format!("Broadcast failed: {}", e)))?;
+        let (idx, provider) = self.next_provider();
+        // 2. 广播交易（只统计广播本身的耗时，不含等待确认）
+        let started = Instant::now();
+        let broadcast = provider.send_raw_transaction(rlp).await;
+        self.record_latency(idx, started.elapsed());
+        let pending_tx = broadcast.map_err(|e| AppError::BroadcastFailed(e.to_string()))?;
+        let tx_hash = *pending_tx;
 
-        // 3. 等待链上确认
+        // 3. 按确认策略等待链上确认
         let receipt_result = timeout(
             std::time::Duration::from_secs(timeout_secs),
-            pending_tx.confirmations(confirmations),
+            wait_for_confirmation(&provider, pending_tx, target),
         )
         .await;
         let receipt = receipt_result
-            .map_err(|_| AppError::Internal("Transaction confirmation timeout".to_string()))? // 处理 timeout 包装
+            .map_err(|_| AppError::ConfirmationTimeout(format!("{:?}", tx_hash)))? // 处理 timeout 包装
             .map_err(|e| AppError::Internal(format!("Wait receipt error: {}", e)))? // 处理中间件错误
             .ok_or_else(|| AppError::Internal("Transaction dropped from mempool".to_string()))?; // 处理掉包
 
@@ -161,10 +713,9 @@ impl ProviderTrait for EthereumProvider {
         // status 为 None 通常出现在非 EIP-1559 或老旧节点，但在现代以太坊网络中通常有值
         if let Some(status) = receipt.status {
             if status.is_zero() {
-                return Err(AppError::Internal(format!(
-                    "Transaction reverted on-chain. Hash: {:?}",
-                    receipt.transaction_hash
-                )));
+                return Err(AppError::Reverted {
+                    reason: format!("Transaction reverted on-chain. Hash: {:?}", receipt.transaction_hash),
+                });
             }
         }
         log_info!(
@@ -177,16 +728,141 @@ impl ProviderTrait for EthereumProvider {
     }
 
     async fn call(&self, tx: &TypedTransaction) -> Result<Bytes, AppError> {
-        self.get_provider()
-            .call(tx, None)
+        self.timed(|p| async move { p.call(tx, None).await })
             .await
-            .map_err(|e| AppError::ProviderError(format!("Call simulation failed: {}", e)))
+            .map_err(|e| with_context(e, "Call simulation failed"))
     }
 
     async fn estimate_gas(&self, tx: &TypedTransaction) -> Result<U256, AppError> {
-        self.get_provider()
-            .estimate_gas(tx, None)
+        self.timed(|p| async move { p.estimate_gas(tx, None).await })
+            .await
+            .map_err(|e| with_context(e, "estimate_gas failed"))
+    }
+
+    async fn block_hash_per_provider(&self, number: u64) -> Vec<Option<H256>> {
+        let mut hashes = Vec::with_capacity(self.providers.len());
+        for (i, provider) in self.providers.iter().enumerate() {
+            let started = Instant::now();
+            let result = provider.get_block(number).await;
+            self.record_latency(i, started.elapsed());
+            let hash = match result {
+                Ok(block) => block.and_then(|b| b.hash),
+                Err(e) => {
+                    log_warn!("节点 #{} 查询区块 {} 失败: {}", i, number, e);
+                    None
+                }
+            };
+            hashes.push(hash);
+        }
+        hashes
+    }
+
+    fn latency_stats(&self) -> Vec<EndpointLatency> {
+        EthereumProvider::latency_stats(self)
+    }
+
+    fn supports(&self, method: RpcMethod) -> bool {
+        self.capabilities.supports(method)
+    }
+
+    async fn probe_capabilities(&self) {
+        self.capabilities.probe_all(&self.providers).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 以固定步长扫过 [0, 1)，统计每个位置被选中的次数
+    fn pick_counts(latencies: &[u64], steps: usize) -> Vec<usize> {
+        let mut counts = vec![0; latencies.len()];
+        for step in 0..steps {
+            let roll = step as f64 / steps as f64;
+            counts[weighted_by_latency(latencies, roll).unwrap()] += 1;
+        }
+        counts
+    }
+
+    #[test]
+    fn latency_pick_prefers_fastest_endpoint() {
+        // 权重 1/1ms : 1/10ms，最快节点约占 10/11
+        let counts = pick_counts(&[10_000, 1_000], 1_100);
+        assert!(counts[1] > counts[0] * 9, "{counts:?}");
+        assert_eq!(weighted_by_latency(&[10_000, 1_000], 0.5), Some(1));
+    }
+
+    #[test]
+    fn latency_pick_still_reaches_every_endpoint() {
+        let counts = pick_counts(&[1_000, 20_000, 500_000], 10_000);
+        assert!(counts.iter().all(|c| *c > 0), "{counts:?}");
+        // roll 接近 1 时落在最后一段
+        assert_eq!(weighted_by_latency(&[1_000, 20_000, 500_000], 0.9999), Some(2));
+    }
+
+    #[test]
+    fn latency_pick_treats_unsampled_endpoint_as_fastest() {
+        // 无样本的节点与最快节点同权重，各占一半
+        assert_eq!(pick_counts(&[0, 1_000], 1_000), vec![500, 500]);
+        assert_eq!(weighted_by_latency(&[], 0.3), None);
+    }
+
+    #[test]
+    fn retry_after_cooldown_is_clamped() {
+        let provider = EthereumProvider::from_transports(vec![
+            ethers_providers::MockProvider::new(),
+            ethers_providers::MockProvider::new(),
+        ]);
+        let config = RateLimitConfig {
+            max_delay_ms: 60_000,
+            ..RateLimitConfig::default()
+        };
+        let before = now_ms();
+        provider.record_retry_after(0, Duration::from_secs(86_400), &config);
+        let until = provider.endpoints[0].cooldown_until_ms.load(Ordering::Relaxed);
+        assert!(until >= before + 60_000 && until <= now_ms() + 60_000, "{until}");
+
+        provider.record_retry_after(1, Duration::MAX, &config);
+        let until = provider.endpoints[1].cooldown_until_ms.load(Ordering::Relaxed);
+        assert!(until <= now_ms() + 60_000, "{until}");
+    }
+
+    fn samples(provider: &EthereumProvider<ethers_providers::MockProvider>, idx: usize) -> u64 {
+        provider.endpoints[idx]
+            .latency_buckets
+            .iter()
+            .map(|b| b.load(Ordering::Relaxed))
+            .sum()
+    }
+
+    #[tokio::test]
+    async fn block_hash_per_provider_records_latency_for_every_node() {
+        let mocks = vec![ethers_providers::MockProvider::new(), ethers_providers::MockProvider::new()];
+        mocks[0].push(serde_json::Value::Null).unwrap();
+        mocks[1].push_response(ethers_providers::MockResponse::Error(ethers_providers::JsonRpcError {
+            code: -32000,
+            message: "unavailable".into(),
+            data: None,
+        }));
+        let provider = EthereumProvider::from_transports(mocks);
+        assert_eq!(provider.block_hash_per_provider(1).await, vec![None, None]);
+        assert_eq!((samples(&provider, 0), samples(&provider, 1)), (1, 1));
+    }
+
+    #[tokio::test]
+    async fn broadcast_records_latency() {
+        let mock = ethers_providers::MockProvider::new();
+        mock.push_response(ethers_providers::MockResponse::Error(ethers_providers::JsonRpcError {
+            code: -32000,
+            message: "nonce too low".into(),
+            data: None,
+        }));
+        let provider = EthereumProvider::from_transports(vec![mock]);
+        let err = provider
+            .send_raw_transaction(Bytes::from(vec![0x01]), 1, ConfirmationTarget::default())
             .await
-            .map_err(|e| AppError::ProviderError(format!("estimate_gas failed: {}", e)))
+            .unwrap_err();
+        assert!(matches!(err, AppError::BroadcastFailed(_)), "{err:?}");
+        assert_eq!(samples(&provider, 0), 1);
     }
 }
@@ -1,5 +1,14 @@
+pub mod block_cache;
+pub mod capabilities;
+pub mod confirmation;
 pub mod ethereum_provider;
 mod retry_adapter;
+pub mod singleflight;
 
-pub use ethereum_provider::{EthereumProvider, ProviderTrait};
+pub use block_cache::BlockCache;
+pub use capabilities::{ProviderCapabilities, RpcMethod};
+pub use confirmation::ConfirmationTarget;
+pub use ethereum_provider::{
+    EndpointLatency, EthereumProvider, LatencyBucket, ProviderTrait, SyncStatus,
+};
 pub use retry_adapter::RetryAdapter;
\ No newline at end of file
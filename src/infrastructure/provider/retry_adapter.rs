@@ -1,26 +1,55 @@
-use super::ethereum_provider::{EthereumProvider, ProviderTrait};
-use crate::errors::error::AppError;
+use super::block_cache::BlockCache;
+use super::capabilities::RpcMethod;
+use super::singleflight::SingleFlight;
+use super::confirmation::{ConfirmationTarget, wait_for_confirmation};
+use super::ethereum_provider::{
+    DEFAULT_RECEIPT_CONCURRENCY, EndpointLatency, EthereumProvider, ProviderTrait, SyncStatus,
+    fetch_receipts_concurrently,
+};
+use crate::config::RateLimitConfig;
+use crate::errors::error::{AppError, is_non_retryable, is_rate_limited, rate_limit_retry_after};
 use crate::{log_info, log_warn};
 use async_trait::async_trait;
-use ethers::prelude::{U64, U256};
+use ethers::prelude::{BlockNumber, U64, U256};
 use ethers::providers::ProviderError;
 use ethers_core::types::transaction::eip2718::TypedTransaction;
-use ethers_core::types::{Address, Block, Bytes, H256, Transaction, TransactionReceipt};
-use ethers_providers::{Http, Middleware, PendingTransaction};
+use ethers_core::types::{
+    Address, Block, Bytes, Filter, H256, Log, Transaction, TransactionReceipt,
+};
+use ethers_providers::{Http, JsonRpcClient, Middleware, PendingTransaction, Provider};
 use rand::Rng;
 use std::sync::Arc;
-use std::time::Duration;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+use tokio::sync::Semaphore;
 use tokio::time::sleep;
 
-pub struct RetryAdapter {
-    provider: Arc<EthereumProvider>,
+pub struct RetryAdapter<P = Http> {
+    provider: Arc<EthereumProvider<P>>,
     max_retries: usize,
     base_delay_secs: Duration,
+    /// 可选的历史区块缓存，命中时不再请求 RPC
+    block_cache: Option<Arc<BlockCache>>,
+    /// 可选的并发读合并（区块、收据）
+    singleflight: Option<ReadSingleFlight>,
+    /// 连续失败（重试耗尽）的调用次数，供告警使用
+    failure_streak: AtomicU64,
+    /// 限流错误的退避与节点冷却策略
+    rate_limit: RateLimitConfig,
+    /// 全局并发预算：每次尝试前获取许可，退避等待期间不占用
+    budget: Option<Arc<Semaphore>>,
+    /// get_transaction_receipts 的并发上限
+    receipt_concurrency: usize,
 }
 
-impl RetryAdapter {
+struct ReadSingleFlight {
+    blocks: SingleFlight<u64, Option<Block<Transaction>>>,
+    receipts: SingleFlight<H256, Option<TransactionReceipt>>,
+}
+
+impl<P: JsonRpcClient + 'static> RetryAdapter<P> {
     pub fn new(
-        provider: Arc<EthereumProvider>,
+        provider: Arc<EthereumProvider<P>>,
         max_retries: usize,
         base_delay_secs: Duration,
     ) -> Self {
@@ -28,18 +57,100 @@ impl RetryAdapter {
             provider,
             max_retries,
             base_delay_secs,
+            block_cache: None,
+            singleflight: None,
+            failure_streak: AtomicU64::new(0),
+            rate_limit: RateLimitConfig::default(),
+            budget: None,
+            receipt_concurrency: DEFAULT_RECEIPT_CONCURRENCY,
         }
     }
 
-    async fn retry_call<T, Fut, F>(&self, mut f: F) -> Result<T, AppError>
+    pub fn with_receipt_concurrency(mut self, receipt_concurrency: usize) -> Self {
+        self.receipt_concurrency = receipt_concurrency.max(1);
+        self
+    }
+
+    /// 限制同时进行中的 RPC 请求数；同一个 Semaphore 可在多个 adapter 间共享
+    pub fn with_concurrency_budget(mut self, budget: Arc<Semaphore>) -> Self {
+        self.budget = Some(budget);
+        self
+    }
+
+    pub fn with_rate_limit(mut self, rate_limit: RateLimitConfig) -> Self {
+        self.rate_limit = rate_limit;
+        self
+    }
+
+    /// 限流时的等待时间：优先使用服务端的 Retry-After，否则 base × 2^(n-1)，均不超过 max_delay_ms
+    fn rate_limit_delay(&self, hits: u32, retry_after: Option<Duration>) -> Duration {
+        let max = Duration::from_millis(self.rate_limit.max_delay_ms);
+        let delay = retry_after.unwrap_or_else(|| {
+            let exponent = hits.saturating_sub(1).min(10);
+            Duration::from_millis(self.rate_limit.base_delay_ms.saturating_mul(1u64 << exponent))
+        });
+        delay.min(max)
+    }
+
+    /// 启用并发读合并：相同区块号 / 交易哈希的并发请求只发起一次 RPC
+    pub fn with_singleflight(mut self) -> Self {
+        self.singleflight = Some(ReadSingleFlight {
+            blocks: SingleFlight::new(),
+            receipts: SingleFlight::new(),
+        });
+        self
+    }
+
+    /// 被合并节省的 RPC 次数（用于监控）
+    pub fn coalesced_count(&self) -> u64 {
+        self.singleflight
+            .as_ref()
+            .map_or(0, |sf| sf.blocks.coalesced_count() + sf.receipts.coalesced_count())
+    }
+
+    pub fn with_block_cache(mut self, block_cache: Arc<BlockCache>) -> Self {
+        self.block_cache = Some(block_cache);
+        self
+    }
+
+    async fn retry_call<T, Fut, F>(&self, f: F) -> Result<T, AppError>
+    where
+        T: Send,
+        F: FnMut(Arc<Provider<P>>) -> Fut + Send,
+        Fut: std::future::Future<Output = Result<T, ProviderError>> + Send,
+    {
+        self.retry_call_then(f, |_, result| async move { Ok(result) })
+            .await
+    }
+
+    /// 与 `retry_call` 相同，但每次尝试在请求 `f` 成功后继续执行 `then`（如等待交易确认）；
+    /// `then` 失败同样触发重试，但不占用并发预算，也不计入节点延迟统计
+    async fn retry_call_then<T, U, Fut, F, ThenFut, Then>(
+        &self,
+        mut f: F,
+        mut then: Then,
+    ) -> Result<U, AppError>
     where
-        F: FnMut(Arc<ethers_providers::Provider<ethers_providers::Http>>) -> Fut + Send,
+        F: FnMut(Arc<Provider<P>>) -> Fut + Send,
         Fut: std::future::Future<Output = Result<T, ProviderError>> + Send,
+        Then: FnMut(Arc<Provider<P>>, T) -> ThenFut + Send,
+        ThenFut: std::future::Future<Output = Result<U, ProviderError>> + Send,
     {
         let mut last_error: Option<ProviderError> = None;
+        // 本次调用中遇到的限流次数，以及上一次失败是否为限流（及其建议等待时间）
+        let mut rate_limit_hits = 0u32;
+        let mut rate_limited: Option<Option<Duration>> = None;
         for attempt in 0..self.max_retries {
-            // 延迟逻辑：从第二次尝试 (attempt = 1) 开始执行
-            if attempt > 0 {
+            if let Some(retry_after) = rate_limited.take() {
+                let delay = self.rate_limit_delay(rate_limit_hits, retry_after);
+                log_warn!(
+                    "RPC 被限流，第 {} 次重试前等待 {:?}...",
+                    attempt + 1,
+                    delay
+                );
+                sleep(delay).await;
+            } else if attempt > 0 {
+                // 延迟逻辑：从第二次尝试 (attempt = 1) 开始执行
                 // 计算指数倍数，最高限制在 2^10 = 1024
                 let exponent = (attempt - 1).min(10);
                 let base_ms = self.base_delay_secs.as_millis() as u64;
@@ -61,35 +172,110 @@ impl RetryAdapter {
 
                 sleep(final_delay).await;
             }
-            let p = self.provider.get_provider();
-            match f(p).await {
-                Ok(result) => return Ok(result),
+            // 许可只覆盖请求本身，限流 / 失败后的等待不占用预算
+            let permit = match &self.budget {
+                Some(budget) => budget.acquire().await.ok(),
+                None => None,
+            };
+            let (idx, p) = self.provider.next_provider();
+            let started = Instant::now();
+            let outcome = f(Arc::clone(&p)).await;
+            self.provider.record_latency(idx, started.elapsed());
+            drop(permit);
+            let outcome = match outcome {
+                Ok(result) => then(p, result).await,
+                Err(e) => Err(e),
+            };
+            match outcome {
+                Ok(result) => {
+                    self.failure_streak.store(0, Ordering::Relaxed);
+                    self.provider.record_success(idx);
+                    return Ok(result);
+                }
+                // 节点不支持该方法、执行 revert 或参数无效，重试没有意义
+                Err(e) if is_non_retryable(&e) => {
+                    return Err(AppError::from(e));
+                }
+                Err(e) if is_rate_limited(&e) => {
+                    rate_limit_hits += 1;
+                    let retry_after = rate_limit_retry_after(
+                        &e,
+                        Duration::from_millis(self.rate_limit.max_delay_ms),
+                    );
+                    if let Some(retry_after) = retry_after {
+                        self.provider.record_retry_after(idx, retry_after, &self.rate_limit);
+                    }
+                    rate_limited = Some(retry_after);
+                    self.provider.record_rate_limited(idx, &self.rate_limit);
+                    last_error = Some(e);
+                    log_warn!("RPC 被限流 (节点 #{}, 第 {} 次): {:?}", idx, attempt + 1, last_error);
+                }
                 Err(e) => {
                     last_error = Some(e);
                     log_warn!("RPC 调用失败 (第 {} 次): {:?}", attempt + 1, last_error);
                 }
             }
         }
-        Err(AppError::ProviderError(format!(
-            "重试 {} 次失败，最后错误: {:?}",
-            self.max_retries, last_error
-        )))
+        self.failure_streak.fetch_add(1, Ordering::Relaxed);
+        let message = format!("重试 {} 次失败，最后错误: {:?}", self.max_retries, last_error);
+        if last_error.as_ref().is_some_and(is_rate_limited) {
+            return Err(AppError::RateLimited(message));
+        }
+        Err(AppError::ProviderError(message))
     }
 }
 
 #[async_trait]
-impl ProviderTrait for RetryAdapter {
+impl<P: JsonRpcClient + 'static> ProviderTrait for RetryAdapter<P> {
     async fn get_last_block_number(&self) -> Result<U64, AppError> {
-        self.retry_call(|p| async move { p.get_block_number().await })
-            .await
+        let head = self
+            .retry_call(|p| async move { p.get_block_number().await })
+            .await?;
+        if let Some(cache) = &self.block_cache {
+            cache.observe_head(head.as_u64());
+        }
+        Ok(head)
     }
 
     async fn get_block_with_txs(
         &self,
         number: u64,
     ) -> Result<Option<Block<Transaction>>, AppError> {
-        let number = number;
-        self.retry_call(move |p| async move { p.get_block_with_txs(number).await })
+        if let Some(block) = self.block_cache.as_ref().and_then(|c| c.get(number)) {
+            return Ok(Some(block));
+        }
+        let fetch = || self.retry_call(move |p| async move { p.get_block_with_txs(number).await });
+        let block = match &self.singleflight {
+            Some(sf) => sf.blocks.run(number, fetch).await?,
+            None => fetch().await?,
+        };
+        // 只缓存已脱离重组窗口的区块
+        if let (Some(cache), Some(b)) = (&self.block_cache, &block) {
+            cache.insert(number, b);
+        }
+        Ok(block)
+    }
+
+    async fn raw_request_value(
+        &self,
+        method: &str,
+        params: serde_json::Value,
+    ) -> Result<serde_json::Value, AppError> {
+        self.retry_call(|p| {
+            let params = params.clone();
+            async move { p.request(method, params).await }
+        })
+        .await
+    }
+
+    async fn get_logs(&self, filter: &Filter) -> Result<Vec<Log>, AppError> {
+        self.retry_call(move |p| async move { p.get_logs(filter).await })
+            .await
+    }
+
+    /// 按哈希取区块不经过区块缓存（缓存按区块号索引，且分叉上的区块不应进入缓存）
+    async fn get_block_by_hash(&self, hash: H256) -> Result<Option<Block<Transaction>>, AppError> {
+        self.retry_call(move |p| async move { p.get_block_with_txs(hash).await })
             .await
     }
 
@@ -97,9 +283,33 @@ impl ProviderTrait for RetryAdapter {
         &self,
         tx_hash: H256,
     ) -> Result<Option<TransactionReceipt>, AppError> {
-        let tx_hash = tx_hash;
-        self.retry_call(move |p| async move { p.get_transaction_receipt(tx_hash).await })
-            .await
+        let fetch =
+            || self.retry_call(move |p| async move { p.get_transaction_receipt(tx_hash).await });
+        match &self.singleflight {
+            Some(sf) => sf.receipts.run(tx_hash, fetch).await,
+            None => fetch().await,
+        }
+    }
+
+    async fn get_transaction_receipts(
+        &self,
+        hashes: &[H256],
+    ) -> Result<Vec<(H256, Option<TransactionReceipt>)>, AppError> {
+        // 逐笔走 get_transaction_receipt，复用重试与并发读合并
+        fetch_receipts_concurrently(hashes, self.receipt_concurrency, |hash| {
+            self.get_transaction_receipt(hash)
+        })
+        .await
+    }
+
+    async fn get_block_receipts(&self, number: u64) -> Result<Vec<TransactionReceipt>, AppError> {
+        let result = self
+            .retry_call(move |p| async move { p.get_block_receipts(number).await })
+            .await;
+        if let Err(AppError::MethodNotSupported(_)) = &result {
+            self.provider.capabilities().record(RpcMethod::BlockReceipts, false);
+        }
+        result
     }
 
     async fn get_chain_id(&self) -> Result<U256, AppError> {
@@ -116,6 +326,39 @@ impl ProviderTrait for RetryAdapter {
             .await
     }
 
+    async fn get_pending_transaction_count(&self, address: &str) -> Result<U256, AppError> {
+        let addr = address
+            .parse::<Address>()
+            .map_err(|_| AppError::InvalidAddress(address.to_string()))?;
+
+        self.retry_call(move |p| async move {
+            p.get_transaction_count(addr, Some(BlockNumber::Pending.into()))
+                .await
+        })
+        .await
+    }
+
+    async fn is_node_syncing(&self) -> Result<Option<SyncStatus>, AppError> {
+        self.retry_call(|p| async move { p.syncing().await })
+            .await
+            .map(SyncStatus::from_syncing)
+    }
+
+    async fn get_balance(&self, address: Address) -> Result<U256, AppError> {
+        self.retry_call(move |p| async move { p.get_balance(address, None).await })
+            .await
+    }
+
+    async fn get_storage_at(&self, address: Address, slot: H256) -> Result<H256, AppError> {
+        self.retry_call(move |p| async move { p.get_storage_at(address, slot, None).await })
+            .await
+    }
+
+    async fn get_code(&self, address: Address, block: Option<BlockNumber>) -> Result<Bytes, AppError> {
+        self.retry_call(move |p| async move { p.get_code(address, block.map(Into::into)).await })
+            .await
+    }
+
     async fn estimate_eip1559_fees(
         &self,
         estimator: Option<fn(U256, Vec<Vec<U256>>) -> (U256, U256)>,
@@ -129,42 +372,70 @@ impl ProviderTrait for RetryAdapter {
         &self,
         rlp: Bytes,
         timeout_secs: u64,
-        confirmations: usize,
+        target: ConfirmationTarget,
     ) -> Result<TransactionReceipt, AppError> {
-        // 1. 调用 retry_call，内部只处理网络/节点层的重试
+        let target = self.provider.capabilities().resolve_confirmation(target);
+        // 记录最后一次尝试是否因确认超时失败，重试耗尽后据此返回 ConfirmationTimeout
+        let timed_out = Arc::new(AtomicBool::new(false));
+        // 是否有节点接收过该交易；从未广播成功时返回 BroadcastFailed，调用方可安全释放 nonce
+        let broadcasted = Arc::new(AtomicBool::new(false));
+        // 1. 调用 retry_call_then，内部只处理网络/节点层的重试
+        // 并发预算与延迟统计只覆盖广播本身，等待确认可能长达 timeout_secs，不能占用同步所需的许可
         let receipt = self
-            .retry_call(move |p| {
-                let rlp = rlp.clone();
-                async move {
-                    // 1. 发送交易
-                    let pending_tx = p.send_raw_transaction(rlp).await?;
-
-                    // 2. 等待确认 (将等待逻辑也放入重试闭包内)
-                    // 注意：如果等待超时，也会触发重试
-                    let wait_res = tokio::time::timeout(
-                        Duration::from_secs(timeout_secs),
-                        pending_tx.confirmations(confirmations),
-                    )
-                    .await;
-                    // 处理超时和结果，并统一转为 ProviderError 以便触发重试
-                    match wait_res {
-                        Ok(Ok(Some(r))) => Ok(r),
-                        Ok(Ok(None)) => {
-                            Err(ProviderError::CustomError("Dropped from mempool".into()))
+            .retry_call_then(
+                |p| {
+                    let rlp = rlp.clone();
+                    let timed_out = Arc::clone(&timed_out);
+                    let broadcasted = Arc::clone(&broadcasted);
+                    async move {
+                        timed_out.store(false, Ordering::Relaxed);
+                        // 1. 发送交易
+                        let tx_hash = p.send_raw_transaction(rlp).await.map(|pending| *pending)?;
+                        broadcasted.store(true, Ordering::Relaxed);
+                        Ok(tx_hash)
+                    }
+                },
+                |p, tx_hash| {
+                    let timed_out = Arc::clone(&timed_out);
+                    async move {
+                        // 2. 等待确认，超时同样触发重试（重新广播同一笔交易）
+                        let pending_tx = PendingTransaction::new(tx_hash, p.as_ref());
+                        let wait_res = tokio::time::timeout(
+                            Duration::from_secs(timeout_secs),
+                            wait_for_confirmation(&p, pending_tx, target),
+                        )
+                        .await;
+                        // 处理超时和结果，并统一转为 ProviderError 以便触发重试
+                        match wait_res {
+                            Ok(Ok(Some(r))) => Ok(r),
+                            Ok(Ok(None)) => {
+                                Err(ProviderError::CustomError("Dropped from mempool".into()))
+                            }
+                            Ok(Err(e)) => Err(e), // Provider 级错误
+                            Err(_) => {
+                                timed_out.store(true, Ordering::Relaxed);
+                                Err(ProviderError::CustomError("Timeout".into()))
+                            }
                         }
-                        Ok(Err(e)) => Err(e), // Provider 级错误
-                        Err(_) => Err(ProviderError::CustomError("Timeout".into())),
                     }
+                },
+            )
+            .await
+            .map_err(|e| {
+                if !broadcasted.load(Ordering::Relaxed) {
+                    AppError::BroadcastFailed(e.to_string())
+                } else if timed_out.load(Ordering::Relaxed) {
+                    AppError::ConfirmationTimeout(e.to_string())
+                } else {
+                    e
                 }
-            })
-            .await?;
+            })?;
         //2. 拿到回执后，在重试逻辑外检查业务状态 (Status)
         // 这样如果 Revert，会直接返回给上层，而不会在 RetryAdapter 里盲目重试
         if receipt.status == Some(0.into()) {
-            return Err(AppError::Internal(format!(
-                "Transaction reverted! Hash: {:?}",
-                receipt.transaction_hash
-            )));
+            return Err(AppError::Reverted {
+                reason: format!("Transaction reverted! Hash: {:?}", receipt.transaction_hash),
+            });
         }
         log_info!(
             "交易执行成功: hash={:?}, block={:?}",
@@ -189,4 +460,119 @@ impl ProviderTrait for RetryAdapter {
         })
         .await
     }
+
+    fn supports(&self, method: RpcMethod) -> bool {
+        self.provider.supports(method)
+    }
+
+    async fn probe_capabilities(&self) {
+        self.provider.probe_capabilities().await;
+    }
+
+    async fn block_hash_per_provider(&self, number: u64) -> Vec<Option<H256>> {
+        self.provider.block_hash_per_provider(number).await
+    }
+
+    fn latency_stats(&self) -> Vec<EndpointLatency> {
+        self.provider.latency_stats()
+    }
+
+    fn consecutive_failures(&self) -> u64 {
+        self.failure_streak.load(Ordering::Relaxed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers_providers::{JsonRpcError, MockProvider, MockResponse};
+
+    fn rate_limit(base_delay_ms: u64, max_delay_ms: u64) -> RateLimitConfig {
+        RateLimitConfig {
+            base_delay_ms,
+            max_delay_ms,
+            ..RateLimitConfig::default()
+        }
+    }
+
+    /// 单节点 adapter：通用退避为 0，只有限流路径会等待
+    fn adapter(mock: &MockProvider, rate_limit: RateLimitConfig) -> RetryAdapter<MockProvider> {
+        let provider = Arc::new(EthereumProvider::from_transports(vec![mock.clone()]));
+        RetryAdapter::new(provider, 2, Duration::ZERO).with_rate_limit(rate_limit)
+    }
+
+    fn rpc_error(code: i64, message: &str) -> MockResponse {
+        MockResponse::Error(JsonRpcError {
+            code,
+            message: message.to_string(),
+            data: None,
+        })
+    }
+
+    #[test]
+    fn rate_limit_delay_doubles_and_honors_retry_after() {
+        let adapter = adapter(&MockProvider::new(), rate_limit(1_000, 60_000));
+        assert_eq!(adapter.rate_limit_delay(1, None), Duration::from_secs(1));
+        assert_eq!(adapter.rate_limit_delay(3, None), Duration::from_secs(4));
+        assert_eq!(adapter.rate_limit_delay(20, None), Duration::from_secs(60));
+        assert_eq!(
+            adapter.rate_limit_delay(1, Some(Duration::from_secs(30))),
+            Duration::from_secs(30)
+        );
+        assert_eq!(
+            adapter.rate_limit_delay(1, Some(Duration::from_secs(120))),
+            Duration::from_secs(60)
+        );
+    }
+
+    #[tokio::test]
+    async fn rate_limited_response_takes_the_longer_delay() {
+        let mock = MockProvider::new();
+        // 响应从队尾弹出：先返回 429，再返回区块高度
+        mock.push(U64::from(16)).unwrap();
+        mock.push_response(rpc_error(429, "Too Many Requests"));
+        let adapter = adapter(&mock, rate_limit(200, 1_000));
+
+        let started = Instant::now();
+        assert_eq!(adapter.get_last_block_number().await.unwrap(), U64::from(16));
+        assert!(started.elapsed() >= Duration::from_millis(200), "{:?}", started.elapsed());
+    }
+
+    #[tokio::test]
+    async fn generic_failure_uses_the_regular_backoff() {
+        let mock = MockProvider::new();
+        mock.push(U64::from(16)).unwrap();
+        mock.push_response(rpc_error(-32603, "internal error"));
+        let adapter = adapter(&mock, rate_limit(200, 1_000));
+
+        let started = Instant::now();
+        assert_eq!(adapter.get_last_block_number().await.unwrap(), U64::from(16));
+        assert!(started.elapsed() < Duration::from_millis(200), "{:?}", started.elapsed());
+    }
+
+    #[tokio::test]
+    async fn exhausted_rate_limit_retries_return_rate_limited() {
+        let mock = MockProvider::new();
+        mock.push_response(rpc_error(429, "Too Many Requests"));
+        mock.push_response(rpc_error(429, "Too Many Requests"));
+        let adapter = adapter(&mock, rate_limit(1, 1));
+
+        assert!(matches!(
+            adapter.get_last_block_number().await,
+            Err(AppError::RateLimited(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn rejected_broadcast_is_reported_as_broadcast_failed() {
+        let mock = MockProvider::new();
+        mock.push_response(rpc_error(-32000, "nonce too low"));
+        mock.push_response(rpc_error(-32000, "nonce too low"));
+        let adapter = adapter(&mock, rate_limit(1, 1));
+
+        let result = adapter
+            .send_raw_transaction(Bytes::from(vec![0x02]), 1, ConfirmationTarget::Blocks(1))
+            .await;
+        assert!(matches!(result, Err(AppError::BroadcastFailed(_))), "{result:?}");
+    }
 }
@@ -0,0 +1,102 @@
+use crate::errors::error::AppError;
+use std::collections::HashMap;
+use std::future::Future;
+use std::hash::Hash;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicU64, Ordering};
+use tokio::sync::broadcast;
+
+/// 合并并发的相同读请求：同一个 key 同时只发起一次真实 RPC，其余调用者等待并共享结果
+///
+/// 第一个到达的调用者（leader）直接执行请求，结果通过 broadcast 发给等待者。
+/// leader 被取消时 Sender 随 guard 一起被移除，等待者收到 Closed 后各自重新执行，不会永久挂起。
+pub struct SingleFlight<K, V> {
+    in_flight: Mutex<HashMap<K, broadcast::Sender<Result<V, String>>>>,
+    /// 被合并（节省）的请求次数
+    coalesced: AtomicU64,
+}
+
+/// leader 结束（正常返回或被取消）时移除 in-flight 记录
+struct InFlightGuard<'a, K: Eq + Hash, V> {
+    flight: &'a SingleFlight<K, V>,
+    key: Option<K>,
+}
+
+impl<K: Eq + Hash, V> InFlightGuard<'_, K, V> {
+    fn finish(mut self) -> Option<broadcast::Sender<Result<V, String>>> {
+        let key = self.key.take()?;
+        self.flight.in_flight.lock().unwrap().remove(&key)
+    }
+}
+
+impl<K: Eq + Hash, V> Drop for InFlightGuard<'_, K, V> {
+    fn drop(&mut self) {
+        if let Some(key) = self.key.take() {
+            self.flight.in_flight.lock().unwrap().remove(&key);
+        }
+    }
+}
+
+impl<K, V> Default for SingleFlight<K, V>
+where
+    K: Eq + Hash + Clone,
+    V: Clone,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K, V> SingleFlight<K, V>
+where
+    K: Eq + Hash + Clone,
+    V: Clone,
+{
+    pub fn new() -> Self {
+        Self {
+            in_flight: Mutex::new(HashMap::new()),
+            coalesced: AtomicU64::new(0),
+        }
+    }
+
+    pub fn coalesced_count(&self) -> u64 {
+        self.coalesced.load(Ordering::Relaxed)
+    }
+
+    pub async fn run<F, Fut>(&self, key: K, f: F) -> Result<V, AppError>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<V, AppError>>,
+    {
+        let waiter = {
+            let mut in_flight = self.in_flight.lock().unwrap();
+            match in_flight.get(&key) {
+                Some(tx) => Some(tx.subscribe()),
+                None => {
+                    let (tx, _) = broadcast::channel(1);
+                    in_flight.insert(key.clone(), tx);
+                    None
+                }
+            }
+        };
+
+        if let Some(mut rx) = waiter {
+            if let Ok(shared) = rx.recv().await {
+                self.coalesced.fetch_add(1, Ordering::Relaxed);
+                return shared.map_err(AppError::ProviderError);
+            }
+            // leader 被取消，自己执行
+            return f().await;
+        }
+
+        let guard = InFlightGuard {
+            flight: self,
+            key: Some(key),
+        };
+        let result = f().await;
+        if let Some(tx) = guard.finish() {
+            let _ = tx.send(result.as_ref().map(V::clone).map_err(|e| e.to_string()));
+        }
+        result
+    }
+}
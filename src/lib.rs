@@ -1,2 +1,15 @@
 // 应用结果类型
-// pub type Result<T> = std::result::Result<T, AppError>;
\ No newline at end of file
+// pub type Result<T> = std::result::Result<T, AppError>;
+
+pub mod api;
+pub mod cli;
+pub mod config;
+pub mod database;
+pub mod errors;
+
+pub mod infrastructure;
+pub mod models;
+pub mod repositories;
+pub mod services;
+pub mod startup;
+pub mod utils;
@@ -1,23 +1,16 @@
-use crate::config::Config;
-use crate::utils::logger::init_logger;
 use anyhow::Context;
-use crate::startup::startup::Application;
-
-mod cli;
-mod config;
-mod database;
-mod errors;
-
-mod infrastructure;
-mod models;
-mod repositories;
-mod services;
-mod startup;
-mod utils;
+use ethereum_rs::config::Config;
+use ethereum_rs::log_info;
+use ethereum_rs::startup::startup::Application;
+use ethereum_rs::utils::logger::init_logger;
+use ethereum_rs::utils::telemetry::{init_tracing, shutdown_tracing};
+
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
     // 初始化日志（全局只需调用一次）
     init_logger();
+    // 可选的 OTLP 链路追踪（OTEL_EXPORTER_OTLP_ENDPOINT）
+    init_tracing();
 
     // 打印不同级别日志
     // log_trace!("这是TRACE级日志（开发调试用）");
@@ -42,10 +35,12 @@ async fn main() -> anyhow::Result<()> {
 
     // 3. 运行应用核心服务
     // run 函数包含了启动后台任务和主循环逻辑
-    application
+    let result = application
         .run()
         .await
-        .context("Application core service failed during runtime")?;
+        .context("Application core service failed during runtime");
+    shutdown_tracing();
+    result?;
 
     // 如果 run() 正常退出，则返回 Ok
     Ok(())
@@ -0,0 +1,54 @@
+use crate::models::Transfer;
+use crate::models::db::schema::address_stats;
+use diesel::{Insertable, Queryable};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// 单个区块内某地址的增量统计，upsert 时与已有记录合并
+#[derive(Debug, Clone, Insertable)]
+#[diesel(table_name = address_stats)]
+pub struct AddressStatsInsert {
+    pub address: String,
+    pub chain_id: i64,
+    pub first_seen_block: i64,
+    pub last_seen_block: i64,
+    pub total_transfers: i64,
+}
+
+impl AddressStatsInsert {
+    /// 按地址汇总一批转账（发送方与接收方各计一次，发送方未知时只计接收方）
+    pub fn from_transfers(chain_id: i64, transfers: &[Transfer]) -> Vec<AddressStatsInsert> {
+        let mut stats: HashMap<&str, AddressStatsInsert> = HashMap::new();
+        for transfer in transfers {
+            let from = (!transfer.sender_unknown).then(|| transfer.from_address.as_str());
+            let to = Some(transfer.to_address.as_str());
+            // 自转账只计一次
+            let to = to.filter(|to| Some(*to) != from);
+            for address in from.into_iter().chain(to) {
+                let entry = stats.entry(address).or_insert_with(|| AddressStatsInsert {
+                    address: address.to_string(),
+                    chain_id,
+                    first_seen_block: transfer.block_number,
+                    last_seen_block: transfer.block_number,
+                    total_transfers: 0,
+                });
+                entry.first_seen_block = entry.first_seen_block.min(transfer.block_number);
+                entry.last_seen_block = entry.last_seen_block.max(transfer.block_number);
+                entry.total_transfers += 1;
+            }
+        }
+        stats.into_values().collect()
+    }
+}
+
+/// address_stats 读模型
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable)]
+#[diesel(table_name = address_stats)]
+pub struct AddressStatsRow {
+    pub address: String,
+    pub chain_id: i64,
+    pub first_seen_block: i64,
+    pub last_seen_block: i64,
+    pub total_transfers: i64,
+    pub updated_at: chrono::NaiveDateTime,
+}
@@ -1,6 +1,6 @@
 use crate::errors::error::AppError;
-use crate::models::db::schema::eth_block;
-use bigdecimal::{BigDecimal, FromPrimitive};
+use crate::models::db::schema::{eth_block, eth_block_raw};
+use bigdecimal::BigDecimal;
 use diesel::{Insertable, Queryable};
 use serde::{Deserialize, Serialize};
 use crate::models::BlockDomain;
@@ -14,7 +14,22 @@ pub struct BlockInsert {
     pub gas_used: BigDecimal,              // Numeric(78,0) -> BigDecimal ✨
     pub base_fee_per_gas: BigDecimal,      // Numeric(78,0) -> BigDecimal ✨
     pub timestamp: i64,                    // BigInt -> i64 ✓
-    pub size: i32,                 // Int4 -> i32 ✓
+    pub size: Option<i32>,         // Nullable<Int4>，历史区块或节点未返回时为 NULL
+    pub tx_count: i32,             // Int4 -> i32 ✓
+    pub fee_recipient: Option<String>,
+    pub difficulty: Option<BigDecimal>,
+    pub total_difficulty: Option<BigDecimal>,
+    pub gas_limit: Option<BigDecimal>,
+    pub extra_data: Option<String>,
+    pub state_root: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Insertable)]
+#[diesel(table_name = eth_block_raw)]
+pub struct BlockRawInsert {
+    pub block_number: i64,
+    pub block_hash: String,
+    pub raw: serde_json::Value,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Queryable)]
@@ -23,34 +38,28 @@ pub struct BlockRow {
     pub block_number: i64,
     pub block_hash: String,
     pub parent_hash: String,
+    pub timestamp: i64,
 }
 
 impl TryFrom<BlockDomain> for BlockInsert {
     type Error = AppError;
 
     fn try_from(block: BlockDomain) -> Result<BlockInsert, Self::Error> {
-        let gas_used = BigDecimal::from_i64(block.gas_used as i64).ok_or_else(|| {
-            AppError::Conversion(format!(
-                "区块 {}: gas_used ({}) 转换为 BigDecimal 失败",
-                block.block_number, block.gas_used
-            ))
-        })?;
-
-        let base_fee_per_gas = BigDecimal::from_i64(block.base_fee_per_gas as i64).ok_or_else(|| {
-            AppError::Conversion(format!(
-                "区块 {}: base_fee_per_gas ({}) 转换为 BigDecimal 失败",
-                block.block_number, block.base_fee_per_gas
-            ))
-        })?;
-
         Ok(Self {
             block_number: block.block_number,
             block_hash: block.block_hash,
             parent_hash: block.parent_hash,
-            gas_used,
-            base_fee_per_gas,
+            gas_used: block.gas_used,
+            base_fee_per_gas: block.base_fee_per_gas,
             timestamp: block.timestamp,
             size: block.size,
+            tx_count: block.tx_count,
+            fee_recipient: block.fee_recipient,
+            difficulty: block.difficulty,
+            total_difficulty: block.total_difficulty,
+            gas_limit: block.gas_limit,
+            extra_data: block.extra_data,
+            state_root: block.state_root,
         })
     }
 }
@@ -0,0 +1,26 @@
+use crate::models::db::schema::contract_deployment;
+use crate::models::domain::deployment::ContractDeployment;
+use diesel::Insertable;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize, Insertable)]
+#[diesel(table_name = contract_deployment)]
+pub struct ContractDeploymentInsert {
+    pub deployer: String,
+    pub contract_address: String,
+    pub tx_hash: String,
+    pub block_number: i64,
+    pub timestamp: i64,
+}
+
+impl From<ContractDeployment> for ContractDeploymentInsert {
+    fn from(deployment: ContractDeployment) -> Self {
+        Self {
+            deployer: deployment.deployer.into(),
+            contract_address: deployment.contract_address.into(),
+            tx_hash: deployment.tx_hash.into(),
+            block_number: deployment.block_number,
+            timestamp: deployment.timestamp,
+        }
+    }
+}
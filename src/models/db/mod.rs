@@ -1,4 +1,6 @@
+pub mod address_stats_db;
 pub mod block_db;
+pub mod deployment_db;
 pub mod schema;
 pub mod transfer_db;
 
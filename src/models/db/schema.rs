@@ -1,5 +1,11 @@
 pub use eth_block::table as eth_block_db;
 pub use eth_transfer::table as eth_transfer_db;
+pub use block_queue::table as block_queue_db;
+pub use address_stats::table as address_stats_db;
+pub use contract_deployment::table as contract_deployment_db;
+pub use sync_state::table as sync_state_db;
+pub use eth_block_raw::table as eth_block_raw_db;
+pub use eth_transfer_rejected::table as eth_transfer_rejected_db;
 
 diesel::table! {
     /// 以太坊区块表
@@ -20,8 +26,22 @@ diesel::table! {
         created_at -> Nullable<Timestamp>,
         /// 区块时间戳
         timestamp -> Int8,
-        /// 区块大小
-        size -> Int4,
+        /// 区块大小（字节），tx_count 迁移之前写入的历史区块为 NULL
+        size -> Nullable<Int4>,
+        /// 区块交易数
+        tx_count -> Int4,
+        /// 手续费接收地址（miner / author）
+        fee_recipient -> Nullable<Varchar>,
+        /// 区块难度
+        difficulty -> Nullable<Numeric>,
+        /// 累计难度
+        total_difficulty -> Nullable<Numeric>,
+        /// Gas 上限
+        gas_limit -> Nullable<Numeric>,
+        /// 区块头 extra_data
+        extra_data -> Nullable<Text>,
+        /// 状态根
+        state_root -> Nullable<Varchar>,
     }
 }
 
@@ -53,5 +73,121 @@ diesel::table! {
         /// 创建时间
         created_at -> Nullable<Timestamp>,
         log_index -> Int8,
+        /// EIP-2930 访问列表
+        access_list -> Nullable<Jsonb>,
+        /// 实际支付的手续费
+        fee_paid -> Numeric,
+        /// 发送方未知（未签名/系统交易）
+        sender_unknown -> Bool,
+        /// 转账类型 0=原生ETH 1=ERC20 2=ERC721 3=ERC1155 4=内部转账 5=WETH包装 6=验证者提款
+        kind -> Int2,
+        /// 收款方是否为合约，未检测时为空
+        to_contract -> Nullable<Bool>,
+        /// 验证者提款的验证者序号
+        validator_index -> Nullable<Int8>,
+    }
+}
+
+diesel::table! {
+    /// 区块处理队列
+    block_queue (block_number) {
+        /// 区块号
+        block_number -> Int8,
+        /// 状态 0=待处理 1=处理中
+        status -> Int2,
+        /// 领取次数
+        attempts -> Int4,
+        /// 领取该区块的 worker
+        worker_id -> Nullable<Varchar>,
+        /// 领取时间
+        locked_at -> Nullable<Timestamp>,
+        /// 最近一次处理失败的原因
+        last_error -> Nullable<Text>,
+        /// 入队时间
+        enqueued_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    /// 地址活跃度汇总
+    address_stats (address, chain_id) {
+        /// 地址
+        address -> Varchar,
+        /// 链 ID
+        chain_id -> Int8,
+        /// 首次出现的区块
+        first_seen_block -> Int8,
+        /// 最近一次出现的区块
+        last_seen_block -> Int8,
+        /// 作为发送方或接收方的转账总数
+        total_transfers -> Int8,
+        /// 更新时间
+        updated_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    /// 合约部署记录
+    contract_deployment (id) {
+        /// 主键 ID
+        id -> Int8,
+        /// 部署者
+        deployer -> Varchar,
+        /// 新合约地址
+        contract_address -> Varchar,
+        /// 交易哈希
+        tx_hash -> Varchar,
+        /// 区块号
+        block_number -> Int8,
+        /// 区块时间戳
+        timestamp -> Int8,
+        /// 创建时间
+        created_at -> Nullable<Timestamp>,
+    }
+}
+
+diesel::table! {
+    /// 同步状态（单行）
+    sync_state (id) {
+        /// 固定为 1
+        id -> Int2,
+        /// 已入库数据对应的解析器版本
+        parser_version -> Int4,
+        /// 更新时间
+        updated_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    /// 原始区块 JSON（ethereum.block_fields 包含 raw 时写入）
+    eth_block_raw (block_number) {
+        /// 区块号
+        block_number -> Int8,
+        /// 区块哈希
+        block_hash -> Varchar,
+        /// 节点返回的完整区块 JSON
+        raw -> Jsonb,
+        /// 创建时间
+        created_at -> Nullable<Timestamp>,
+    }
+}
+
+diesel::table! {
+    /// 未能入库的转账（死信），amount 超出列精度且 on_overflow = skip 时写入
+    eth_transfer_rejected (tx_hash, log_index) {
+        /// 交易哈希
+        tx_hash -> Varchar,
+        /// 日志索引
+        log_index -> Int8,
+        /// 区块号
+        block_number -> Int8,
+        /// 代币合约地址
+        contract_address -> Nullable<Varchar>,
+        /// 原始金额
+        amount -> Numeric,
+        /// 未入库原因
+        reason -> Text,
+        /// 创建时间
+        created_at -> Nullable<Timestamp>,
     }
 }
@@ -1,7 +1,9 @@
-use crate::models::Transfer;
-use crate::models::db::schema::eth_transfer;
+use crate::errors::error::AppError;
+use crate::models::{AddressStr, Transfer, TxHashStr};
+use crate::models::domain::transfer::TransferKind;
+use crate::models::db::schema::{eth_transfer, eth_transfer_rejected};
 use bigdecimal::BigDecimal;
-use diesel::Insertable;
+use diesel::{Insertable, Queryable};
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Serialize, Deserialize, Insertable)]
@@ -18,24 +20,229 @@ pub struct EthTransferInsert {
     pub max_fee_per_gas: BigDecimal,
     pub status: i16,
     pub log_index: i64,
+    pub access_list: Option<serde_json::Value>,
+    pub fee_paid: BigDecimal,
+    pub sender_unknown: bool,
+    pub kind: i16,
+    pub to_contract: Option<bool>,
+    pub validator_index: Option<i64>,
+}
+
+/// 未能写入 eth_transfer 的转账（死信）
+#[derive(Debug, Clone, Insertable)]
+#[diesel(table_name = eth_transfer_rejected)]
+pub struct EthTransferRejectedInsert {
+    pub tx_hash: String,
+    pub log_index: i64,
+    pub block_number: i64,
+    pub contract_address: Option<String>,
+    pub amount: BigDecimal,
+    pub reason: String,
+}
+
+impl EthTransferRejectedInsert {
+    pub fn new(transfer: &Transfer, reason: String) -> Self {
+        Self {
+            tx_hash: transfer.tx_hash.as_str().to_string(),
+            log_index: transfer.log_index,
+            block_number: transfer.block_number,
+            contract_address: transfer.contract_address.as_ref().map(|c| c.as_str().to_string()),
+            amount: transfer.amount.clone(),
+            reason,
+        }
+    }
+}
+
+/// amount / gas / max_fee_per_gas / fee_paid 列保存 wei 等最小单位的整数；
+/// 列被改为 NUMERIC(78, 0) 等 scale 为 0 的类型时，带小数部分的值会被静默舍入，这里直接拒绝
+fn ensure_integer_amounts(transfer: &Transfer) -> Result<(), AppError> {
+    let fields = [
+        ("amount", &transfer.amount),
+        ("gas", &transfer.gas),
+        ("max_fee_per_gas", &transfer.max_fee_per_gas),
+        ("fee_paid", &transfer.fee_paid),
+    ];
+    match fields.into_iter().find(|(_, value)| !value.is_integer()) {
+        Some((field, value)) => Err(AppError::Conversion(format!(
+            "交易 {} (log_index {}) 的 {} 含小数部分: {}，原始金额列只接受整数",
+            transfer.tx_hash, transfer.log_index, field, value
+        ))),
+        None => Ok(()),
+    }
 }
 
 impl TryFrom<Transfer> for EthTransferInsert {
-    type Error = anyhow::Error;
+    type Error = AppError;
 
     fn try_from(transfer: Transfer) -> Result<EthTransferInsert, Self::Error> {
+        ensure_integer_amounts(&transfer)?;
         Ok(EthTransferInsert {
             block_number: transfer.block_number,
-            tx_hash: transfer.tx_hash,
-            from_address: transfer.from_address,
-            to_address: transfer.to_address,
+            tx_hash: transfer.tx_hash.into(),
+            from_address: transfer.from_address.into(),
+            to_address: transfer.to_address.into(),
             amount: transfer.amount,
-            contract_address: transfer.contract_address,
+            contract_address: transfer.contract_address.map(String::from),
             timestamp: transfer.timestamp,
             gas: transfer.gas,
             max_fee_per_gas: transfer.max_fee_per_gas,
             status: transfer.status,
             log_index: transfer.log_index,
+            access_list: transfer
+                .access_list
+                .map(serde_json::to_value)
+                .transpose()
+                .map_err(|e| AppError::Conversion(format!("access_list 序列化失败: {}", e)))?,
+            fee_paid: transfer.fee_paid,
+            sender_unknown: transfer.sender_unknown,
+            kind: transfer.kind.as_i16(),
+            to_contract: transfer.to_contract,
+            validator_index: transfer.validator_index,
         })
     }
 }
+
+/// eth_transfer 读模型（字段顺序与 select 保持一致），通过 TryFrom 校验后映射回领域模型 Transfer
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable)]
+#[diesel(table_name = eth_transfer)]
+pub struct EthTransferRow {
+    pub block_number: i64,
+    pub tx_hash: String,
+    pub from_address: String,
+    pub to_address: String,
+    pub amount: BigDecimal,
+    pub contract_address: Option<String>,
+    pub timestamp: i64,
+    pub gas: BigDecimal,
+    pub max_fee_per_gas: BigDecimal,
+    pub status: i16,
+    pub log_index: i64,
+    pub access_list: Option<serde_json::Value>,
+    pub fee_paid: BigDecimal,
+    pub sender_unknown: bool,
+    pub kind: i16,
+    pub to_contract: Option<bool>,
+    pub validator_index: Option<i64>,
+}
+
+impl TryFrom<EthTransferRow> for Transfer {
+    type Error = AppError;
+
+    fn try_from(row: EthTransferRow) -> Result<Self, Self::Error> {
+        Transfer::builder()
+            .block_number(row.block_number)
+            .tx_hash(row.tx_hash.parse::<TxHashStr>()?)
+            .from_address(row.from_address.parse::<AddressStr>()?)
+            .to_address(row.to_address.parse::<AddressStr>()?)
+            .amount(row.amount)
+            .contract_address(row.contract_address.map(|c| c.parse()).transpose()?)
+            .timestamp(row.timestamp)
+            .gas(row.gas)
+            .max_fee_per_gas(row.max_fee_per_gas)
+            .status(row.status)
+            .log_index(row.log_index)
+            .access_list(
+                row.access_list
+                    .map(serde_json::from_value)
+                    .transpose()
+                    .map_err(|e| AppError::Conversion(format!("access_list 解析失败: {}", e)))?,
+            )
+            .fee_paid(row.fee_paid)
+            .sender_unknown(row.sender_unknown)
+            .kind(
+                TransferKind::from_i16(row.kind)
+                    .ok_or_else(|| AppError::Conversion(format!("未知的转账类型: {}", row.kind)))?,
+            )
+            .to_contract(row.to_contract)
+            .validator_index(row.validator_index)
+            .build()
+    }
+}
+
+/// 转账摘要投影 (tx_hash, amount, block_number)：高频读接口只取需要的列，减少宽表传输量
+pub type TransferSummary = (String, BigDecimal, i64);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers_core::types::transaction::eip2930::{AccessList, AccessListItem};
+    use ethers_core::types::{H160, H256};
+    use std::str::FromStr;
+
+    fn transfer_with_amount(amount: &str) -> Transfer {
+        Transfer::builder()
+            .block_number(1)
+            .tx_hash(H256::repeat_byte(0x11))
+            .from_address(H160::repeat_byte(0x22))
+            .to_address(H160::repeat_byte(0x33))
+            .amount(BigDecimal::from_str(amount).unwrap())
+            .timestamp(1_700_000_000)
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn fractional_raw_amount_is_rejected() {
+        let result = EthTransferInsert::try_from(transfer_with_amount("1.5"));
+        assert!(matches!(result, Err(AppError::Conversion(_))));
+    }
+
+    #[test]
+    fn integer_amount_with_scale_is_accepted() {
+        let insert = EthTransferInsert::try_from(transfer_with_amount("1.000")).unwrap();
+        assert_eq!(insert.amount, BigDecimal::from(1));
+    }
+
+    #[test]
+    fn access_list_round_trips_through_jsonb_column() {
+        let access_list = AccessList(vec![
+            AccessListItem {
+                address: H160::repeat_byte(0xaa),
+                storage_keys: vec![H256::repeat_byte(0x01), H256::repeat_byte(0x02)],
+            },
+            AccessListItem {
+                address: H160::repeat_byte(0xbb),
+                storage_keys: vec![],
+            },
+        ]);
+        let mut transfer = transfer_with_amount("1");
+        transfer.access_list = Some(access_list.clone());
+
+        let insert = EthTransferInsert::try_from(transfer).unwrap();
+        let json = insert.access_list.clone().unwrap();
+        assert_eq!(json[0]["address"], format!("{:#x}", H160::repeat_byte(0xaa)));
+        assert_eq!(json[0]["storageKeys"].as_array().unwrap().len(), 2);
+
+        let row = EthTransferRow {
+            block_number: insert.block_number,
+            tx_hash: insert.tx_hash,
+            from_address: insert.from_address,
+            to_address: insert.to_address,
+            amount: insert.amount,
+            contract_address: insert.contract_address,
+            timestamp: insert.timestamp,
+            gas: insert.gas,
+            max_fee_per_gas: insert.max_fee_per_gas,
+            status: insert.status,
+            log_index: insert.log_index,
+            access_list: insert.access_list,
+            fee_paid: insert.fee_paid,
+            sender_unknown: insert.sender_unknown,
+            kind: insert.kind,
+            to_contract: insert.to_contract,
+            validator_index: insert.validator_index,
+        };
+        let restored = Transfer::try_from(row).unwrap();
+        assert_eq!(restored.access_list, Some(access_list));
+        assert_eq!(
+            restored.touched_addresses(),
+            vec![H160::repeat_byte(0xaa), H160::repeat_byte(0xbb)]
+        );
+    }
+
+    #[test]
+    fn missing_access_list_is_stored_as_null() {
+        let insert = EthTransferInsert::try_from(transfer_with_amount("1")).unwrap();
+        assert_eq!(insert.access_list, None);
+    }
+}
@@ -1,23 +1,67 @@
+use crate::config::{BlockField, TimestampCheckConfig};
 use crate::errors::error::AppError;
+use crate::log_warn;
 use crate::models::block_db::BlockRow;
+use crate::utils::format::u256_to_bigdecimal;
+use bigdecimal::BigDecimal;
 use ethers::prelude::U64;
-use ethers_core::types::{H256, Transaction};
+use ethers_core::types::{H256, Transaction, U256};
+use std::collections::HashSet;
+
+/// 原始区块 JSON 超过该大小时记录警告
+const RAW_BLOCK_WARN_BYTES: usize = 1 << 20;
+
+/// 只统计 JSON 序列化后的字节数，不分配缓冲区
+fn json_len(value: &serde_json::Value) -> usize {
+    struct Counter(usize);
+    impl std::io::Write for Counter {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0 += buf.len();
+            Ok(buf.len())
+        }
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+    let mut counter = Counter(0);
+    // 写入计数器不会失败
+    let _ = serde_json::to_writer(&mut counter, value);
+    counter.0
+}
 
 #[derive(Debug, Clone)]
 pub struct BlockDomain {
     pub block_number: i64,
     pub block_hash: String,
     pub parent_hash: String,
-    pub gas_used: f64,
-    pub base_fee_per_gas: f64,
+    /// gas_used / base_fee_per_gas 直接由 U256 转为 BigDecimal，不经过浮点，避免超过 2^53 时丢失精度
+    pub gas_used: BigDecimal,
+    pub base_fee_per_gas: BigDecimal,
     pub timestamp: i64,
-    pub size: i32,
+    /// 区块字节大小（来自区块头 size 字段），节点未返回时为 None，入库为 NULL
+    pub size: Option<i32>,
+    /// 区块内交易数
+    pub tx_count: i32,
+    /// 手续费接收地址：PoW 时期为矿工，合并后 `miner` / `author` 字段即提议者指定的 fee recipient；
+    /// 节点未返回或未配置保存时为 None
+    pub fee_recipient: Option<String>,
+    /// 区块难度，合并后恒为 0
+    pub difficulty: Option<BigDecimal>,
+    /// 累计难度，部分节点合并后不再返回
+    pub total_difficulty: Option<BigDecimal>,
+    pub gas_limit: Option<BigDecimal>,
+    /// 区块头 extra_data（0x 十六进制）
+    pub extra_data: Option<String>,
+    pub state_root: Option<String>,
+    /// 节点返回的原始区块 JSON，配置了 `BlockField::Raw` 时才有
+    pub raw: Option<serde_json::Value>,
 }
 
 #[derive(Debug, Clone)]
 pub struct BlockQuery {
     pub block_number: U64,
     pub block_hash: H256,
+    pub timestamp: i64,
 }
 
 impl TryFrom<BlockRow> for BlockQuery {
@@ -31,56 +75,234 @@ impl TryFrom<BlockRow> for BlockQuery {
         Ok(Self {
             block_number,
             block_hash,
+            timestamp: db.timestamp,
         })
     }
 }
 
 impl BlockDomain {
-    pub fn new(
-        block_number: i64,
-        block_hash: String,
-        parent_hash: String,
-        gas_used: f64,
-        base_fee_per_gas: f64,
-        timestamp: i64,
-        size: i32,
-    ) -> Self {
-        Self {
-            block_number,
-            block_hash,
-            parent_hash,
-            gas_used,
-            base_fee_per_gas,
-            timestamp,
-            size,
-        }
-    }
-
-    pub fn from_ethers(block: &ethers_core::types::Block<Transaction>) -> Result<Self, AppError> {
+    /// 转换节点返回的区块，可选字段只填充 `fields` 中配置的，其余为空
+    pub fn from_ethers(
+        block: &ethers_core::types::Block<Transaction>,
+        fields: &HashSet<BlockField>,
+    ) -> Result<Self, AppError> {
         let block_number = crate::utils::option_u64_to_i64(block.number)?;
         let block_hash = crate::utils::h256_opt_to_string(block.hash);
         let block_parent_hash = crate::utils::h256_to_string(block.parent_hash);
-        let gas_used = crate::utils::u256_to_i64(block.gas_used)? as f64;
-        let base_fee_per_gas = crate::utils::opt_u256_to_i64_loose(block.base_fee_per_gas)? as f64;
+        let gas_used = u256_to_bigdecimal(block.gas_used);
+        // 伦敦升级前的区块没有 base_fee_per_gas，记为 0
+        let base_fee_per_gas = u256_to_bigdecimal(block.base_fee_per_gas.unwrap_or_default());
         let block_timestamp = crate::utils::u256_to_i64(block.timestamp)?;
-        let size: i32 = block
+        let size = block
+            .size
+            .map(|size| {
+                (size <= U256::from(i32::MAX))
+                    .then(|| size.as_u32() as i32)
+                    .ok_or_else(|| AppError::InvalidNumber("block size overflow".into()))
+            })
+            .transpose()?;
+        let tx_count: i32 = block
             .transactions
             .len()
             .try_into()
             .map_err(|_| AppError::InvalidNumber("transactions count overflow".into()))?;
+        let stored = |field: BlockField| fields.contains(&field);
+        let fee_recipient = block
+            .author
+            .filter(|_| stored(BlockField::FeeRecipient))
+            .map(|a| format!("{:#x}", a));
+        let difficulty = stored(BlockField::Difficulty).then(|| u256_to_bigdecimal(block.difficulty));
+        let total_difficulty = block
+            .total_difficulty
+            .filter(|_| stored(BlockField::TotalDifficulty))
+            .map(u256_to_bigdecimal);
 
-        Ok(Self::new(
+        let mut domain = Self {
             block_number,
             block_hash,
-            block_parent_hash,
+            parent_hash: block_parent_hash,
             gas_used,
             base_fee_per_gas,
-            block_timestamp,
+            timestamp: block_timestamp,
             size,
-        ))
+            tx_count,
+            fee_recipient,
+            difficulty,
+            total_difficulty,
+            gas_limit: stored(BlockField::GasLimit).then(|| u256_to_bigdecimal(block.gas_limit)),
+            extra_data: stored(BlockField::ExtraData).then(|| block.extra_data.to_string()),
+            state_root: stored(BlockField::StateRoot)
+                .then(|| crate::utils::h256_to_string(block.state_root)),
+            raw: None,
+        };
+        if stored(BlockField::Raw) {
+            let raw = serde_json::to_value(block)
+                .map_err(|e| AppError::ConversionError(format!("区块 {} 序列化失败: {}", block_number, e)))?;
+            let size = json_len(&raw);
+            if size > RAW_BLOCK_WARN_BYTES {
+                log_warn!("区块 {} 原始 JSON 大小 {} 字节，超过 {} 字节", block_number, size, RAW_BLOCK_WARN_BYTES);
+            }
+            domain.raw = Some(raw);
+        }
+        Ok(domain)
+    }
+
+    /// 区块时间戳合理性校验
+    /// - 不早于配置的创世时间、不超过本机时钟 + `max_future_drift_secs`
+    /// - 不小于父区块时间戳（相等时仅告警，部分 L2 同一秒内会出多个块）
+    ///
+    /// 明显异常（节点返回零值、远未来时间或时间倒退）返回 `AppError::Validation`，避免错误数据入库
+    pub fn validate_timestamp(
+        &self,
+        parent_timestamp: Option<i64>,
+        check: &TimestampCheckConfig,
+    ) -> Result<(), AppError> {
+        if self.timestamp < check.genesis_timestamp {
+            return Err(AppError::Validation(format!(
+                "区块 {} 时间戳 {} 早于创世时间 {}",
+                self.block_number, self.timestamp, check.genesis_timestamp
+            )));
+        }
+
+        let now = chrono::Utc::now().timestamp();
+        let max_allowed = now.saturating_add(check.max_future_drift_secs as i64);
+        if self.timestamp > max_allowed {
+            return Err(AppError::Validation(format!(
+                "区块 {} 时间戳 {} 超前本机时钟 {} 秒",
+                self.block_number,
+                self.timestamp,
+                self.timestamp - now
+            )));
+        }
+
+        if let Some(parent) = parent_timestamp {
+            if self.timestamp < parent {
+                return Err(AppError::Validation(format!(
+                    "区块 {} 时间戳 {} 小于父区块时间戳 {}",
+                    self.block_number, self.timestamp, parent
+                )));
+            }
+            if self.timestamp == parent {
+                log_warn!(
+                    "区块 {} 时间戳与父区块相同 ({})",
+                    self.block_number,
+                    self.timestamp
+                );
+            }
+        }
+        Ok(())
     }
 
     pub fn is_empty(&self) -> bool {
         self.block_number == 0 && self.block_hash.is_empty() && self.parent_hash.is_empty()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::block_db::BlockInsert;
+    use ethers_core::types::Block;
+    use std::str::FromStr;
+
+    fn block(gas_used: U256) -> Block<Transaction> {
+        Block {
+            number: Some(U64::from(19_000_000u64)),
+            hash: Some(H256::repeat_byte(0x11)),
+            gas_used,
+            base_fee_per_gas: Some(U256::from(1u64) << 60),
+            timestamp: U256::from(1_700_000_000u64),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn gas_used_above_2_pow_53_keeps_full_precision() {
+        // 2^53 + 1 是 f64 无法精确表示的最小整数
+        let gas_used = (U256::one() << 53) + U256::one();
+        let domain = BlockDomain::from_ethers(&block(gas_used), &HashSet::new()).unwrap();
+        assert_eq!(domain.gas_used, BigDecimal::from_str("9007199254740993").unwrap());
+        assert_eq!(domain.base_fee_per_gas, BigDecimal::from_str("1152921504606846976").unwrap());
+
+        let insert = BlockInsert::try_from(domain).unwrap();
+        assert_eq!(insert.gas_used.to_string(), "9007199254740993");
+    }
+
+    #[test]
+    fn gas_used_at_u256_max_is_not_truncated() {
+        let domain = BlockDomain::from_ethers(&block(U256::MAX), &HashSet::new()).unwrap();
+        assert_eq!(domain.gas_used.to_string(), U256::MAX.to_string());
+    }
+
+    #[test]
+    fn fee_recipient_is_populated_from_author() {
+        let recipient = ethers_core::types::H160::repeat_byte(0xfe);
+        let sample = Block {
+            author: Some(recipient),
+            difficulty: U256::zero(),
+            total_difficulty: Some(U256::from(58_750_003_716_598_352_816_469u128)),
+            ..block(U256::from(21_000u64))
+        };
+        let fields = HashSet::from([BlockField::FeeRecipient, BlockField::TotalDifficulty]);
+        let domain = BlockDomain::from_ethers(&sample, &fields).unwrap();
+        assert_eq!(domain.fee_recipient, Some(format!("{:#x}", recipient)));
+        assert_eq!(
+            domain.total_difficulty,
+            Some(BigDecimal::from_str("58750003716598352816469").unwrap())
+        );
+        // 未配置的字段不保存
+        assert_eq!(domain.difficulty, None);
+
+        let insert = BlockInsert::try_from(domain).unwrap();
+        assert_eq!(insert.fee_recipient, Some(format!("{:#x}", recipient)));
+    }
+
+    #[test]
+    fn fee_recipient_is_none_when_not_stored() {
+        let sample = Block {
+            author: Some(ethers_core::types::H160::repeat_byte(0xfe)),
+            ..block(U256::from(21_000u64))
+        };
+        let domain = BlockDomain::from_ethers(&sample, &HashSet::new()).unwrap();
+        assert_eq!(domain.fee_recipient, None);
+        assert_eq!(BlockInsert::try_from(domain).unwrap().fee_recipient, None);
+    }
+
+    #[test]
+    fn size_comes_from_header_and_tx_count_from_transactions() {
+        let sample = Block {
+            size: Some(U256::from(1_234u64)),
+            transactions: vec![Transaction::default(); 3],
+            ..block(U256::from(63_000u64))
+        };
+        let domain = BlockDomain::from_ethers(&sample, &HashSet::new()).unwrap();
+        assert_eq!(domain.size, Some(1_234));
+        assert_eq!(domain.tx_count, 3);
+
+        let insert = BlockInsert::try_from(domain).unwrap();
+        assert_eq!((insert.size, insert.tx_count), (Some(1_234), 3));
+    }
+
+    #[test]
+    fn missing_size_is_stored_as_null() {
+        let sample = Block {
+            size: None,
+            transactions: vec![Transaction::default(); 2],
+            ..block(U256::from(42_000u64))
+        };
+        let domain = BlockDomain::from_ethers(&sample, &HashSet::new()).unwrap();
+        assert_eq!(domain.size, None);
+        assert_eq!(domain.tx_count, 2);
+        assert_eq!(BlockInsert::try_from(domain).unwrap().size, None);
+    }
+
+    #[test]
+    fn raw_block_is_kept_as_json_value() {
+        let sample = block(U256::from(21_000u64));
+        let fields = HashSet::from([BlockField::Raw]);
+        let domain = BlockDomain::from_ethers(&sample, &fields).unwrap();
+        let raw = domain.raw.unwrap();
+        assert_eq!(raw, serde_json::to_value(&sample).unwrap());
+        assert_eq!(json_len(&raw), serde_json::to_vec(&raw).unwrap().len());
+    }
+}
@@ -0,0 +1,84 @@
+use crate::models::domain::types::{AddressStr, TxHashStr};
+use ethers_core::types::{Transaction, TransactionReceipt, U64};
+use serde::{Deserialize, Serialize};
+
+/// 合约部署记录：合约创建交易成功后 receipt.contract_address 给出的新合约地址
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContractDeployment {
+    pub deployer: AddressStr,
+    pub contract_address: AddressStr,
+    pub tx_hash: TxHashStr,
+    pub block_number: i64,
+    pub timestamp: i64,
+}
+
+impl ContractDeployment {
+    /// 从合约创建交易及其收据构造部署记录
+    /// 非创建交易（to 不为空）、执行失败或收据中没有 contract_address 时返回 None
+    pub fn from_receipt(
+        tx: &Transaction,
+        receipt: &TransactionReceipt,
+        block_number: i64,
+        block_timestamp: i64,
+    ) -> Option<Self> {
+        if tx.to.is_some() || receipt.status != Some(U64::from(1)) {
+            return None;
+        }
+        let contract_address = receipt.contract_address?;
+        Some(Self {
+            deployer: tx.from.into(),
+            contract_address: contract_address.into(),
+            tx_hash: tx.hash.into(),
+            block_number,
+            timestamp: block_timestamp,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers_core::types::{H160, H256};
+
+    fn creation() -> (Transaction, TransactionReceipt) {
+        let tx = Transaction {
+            hash: H256::repeat_byte(0x55),
+            from: H160::repeat_byte(0x01),
+            to: None,
+            ..Default::default()
+        };
+        let receipt = TransactionReceipt {
+            transaction_hash: tx.hash,
+            status: Some(U64::from(1)),
+            contract_address: Some(H160::repeat_byte(0xcc)),
+            ..Default::default()
+        };
+        (tx, receipt)
+    }
+
+    #[test]
+    fn deployment_is_recorded_from_receipt_contract_address() {
+        let (tx, receipt) = creation();
+        let deployment = ContractDeployment::from_receipt(&tx, &receipt, 100, 1_700_000_000).unwrap();
+        assert_eq!(deployment.deployer.as_str(), format!("{:?}", H160::repeat_byte(0x01)));
+        assert_eq!(deployment.contract_address.as_str(), format!("{:?}", H160::repeat_byte(0xcc)));
+        assert_eq!(deployment.tx_hash.as_str(), format!("{:?}", H256::repeat_byte(0x55)));
+        assert_eq!(deployment.block_number, 100);
+        assert_eq!(deployment.timestamp, 1_700_000_000);
+    }
+
+    #[test]
+    fn non_creation_or_failed_receipt_is_not_a_deployment() {
+        let (mut call, receipt) = creation();
+        call.to = Some(H160::repeat_byte(0x02));
+        assert!(ContractDeployment::from_receipt(&call, &receipt, 100, 0).is_none());
+
+        let (tx, mut failed) = creation();
+        failed.status = Some(U64::zero());
+        assert!(ContractDeployment::from_receipt(&tx, &failed, 100, 0).is_none());
+
+        let (tx, mut missing) = creation();
+        missing.contract_address = None;
+        assert!(ContractDeployment::from_receipt(&tx, &missing, 100, 0).is_none());
+    }
+}
@@ -1,6 +1,9 @@
 pub mod transfer;
 pub mod block;
+pub mod deployment;
 pub mod token;
+pub mod types;
 
 pub use block::BlockDomain;
 pub use transfer::Transfer;
+pub use types::{AddressStr, TxHashStr};
@@ -0,0 +1,13 @@
+use crate::models::domain::types::AddressStr;
+use serde::{Deserialize, Serialize};
+
+/// ERC20 代币元数据（name / symbol / decimals）
+/// 部分老代币不实现 name / symbol，此时对应字段为 None；非 EIP-1967 代理合约的 implementation 为 None
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TokenMetadata {
+    pub address: AddressStr,
+    pub name: Option<String>,
+    pub symbol: Option<String>,
+    pub decimals: u8,
+    pub implementation: Option<AddressStr>,
+}
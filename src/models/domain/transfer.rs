@@ -1,38 +1,136 @@
 use crate::config::filter_config::FilterConfig;
+use crate::config::{Erc20ValueWord, EthereumConfig, FilterMode, UnknownSenderPolicy, ValueSource};
 use crate::infrastructure::protocol::constants::ERC20_TRANSFER_TOPIC;
+use crate::infrastructure::protocol::contract_parsers::ContractParserRegistry;
+use crate::infrastructure::protocol::transfer_events::{TransferEventRegistry, extract_address};
 use crate::utils::format::u256_to_bigdecimal;
 use crate::utils::u256_to_i64;
+use crate::errors::error::AppError;
+use crate::log_warn;
+use crate::models::domain::types::{AddressStr, TxHashStr};
 use bigdecimal::BigDecimal;
-use ethers_core::types::{H160, Log, Transaction, TransactionReceipt, U256};
+use ethers_core::types::{Block, H160, Log, Transaction, TransactionReceipt, U256};
+use ethers_core::types::transaction::eip2930::AccessList;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
 
-#[derive(Debug, Clone)]
+/// status 取值与 eth_transfer.status 注释一致：1=确认 2=确认中 3=失败
+pub const STATUS_CONFIRMED: i16 = 1;
+pub const STATUS_CONFIRMING: i16 = 2;
+pub const STATUS_FAILED: i16 = 3;
+
+/// ERC20 transfer(address,uint256) 的函数选择器
+const ERC20_TRANSFER_SELECTOR: [u8; 4] = [0xa9, 0x05, 0x9c, 0xbb];
+
+/// 转账类型，入库为 SMALLINT（`eth_transfer.kind`）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TransferKind {
+    /// 原生 ETH 转账（交易 value）
+    NativeEth,
+    Erc20,
+    Erc721,
+    Erc1155,
+    /// 合约内部调用产生的 ETH 转账（trace）
+    Internal,
+    /// WETH 包装 / 解包（Deposit / Withdrawal）
+    WethWrap,
+    /// 共识层验证者提款（上海升级后区块中的 withdrawals，不属于任何交易）
+    Withdrawal,
+}
+
+impl TransferKind {
+    pub fn as_i16(self) -> i16 {
+        match self {
+            TransferKind::NativeEth => 0,
+            TransferKind::Erc20 => 1,
+            TransferKind::Erc721 => 2,
+            TransferKind::Erc1155 => 3,
+            TransferKind::Internal => 4,
+            TransferKind::WethWrap => 5,
+            TransferKind::Withdrawal => 6,
+        }
+    }
+
+    pub fn from_i16(value: i16) -> Option<Self> {
+        match value {
+            0 => Some(TransferKind::NativeEth),
+            1 => Some(TransferKind::Erc20),
+            2 => Some(TransferKind::Erc721),
+            3 => Some(TransferKind::Erc1155),
+            4 => Some(TransferKind::Internal),
+            5 => Some(TransferKind::WethWrap),
+            6 => Some(TransferKind::Withdrawal),
+            _ => None,
+        }
+    }
+}
+
+/// 解析同一区块内交易时共用的上下文
+#[derive(Clone, Copy)]
+pub struct TransferContext<'a> {
+    pub block_number: i64,
+    pub timestamp: i64,
+    pub filter: &'a FilterConfig,
+    pub events: &'a TransferEventRegistry,
+    pub parsers: &'a ContractParserRegistry,
+    pub config: &'a EthereumConfig,
+}
+
+/// 按事件定义从日志中提取出的转账要素
+#[derive(Debug, Clone, Copy)]
+pub struct EventTransfer {
+    pub from: H160,
+    pub to: H160,
+    pub amount: U256,
+    pub kind: TransferKind,
+}
+
+/// 转账领域模型，可直接序列化给 HTTP 接口 / 消息推送使用
+/// amount、gas、max_fee_per_gas、fee_paid 为 BigDecimal，serde 序列化为十进制字符串（如 "1000000000000000000"），
+/// 而不是 JSON 数字，避免下游按 f64 解析时丢失精度
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Transfer {
     pub block_number: i64,
-    pub tx_hash: String,
-    pub from_address: String,
-    pub to_address: String,
+    pub tx_hash: TxHashStr,
+    pub from_address: AddressStr,
+    pub to_address: AddressStr,
     pub amount: BigDecimal,
-    pub contract_address: Option<String>,
+    pub contract_address: Option<AddressStr>,
     pub timestamp: i64,
     pub gas: BigDecimal,
     pub max_fee_per_gas: BigDecimal,
     pub status: i16,
     pub log_index: i64,
+    /// 交易调用监控合约时记录的 EIP-2930 访问列表（legacy 交易或未调用监控合约时为 None）
+    pub access_list: Option<AccessList>,
+    /// 实际支付的手续费（wei）：gas_used × effective_gas_price
+    pub fee_paid: BigDecimal,
+    /// 节点返回的 from 为空且无法从签名恢复（未签名/系统交易），此时 from_address 为零地址
+    pub sender_unknown: bool,
+    pub kind: TransferKind,
+    /// 收款方是否为合约（eth_getCode 非空），未检测时为 None
+    pub to_contract: Option<bool>,
+    /// 验证者提款的验证者序号，其余类型为 None
+    pub validator_index: Option<i64>,
 }
 impl Transfer {
+    /// 推荐使用 [`Transfer::builder`]：字段同类型较多，按位置传参容易错位
+    #[deprecated(note = "use Transfer::builder() instead")]
     pub fn new(
         block_number: i64,
-        tx_hash: String,
-        from_address: String,
-        to_address: String,
+        tx_hash: TxHashStr,
+        from_address: AddressStr,
+        to_address: AddressStr,
         amount: BigDecimal,
-        contract_address: Option<String>,
+        contract_address: Option<AddressStr>,
         timestamp: i64,
         gas: BigDecimal,
         max_fee_per_gas: BigDecimal,
         status: i16,
         log_index: i64,
     ) -> Self {
+        let contract_address_is_some = contract_address.is_some();
         Self {
             block_number,
             tx_hash,
@@ -45,9 +143,31 @@ impl Transfer {
             max_fee_per_gas,
             status,
             log_index,
+            access_list: None,
+            fee_paid: BigDecimal::from(0),
+            sender_unknown: false,
+            kind: if contract_address_is_some {
+                TransferKind::Erc20
+            } else {
+                TransferKind::NativeEth
+            },
+            to_contract: None,
+            validator_index: None,
         }
     }
 
+    pub fn builder() -> TransferBuilder {
+        TransferBuilder::default()
+    }
+
+    /// 实际支付的手续费；迁移前写入的历史记录 fee_paid 为 0，按 gas × max_fee_per_gas 估算上限
+    pub fn effective_fee(&self) -> BigDecimal {
+        if self.fee_paid == BigDecimal::from(0) {
+            return &self.gas * &self.max_fee_per_gas;
+        }
+        self.fee_paid.clone()
+    }
+
     /// ETH 交易
     pub fn from_eth_tx(
         tx: &Transaction,
@@ -56,68 +176,183 @@ impl Transfer {
         timestamp: i64,
         log_index: i64,
     ) -> Self {
-        Self {
-            block_number,
-            tx_hash: format!("{:#x}", tx.hash),
-            from_address: format!("{:#x}", tx.from),
-            to_address: tx.to.map(|v| format!("{:#x}", v)).unwrap_or_default(),
-            amount: u256_to_bigdecimal(tx.value),
-            contract_address: None,
-            timestamp,
-            gas: u256_to_bigdecimal(tx.gas),
-            max_fee_per_gas: tx
-                .max_fee_per_gas
-                .map(u256_to_bigdecimal)
-                .unwrap_or_else(|| BigDecimal::from(0)),
-            status: receipt.status.unwrap_or_default().as_u64() as i16,
-            log_index,
-        }
+        Self::builder()
+            .block_number(block_number)
+            .tx_hash(tx.hash)
+            .from_address(tx.from)
+            // 合约创建交易没有 to，调用方只会对有 to 的交易调用，这里兜底为零地址
+            .to_address(tx.to.unwrap_or_default())
+            .amount(u256_to_bigdecimal(tx.value))
+            .timestamp(timestamp)
+            .gas(u256_to_bigdecimal(tx.gas))
+            .max_fee_per_gas(max_fee_of(tx))
+            .status(receipt.status.unwrap_or_default().as_u64() as i16)
+            .log_index(log_index)
+            .fee_paid(fee_paid_of(tx, receipt))
+            // 解析前已尝试从签名恢复发送方，仍为零地址说明无法恢复
+            .sender_unknown(tx.from.is_zero())
+            .kind(TransferKind::NativeEth)
+            .build()
+            .expect("from_eth_tx sets all required fields")
     }
 
-    /// ERC20 交易
-    pub fn from_erc20_log(
+    /// 代币转账事件（ERC20 / ERC721 / ERC1155 及配置的协议事件）
+    /// from / to 由事件定义提取，不再固定取 topics[1] / topics[2]
+    pub fn from_event_log(
         tx: &Transaction,
         log: &Log,
         receipt: &TransactionReceipt,
+        ctx: &TransferContext<'_>,
+        event: EventTransfer,
+    ) -> Self {
+        Self::builder()
+            .block_number(ctx.block_number)
+            .tx_hash(tx.hash)
+            .from_address(event.from)
+            .to_address(event.to)
+            .amount(u256_to_bigdecimal(event.amount))
+            .contract_address(Some(log.address.into()))
+            .timestamp(ctx.timestamp)
+            .gas(u256_to_bigdecimal(receipt.gas_used.unwrap_or_default()))
+            .max_fee_per_gas(max_fee_of(tx))
+            .status(receipt.status.unwrap_or_default().as_u64() as i16)
+            .log_index(u256_to_i64(log.log_index.unwrap_or_default()).unwrap_or_default())
+            .fee_paid(fee_paid_of(tx, receipt))
+            .kind(event.kind)
+            .build()
+            .expect("from_event_log sets all required fields")
+    }
+
+    /// 执行失败（revert）的交易：记录一次尝试，金额为调用时的意图金额，status = 3
+    /// ETH 转账取 tx.value；ERC20 transfer 从 calldata 中解析接收方与金额，其他合约调用金额记为 0
+    pub fn from_failed_tx(
+        tx: &Transaction,
+        receipt: &TransactionReceipt,
         block_number: i64,
-        tx_hash: String,
         timestamp: i64,
-        amount: U256,
-        log_index: i64,
-    ) -> Self {
-        Self {
-            block_number,
-            tx_hash,
-            from_address: format!("{:#x}", H160::from(log.topics[1])),
-            to_address: format!("{:#x}", H160::from(log.topics[2])),
-            amount: u256_to_bigdecimal(amount),
-            contract_address: Some(format!("{:#x}", log.address)),
-            timestamp,
-            gas: u256_to_bigdecimal(receipt.gas_used.unwrap_or_default()),
-            max_fee_per_gas: tx
-                .max_fee_per_gas
-                .map(u256_to_bigdecimal)
-                .unwrap_or_else(|| BigDecimal::from(0)),
-            status: receipt.status.unwrap_or_default().as_u64() as i16,
-            log_index,
+        filter: &FilterConfig,
+        mode: FilterMode,
+    ) -> Option<Self> {
+        let to = tx.to?;
+        let input = tx.input.as_ref();
+        let (to_address, amount, contract_address) = if input.is_empty() {
+            (to, tx.value, None)
+        } else if input.len() >= 68 && input[..4] == ERC20_TRANSFER_SELECTOR {
+            // transfer(address,uint256)：4 字节选择器 + 两个 32 字节参数
+            (
+                H160::from_slice(&input[16..36]),
+                U256::from_big_endian(&input[36..68]),
+                Some(to),
+            )
+        } else {
+            (to, U256::zero(), Some(to))
+        };
+
+        if contract_address.is_some_and(|c| filter.is_contract_blocked(&c)) {
+            return None;
+        }
+
+        let touches_monitored = match mode {
+            FilterMode::All => true,
+            FilterMode::ContractsOnly => contract_address.is_some_and(|c| filter.contracts.contains(&c)),
+            FilterMode::Whitelist => {
+                filter.addresses.contains(&tx.from)
+                    || filter.addresses.contains(&to_address)
+                    || contract_address.is_some_and(|c| filter.addresses.contains(&c))
+            }
+        };
+        if !touches_monitored {
+            return None;
         }
+
+        Self::builder()
+            .block_number(block_number)
+            .tx_hash(tx.hash)
+            .from_address(tx.from)
+            .to_address(to_address)
+            .amount(u256_to_bigdecimal(amount))
+            .contract_address(contract_address.map(AddressStr::from))
+            .timestamp(timestamp)
+            .gas(u256_to_bigdecimal(receipt.gas_used.unwrap_or_default()))
+            .max_fee_per_gas(max_fee_of(tx))
+            .status(STATUS_FAILED)
+            .fee_paid(fee_paid_of(tx, receipt))
+            .sender_unknown(tx.from.is_zero())
+            .build()
+            .ok()
+    }
+
+    /// 区块中的验证者提款（上海升级前的区块没有 withdrawals 字段，返回空）
+    ///
+    /// 提款不属于任何交易：tx_hash 取区块哈希，log_index 取全链唯一的提款序号，from 为零地址；
+    /// 金额由 gwei 换算为 wei。按 filter_mode 过滤收款地址，与 ETH 转账一致（ContractsOnly 不记录）
+    pub fn from_withdrawals(
+        block: &Block<Transaction>,
+        block_number: i64,
+        timestamp: i64,
+        filter: &FilterConfig,
+        mode: FilterMode,
+    ) -> Vec<Transfer> {
+        let (Some(withdrawals), Some(block_hash)) = (&block.withdrawals, block.hash) else {
+            return vec![];
+        };
+        withdrawals
+            .iter()
+            .filter(|w| match mode {
+                FilterMode::All => true,
+                FilterMode::ContractsOnly => false,
+                FilterMode::Whitelist => filter.addresses.contains(&w.address),
+            })
+            .filter(|w| !w.amount.is_zero())
+            .filter_map(|w| {
+                Self::builder()
+                    .block_number(block_number)
+                    .tx_hash(block_hash)
+                    .from_address(H160::zero())
+                    .to_address(w.address)
+                    .amount(u256_to_bigdecimal(w.amount.saturating_mul(U256::exp10(9))))
+                    .timestamp(timestamp)
+                    .log_index(w.index.as_u64() as i64)
+                    .kind(TransferKind::Withdrawal)
+                    .validator_index(Some(w.validator_index.as_u64() as i64))
+                    .build()
+                    .ok()
+            })
+            .collect()
     }
 
     ///解析交易
     pub fn process_transaction(
         tx: Transaction,
         receipt: TransactionReceipt,
-        block_number: i64,
-        block_timestamp: i64,
-        filter: &FilterConfig,
+        ctx: &TransferContext<'_>,
     ) -> Vec<Transfer> {
+        let TransferContext {
+            block_number,
+            timestamp: block_timestamp,
+            filter,
+            events,
+            parsers,
+            config,
+        } = *ctx;
         let mut transfers = vec![];
+        let mode = config.filter_mode;
         //ETH 转账过滤
         if let Some(to_addr) = tx.to {
-            // 只要发送者或接收者在用户白名单中，且有金额
-            if !tx.value.is_zero()
-                && (filter.addresses.contains(&tx.from) || filter.addresses.contains(&to_addr))
-            {
+            // Whitelist：发送者或接收者在用户白名单中；All：全部；ContractsOnly：不记录 ETH 转账
+            let wanted = match mode {
+                FilterMode::All => true,
+                FilterMode::ContractsOnly => false,
+                FilterMode::Whitelist => {
+                    filter.addresses.contains(&tx.from) || filter.addresses.contains(&to_addr)
+                }
+            };
+            // 解析前已尝试从签名恢复发送方，仍为零地址说明发送方无法确定
+            let skip_unknown =
+                tx.from.is_zero() && config.unknown_sender == UnknownSenderPolicy::Skip;
+            if !tx.value.is_zero() && wanted && skip_unknown {
+                log_warn!("交易 {:?} 发送方未知，按 unknown_sender = skip 不记录 ETH 转账", tx.hash);
+            } else if !tx.value.is_zero() && wanted {
                 transfers.push(Transfer::from_eth_tx(
                     &tx,
                     &receipt,
@@ -128,40 +363,773 @@ impl Transfer {
             }
         }
 
-        //  ERC20 转账过滤
-        for log in receipt.logs.iter().filter(|log| {
-            // 基础 ERC20 Topic 检查
-            let is_erc20 = log.topics.len() == 3
-                && log.topics[0] == *ERC20_TRANSFER_TOPIC
-                && log.data.0.len() == 32;
-            if !is_erc20 {
-                return false;
+        // 双发合约去重：同一交易内已记录的 (合约, from, to, value)
+        let mut emitted = HashSet::new();
+
+        // 代币转账过滤：按注册表中的事件定义提取 from / to / value
+        for log in &receipt.logs {
+            // 黑名单合约在任何模式下都不记录，优先于监控合约列表与自定义解析器
+            if filter.is_contract_blocked(&log.address) {
+                continue;
+            }
+            // 注册了自定义解析器的合约优先交给解析器，不再走默认逻辑
+            if let Some(parser) = parsers.get(&log.address) {
+                if mode != FilterMode::All && !filter.contracts.contains(&log.address) {
+                    continue;
+                }
+                let Some(transfer) =
+                    parser.parse(&tx, log, &receipt, block_number, block_timestamp)
+                else {
+                    continue;
+                };
+                let involves_user = [&transfer.from_address, &transfer.to_address]
+                    .iter()
+                    .filter_map(|a| a.as_str().parse::<H160>().ok())
+                    .any(|a| filter.addresses.contains(&a));
+                if mode == FilterMode::Whitelist && !involves_user {
+                    continue;
+                }
+                transfers.push(transfer);
+                continue;
             }
 
-            //合约地址检查
-            let is_monitored_contract = filter.contracts.contains(&log.address);
+            let Some(event) = events.matching(log) else {
+                continue;
+            };
 
-            // 用户地址检查 (从 Topic 解析 from/to)
-            let from_addr = H160::from(log.topics[1]);
-            let to_addr = H160::from(log.topics[2]);
-            let is_monitored_user =
-                filter.addresses.contains(&from_addr) || filter.addresses.contains(&to_addr);
+            // 合约地址检查：事件未限定合约时沿用监控合约列表（All 模式不限合约）
+            if mode != FilterMode::All
+                && event.contracts.is_empty()
+                && !filter.contracts.contains(&log.address)
+            {
+                continue;
+            }
+
+            let (Some(from_addr), Some(to_addr)) = (
+                extract_address(log, event.from),
+                extract_address(log, event.to),
+            ) else {
+                log_warn!(
+                    "合约 {:#x} {} 日志结构与配置不符，跳过: tx={:?}",
+                    log.address,
+                    event.name,
+                    log.transaction_hash
+                );
+                continue;
+            };
+
+            // Whitelist 模式下必须涉及我们支持的用户
+            if mode == FilterMode::Whitelist
+                && !filter.addresses.contains(&from_addr)
+                && !filter.addresses.contains(&to_addr)
+            {
+                continue;
+            }
 
-            // 必须是我们支持的合约 且 涉及我们支持的用户
-            is_monitored_contract && is_monitored_user
-        }) {
-            let value = U256::from_big_endian(&log.data.0);
-            transfers.push(Transfer::from_erc20_log(
+            let Some(value) = Self::decode_event_value(log, event.value, config) else {
+                continue;
+            };
+            if config.dedup_double_emit_contracts.contains(&log.address)
+                && !emitted.insert((log.address, from_addr, to_addr, value))
+            {
+                log_warn!(
+                    "合约 {:#x} 在交易 {:?} 中重复发出相同转账事件，跳过 log_index={:?}",
+                    log.address,
+                    tx.hash,
+                    log.log_index
+                );
+                continue;
+            }
+            transfers.push(Transfer::from_event_log(
                 &tx,
                 log,
                 &receipt,
-                block_number,
-                format!("{:#x}", tx.hash),
-                block_timestamp,
-                value,
-                u256_to_i64(log.log_index.unwrap_or_default()).unwrap_or_default(),
+                ctx,
+                EventTransfer {
+                    from: from_addr,
+                    to: to_addr,
+                    amount: value,
+                    kind: event.kind,
+                },
             ));
         }
+        // 调用监控合约的交易记录访问列表，揭示 from/to 之外触达的合约与存储
+        if let Some(access_list) = Self::monitored_access_list(&tx, filter) {
+            for transfer in &mut transfers {
+                transfer.access_list = Some(access_list.clone());
+            }
+        }
         transfers
     }
+
+    /// 交易目标为监控合约且携带非空访问列表时返回；legacy 交易没有 access_list，返回 None
+    fn monitored_access_list(tx: &Transaction, filter: &FilterConfig) -> Option<AccessList> {
+        let to = tx.to?;
+        if !filter.contracts.contains(&to) {
+            return None;
+        }
+        tx.access_list.clone().filter(|list| !list.0.is_empty())
+    }
+
+    /// 访问列表中触达的合约地址（去重，保持原顺序）
+    pub fn touched_addresses(&self) -> Vec<H160> {
+        let mut seen = HashSet::new();
+        self.access_list
+            .iter()
+            .flat_map(|list| list.0.iter())
+            .map(|item| item.address)
+            .filter(|address| seen.insert(*address))
+            .collect()
+    }
+
+    /// 按事件定义解析金额；ERC20 Transfer 的 data 沿用 `decode_erc20_value` 的容错规则
+    fn decode_event_value(log: &Log, source: ValueSource, config: &EthereumConfig) -> Option<U256> {
+        let value = match source {
+            ValueSource::Data(0) if log.topics[0] == *ERC20_TRANSFER_TOPIC => {
+                return Self::decode_erc20_value(log, config.strict_erc20_data, config.erc20_value_word);
+            }
+            ValueSource::Data(i) => log.data.get(i * 32..(i + 1) * 32).map(U256::from_big_endian),
+            ValueSource::Topic(i) => log.topics.get(i).map(|t| U256::from_big_endian(t.as_bytes())),
+            ValueSource::Constant(v) => Some(U256::from(v)),
+        };
+        if value.is_none() {
+            log_warn!(
+                "合约 {:#x} 日志缺少金额字段 {:?}，跳过: tx={:?}",
+                log.address,
+                source,
+                log.transaction_hash
+            );
+        }
+        value
+    }
+
+    /// 从 Transfer 日志的 data 中解析转账金额
+    /// 标准实现 data 恰好 32 字节；部分不规范代币会补齐或附带额外数据：
+    /// 非严格模式下，长度为 32 的整数倍时按 `value_word` 取第一个或最后一个 32 字节并打印警告，
+    /// 其余形状（不足 32 字节或非整数倍）以及严格模式下的非标准长度一律丢弃
+    fn decode_erc20_value(log: &Log, strict: bool, value_word: Erc20ValueWord) -> Option<U256> {
+        let data = &log.data.0;
+        if data.len() == 32 {
+            return Some(U256::from_big_endian(data));
+        }
+        if strict || data.len() < 32 || data.len() % 32 != 0 {
+            log_warn!(
+                "合约 {:#x} Transfer 日志 data 长度异常 ({} 字节)，跳过: tx={:?}",
+                log.address,
+                data.len(),
+                log.transaction_hash
+            );
+            return None;
+        }
+        let word = match value_word {
+            Erc20ValueWord::First => &data[..32],
+            Erc20ValueWord::Last => &data[data.len() - 32..],
+        };
+        log_warn!(
+            "合约 {:#x} Transfer 日志 data 长度非标准 ({} 字节)，按 {:?} 32 字节解析金额: tx={:?}",
+            log.address,
+            data.len(),
+            value_word,
+            log.transaction_hash
+        );
+        Some(U256::from_big_endian(word))
+    }
+}
+
+/// EIP-1559 交易的 max_fee_per_gas，legacy 交易记为 0
+fn max_fee_of(tx: &Transaction) -> BigDecimal {
+    tx.max_fee_per_gas
+        .map(u256_to_bigdecimal)
+        .unwrap_or_else(|| BigDecimal::from(0))
+}
+
+/// 实际手续费 gas_used × effective_gas_price；
+/// 回执缺少 effective_gas_price（部分老节点）时按 max_fee_per_gas 计算，legacy 交易取 gas_price
+fn fee_paid_of(tx: &Transaction, receipt: &TransactionReceipt) -> BigDecimal {
+    let gas_used = receipt.gas_used.unwrap_or_default();
+    let price = receipt
+        .effective_gas_price
+        .or(tx.max_fee_per_gas)
+        .or(tx.gas_price)
+        .unwrap_or_default();
+    u256_to_bigdecimal(gas_used.saturating_mul(price))
+}
+
+/// Transfer 构建器：按名称设置字段，避免同类型参数错位
+///
+/// 必填：block_number、tx_hash、from_address、to_address、amount、timestamp；
+/// 其余默认 contract_address = None、gas = 0、max_fee_per_gas = 0、status = 1（确认）、log_index = 0、access_list = None、fee_paid = 0、sender_unknown = false、to_contract = None、validator_index = None；
+/// kind 未设置时按 contract_address 推断：有合约地址为 Erc20，否则为 NativeEth
+#[derive(Debug, Default, Clone)]
+pub struct TransferBuilder {
+    block_number: Option<i64>,
+    tx_hash: Option<TxHashStr>,
+    from_address: Option<AddressStr>,
+    to_address: Option<AddressStr>,
+    amount: Option<BigDecimal>,
+    contract_address: Option<AddressStr>,
+    timestamp: Option<i64>,
+    gas: Option<BigDecimal>,
+    max_fee_per_gas: Option<BigDecimal>,
+    status: Option<i16>,
+    log_index: Option<i64>,
+    access_list: Option<AccessList>,
+    fee_paid: Option<BigDecimal>,
+    sender_unknown: bool,
+    kind: Option<TransferKind>,
+    to_contract: Option<bool>,
+    validator_index: Option<i64>,
+}
+
+impl TransferBuilder {
+    pub fn block_number(mut self, block_number: i64) -> Self {
+        self.block_number = Some(block_number);
+        self
+    }
+
+    pub fn tx_hash(mut self, tx_hash: impl Into<TxHashStr>) -> Self {
+        self.tx_hash = Some(tx_hash.into());
+        self
+    }
+
+    pub fn from_address(mut self, from_address: impl Into<AddressStr>) -> Self {
+        self.from_address = Some(from_address.into());
+        self
+    }
+
+    pub fn to_address(mut self, to_address: impl Into<AddressStr>) -> Self {
+        self.to_address = Some(to_address.into());
+        self
+    }
+
+    pub fn amount(mut self, amount: BigDecimal) -> Self {
+        self.amount = Some(amount);
+        self
+    }
+
+    pub fn contract_address(mut self, contract_address: Option<AddressStr>) -> Self {
+        self.contract_address = contract_address;
+        self
+    }
+
+    pub fn timestamp(mut self, timestamp: i64) -> Self {
+        self.timestamp = Some(timestamp);
+        self
+    }
+
+    pub fn gas(mut self, gas: BigDecimal) -> Self {
+        self.gas = Some(gas);
+        self
+    }
+
+    pub fn max_fee_per_gas(mut self, max_fee_per_gas: BigDecimal) -> Self {
+        self.max_fee_per_gas = Some(max_fee_per_gas);
+        self
+    }
+
+    pub fn status(mut self, status: i16) -> Self {
+        self.status = Some(status);
+        self
+    }
+
+    pub fn log_index(mut self, log_index: i64) -> Self {
+        self.log_index = Some(log_index);
+        self
+    }
+
+    pub fn access_list(mut self, access_list: Option<AccessList>) -> Self {
+        self.access_list = access_list;
+        self
+    }
+
+    pub fn fee_paid(mut self, fee_paid: BigDecimal) -> Self {
+        self.fee_paid = Some(fee_paid);
+        self
+    }
+
+    pub fn sender_unknown(mut self, sender_unknown: bool) -> Self {
+        self.sender_unknown = sender_unknown;
+        self
+    }
+
+    pub fn kind(mut self, kind: TransferKind) -> Self {
+        self.kind = Some(kind);
+        self
+    }
+
+    pub fn to_contract(mut self, to_contract: Option<bool>) -> Self {
+        self.to_contract = to_contract;
+        self
+    }
+
+    pub fn validator_index(mut self, validator_index: Option<i64>) -> Self {
+        self.validator_index = validator_index;
+        self
+    }
+
+    /// 校验必填字段，缺失时返回 `AppError::Validation`
+    pub fn build(self) -> Result<Transfer, AppError> {
+        fn required<T>(value: Option<T>, field: &str) -> Result<T, AppError> {
+            value.ok_or_else(|| AppError::Validation(format!("Transfer 缺少必填字段 {}", field)))
+        }
+
+        let kind = self.kind.unwrap_or(match self.contract_address {
+            Some(_) => TransferKind::Erc20,
+            None => TransferKind::NativeEth,
+        });
+        Ok(Transfer {
+            block_number: required(self.block_number, "block_number")?,
+            tx_hash: required(self.tx_hash, "tx_hash")?,
+            from_address: required(self.from_address, "from_address")?,
+            to_address: required(self.to_address, "to_address")?,
+            amount: required(self.amount, "amount")?,
+            contract_address: self.contract_address,
+            timestamp: required(self.timestamp, "timestamp")?,
+            gas: self.gas.unwrap_or_else(|| BigDecimal::from(0)),
+            max_fee_per_gas: self.max_fee_per_gas.unwrap_or_else(|| BigDecimal::from(0)),
+            status: self.status.unwrap_or(STATUS_CONFIRMED),
+            log_index: self.log_index.unwrap_or_default(),
+            access_list: self.access_list,
+            fee_paid: self.fee_paid.unwrap_or_else(|| BigDecimal::from(0)),
+            sender_unknown: self.sender_unknown,
+            kind,
+            to_contract: self.to_contract,
+            validator_index: self.validator_index,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers_core::types::{Bytes, H256};
+
+    /// data 由若干 32 字节的字组成，第 i 个字的最后一个字节为 words[i]
+    fn transfer_log(words: &[u8]) -> Log {
+        let mut data = vec![0u8; words.len() * 32];
+        for (i, word) in words.iter().enumerate() {
+            data[i * 32 + 31] = *word;
+        }
+        Log {
+            address: H160::repeat_byte(0xaa),
+            topics: vec![
+                *ERC20_TRANSFER_TOPIC,
+                H256::from(H160::repeat_byte(0x01)),
+                H256::from(H160::repeat_byte(0x02)),
+            ],
+            data: Bytes::from(data),
+            ..Default::default()
+        }
+    }
+
+    /// 最小可用的 EthereumConfig，`overrides` 中的字段覆盖默认值
+    fn test_config(overrides: serde_json::Value) -> EthereumConfig {
+        let mut config = serde_json::json!({
+            "rpc_url": "http://localhost:8545",
+            "chain_id": 1,
+            "delay": 0,
+            "max_retries": 1,
+            "base_delay_secs": 1,
+        });
+        if let (Some(config), Some(overrides)) = (config.as_object_mut(), overrides.as_object()) {
+            config.extend(overrides.clone());
+        }
+        serde_json::from_value(config).unwrap()
+    }
+
+    fn filter(contracts: &[H160], addresses: &[H160], blocked: &[H160]) -> FilterConfig {
+        FilterConfig {
+            contracts: contracts.iter().copied().collect(),
+            addresses: addresses.iter().copied().collect(),
+            allowed_recipients: None,
+            blocked_contracts: blocked.iter().copied().collect(),
+            transfer_events: std::sync::Arc::new(vec![]),
+            contract_confirmations: Default::default(),
+        }
+    }
+
+    /// 调用 token 合约的交易，收据中包含给定的日志
+    fn token_tx(logs: Vec<Log>) -> (Transaction, TransactionReceipt) {
+        let tx = Transaction {
+            hash: H256::repeat_byte(0x55),
+            from: H160::repeat_byte(0x01),
+            to: Some(H160::repeat_byte(0xaa)),
+            ..Default::default()
+        };
+        let receipt = TransactionReceipt {
+            transaction_hash: tx.hash,
+            status: Some(1.into()),
+            logs,
+            ..Default::default()
+        };
+        (tx, receipt)
+    }
+
+    fn process(
+        logs: Vec<Log>,
+        filter: &FilterConfig,
+        config: &EthereumConfig,
+    ) -> Vec<Transfer> {
+        let events = TransferEventRegistry::new(&[]);
+        let parsers = ContractParserRegistry::new();
+        let ctx = TransferContext {
+            block_number: 100,
+            timestamp: 1_700_000_000,
+            filter,
+            events: &events,
+            parsers: &parsers,
+            config,
+        };
+        let (tx, receipt) = token_tx(logs);
+        Transfer::process_transaction(tx, receipt, &ctx)
+    }
+
+    #[test]
+    fn blocked_contract_is_skipped_even_when_monitored() {
+        let token = H160::repeat_byte(0xaa);
+        let user = H160::repeat_byte(0x01);
+        let config = test_config(serde_json::json!({}));
+        let logs = vec![transfer_log(&[7])];
+
+        let monitored = filter(&[token], &[user], &[]);
+        assert_eq!(process(logs.clone(), &monitored, &config).len(), 1);
+
+        let blocked = filter(&[token], &[user], &[token]);
+        assert!(process(logs, &blocked, &config).is_empty());
+    }
+
+    #[test]
+    fn erc20_value_from_first_word_of_padded_data() {
+        let log = transfer_log(&[7, 9]);
+        let value = Transfer::decode_erc20_value(&log, false, Erc20ValueWord::First);
+        assert_eq!(value, Some(U256::from(7)));
+    }
+
+    #[test]
+    fn erc20_value_rejects_short_or_unaligned_data() {
+        let mut log = transfer_log(&[7]);
+        log.data = Bytes::from(vec![0u8; 31]);
+        assert_eq!(Transfer::decode_erc20_value(&log, false, Erc20ValueWord::First), None);
+        log.data = Bytes::from(vec![0u8; 40]);
+        assert_eq!(Transfer::decode_erc20_value(&log, false, Erc20ValueWord::First), None);
+    }
+
+    #[test]
+    fn strict_erc20_value_only_accepts_canonical_data() {
+        let log = transfer_log(&[7, 9]);
+        assert_eq!(Transfer::decode_erc20_value(&log, true, Erc20ValueWord::First), None);
+        let log = transfer_log(&[7]);
+        assert_eq!(
+            Transfer::decode_erc20_value(&log, true, Erc20ValueWord::First),
+            Some(U256::from(7))
+        );
+    }
+
+    #[test]
+    fn erc20_value_from_last_word_of_padded_data() {
+        let config = test_config(serde_json::json!({ "erc20_value_word": "last" }));
+        let log = transfer_log(&[7, 9]);
+        let value = Transfer::decode_event_value(&log, ValueSource::Data(0), &config);
+        assert_eq!(value, Some(U256::from(9)));
+    }
+
+    #[test]
+    fn erc20_value_from_canonical_data_ignores_word_selection() {
+        let config = test_config(serde_json::json!({ "erc20_value_word": "last" }));
+        let log = transfer_log(&[7]);
+        let value = Transfer::decode_event_value(&log, ValueSource::Data(0), &config);
+        assert_eq!(value, Some(U256::from(7)));
+    }
+
+    fn withdrawal(index: u64, validator: u64, to: H160, gwei: U256) -> ethers_core::types::Withdrawal {
+        ethers_core::types::Withdrawal {
+            index: index.into(),
+            validator_index: validator.into(),
+            address: to,
+            amount: gwei,
+        }
+    }
+
+    fn withdrawal_block(withdrawals: Vec<ethers_core::types::Withdrawal>) -> Block<Transaction> {
+        Block {
+            hash: Some(H256::repeat_byte(0xbb)),
+            withdrawals: Some(withdrawals),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn withdrawal_amount_is_converted_from_gwei_to_wei() {
+        let to = H160::repeat_byte(0x03);
+        let block = withdrawal_block(vec![
+            withdrawal(0, 1, to, U256::from(32u64)),
+            withdrawal(1, 1, to, U256::from(u64::MAX)),
+            withdrawal(2, 1, to, U256::MAX),
+        ]);
+        let transfers =
+            Transfer::from_withdrawals(&block, 100, 0, &filter(&[], &[], &[]), FilterMode::All);
+
+        let amounts: Vec<String> = transfers.iter().map(|t| t.amount.to_string()).collect();
+        assert_eq!(amounts[0], "32000000000");
+        // 提款金额字段为 u64 gwei，最大值换算为 wei 后超出 u64 但仍精确
+        assert_eq!(amounts[1], "18446744073709551615000000000");
+        // 超出 U256 的换算结果饱和而不是溢出
+        assert_eq!(amounts[2], U256::MAX.to_string());
+    }
+
+    #[test]
+    fn withdrawal_maps_kind_index_and_validator() {
+        let to = H160::repeat_byte(0x03);
+        let block = withdrawal_block(vec![
+            withdrawal(41, 7, to, U256::from(1u64)),
+            withdrawal(42, 8, to, U256::zero()),
+            withdrawal(43, 9, to, U256::from(2u64)),
+        ]);
+        let transfers =
+            Transfer::from_withdrawals(&block, 100, 0, &filter(&[], &[], &[]), FilterMode::All);
+
+        // 零金额提款不记录
+        assert_eq!(transfers.len(), 2);
+        let first = &transfers[0];
+        assert_eq!(first.kind, TransferKind::Withdrawal);
+        assert_eq!(first.log_index, 41);
+        assert_eq!(first.validator_index, Some(7));
+        assert_eq!(first.from_address.as_str(), format!("{:?}", H160::zero()));
+        assert_eq!(first.to_address.as_str(), format!("{:?}", to));
+        assert_eq!(transfers[1].log_index, 43);
+        assert_eq!(transfers[1].validator_index, Some(9));
+    }
+
+    /// 提款借用 (tx_hash, log_index) 唯一键：tx_hash 为区块哈希，log_index 为全链递增的提款序号。
+    /// 区块哈希不会与任何交易哈希相同，所以提款与同区块日志转账即使序号相同也不会冲突
+    #[test]
+    fn withdrawal_keys_use_block_hash_and_withdrawal_index() {
+        let to = H160::repeat_byte(0x03);
+        let block = withdrawal_block(vec![
+            withdrawal(0, 1, to, U256::from(1u64)),
+            withdrawal(1, 1, to, U256::from(1u64)),
+        ]);
+        let withdrawals =
+            Transfer::from_withdrawals(&block, 100, 0, &filter(&[], &[], &[]), FilterMode::All);
+        let config = test_config(serde_json::json!({ "filter_mode": "all" }));
+        let mut log = transfer_log(&[7]);
+        log.log_index = Some(0.into());
+        let erc20 = process(vec![log], &filter(&[], &[], &[]), &config);
+
+        let block_hash = TxHashStr::from(H256::repeat_byte(0xbb));
+        assert!(withdrawals.iter().all(|w| w.tx_hash.as_str() == block_hash.as_str()));
+        let keys: HashSet<(String, i64)> = withdrawals
+            .iter()
+            .chain(&erc20)
+            .map(|t| (t.tx_hash.as_str().to_owned(), t.log_index))
+            .collect();
+        assert_eq!(erc20.len(), 1);
+        assert_eq!(erc20[0].log_index, withdrawals[0].log_index);
+        assert_eq!(keys.len(), withdrawals.len() + erc20.len());
+    }
+
+    #[test]
+    fn withdrawals_follow_filter_mode() {
+        let watched = H160::repeat_byte(0x03);
+        let block = withdrawal_block(vec![
+            withdrawal(0, 1, watched, U256::from(1u64)),
+            withdrawal(1, 1, H160::repeat_byte(0x04), U256::from(1u64)),
+        ]);
+        let filter = filter(&[], &[watched], &[]);
+        let count = |mode| Transfer::from_withdrawals(&block, 100, 0, &filter, mode).len();
+        assert_eq!(count(FilterMode::All), 2);
+        assert_eq!(count(FilterMode::Whitelist), 1);
+        assert_eq!(count(FilterMode::ContractsOnly), 0);
+    }
+
+    #[test]
+    fn identical_logs_are_deduplicated_only_for_configured_contracts() {
+        let token = H160::repeat_byte(0xaa);
+        let filter = filter(&[token], &[H160::repeat_byte(0x01)], &[]);
+        let logs: Vec<Log> = (0..2u64)
+            .map(|i| Log {
+                log_index: Some(i.into()),
+                ..transfer_log(&[7])
+            })
+            .collect();
+
+        // 默认不去重：不同 log_index 视为不同的转账
+        let config = test_config(serde_json::json!({}));
+        assert_eq!(process(logs.clone(), &filter, &config).len(), 2);
+
+        let config = test_config(serde_json::json!({
+            "dedup_double_emit_contracts": [format!("{:#x}", token)],
+        }));
+        let transfers = process(logs.clone(), &filter, &config);
+        assert_eq!(transfers.len(), 1);
+        assert_eq!(transfers[0].log_index, 0);
+
+        // 金额不同的日志不是重复发出
+        let mut distinct = logs;
+        distinct[1] = Log {
+            log_index: Some(1u64.into()),
+            ..transfer_log(&[8])
+        };
+        assert_eq!(process(distinct, &filter, &config).len(), 2);
+    }
+
+    #[test]
+    fn access_list_is_attached_when_calling_a_monitored_contract() {
+        use ethers_core::types::transaction::eip2930::AccessListItem;
+
+        let token = H160::repeat_byte(0xaa);
+        let filter = filter(&[token], &[H160::repeat_byte(0x01)], &[]);
+        let config = test_config(serde_json::json!({}));
+        let events = TransferEventRegistry::new(&[]);
+        let parsers = ContractParserRegistry::new();
+        let ctx = TransferContext {
+            block_number: 100,
+            timestamp: 1_700_000_000,
+            filter: &filter,
+            events: &events,
+            parsers: &parsers,
+            config: &config,
+        };
+        let access_list = AccessList(vec![AccessListItem {
+            address: H160::repeat_byte(0xcc),
+            storage_keys: vec![H256::repeat_byte(0x01)],
+        }]);
+
+        let (mut tx, receipt) = token_tx(vec![transfer_log(&[7])]);
+        tx.access_list = Some(access_list.clone());
+        let transfers = Transfer::process_transaction(tx, receipt.clone(), &ctx);
+        assert_eq!(transfers[0].access_list, Some(access_list));
+        assert_eq!(transfers[0].touched_addresses(), vec![H160::repeat_byte(0xcc)]);
+
+        // legacy 交易没有访问列表
+        let (legacy, _) = token_tx(vec![]);
+        let transfers = Transfer::process_transaction(legacy, receipt, &ctx);
+        assert_eq!(transfers[0].access_list, None);
+        assert!(transfers[0].touched_addresses().is_empty());
+    }
+
+    fn reverted(tx: &Transaction) -> TransactionReceipt {
+        TransactionReceipt {
+            transaction_hash: tx.hash,
+            status: Some(0.into()),
+            gas_used: Some(30_000.into()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn reverted_eth_transfer_is_recorded_as_failed_attempt() {
+        let sender = H160::repeat_byte(0x01);
+        let tx = Transaction {
+            hash: H256::repeat_byte(0x66),
+            from: sender,
+            to: Some(H160::repeat_byte(0x02)),
+            value: 5_000u64.into(),
+            ..Default::default()
+        };
+        let filter = filter(&[], &[sender], &[]);
+        let failed =
+            Transfer::from_failed_tx(&tx, &reverted(&tx), 100, 0, &filter, FilterMode::Whitelist)
+                .unwrap();
+
+        assert_eq!(failed.status, STATUS_FAILED);
+        assert_eq!(failed.amount, BigDecimal::from(5_000));
+        assert_eq!(failed.to_address.as_str(), format!("{:?}", H160::repeat_byte(0x02)));
+        assert_eq!(failed.contract_address, None);
+        assert_eq!(failed.gas, BigDecimal::from(30_000));
+    }
+
+    #[test]
+    fn reverted_erc20_transfer_decodes_attempted_recipient_and_amount() {
+        let sender = H160::repeat_byte(0x01);
+        let token = H160::repeat_byte(0xaa);
+        let recipient = H160::repeat_byte(0x09);
+        let mut input = ERC20_TRANSFER_SELECTOR.to_vec();
+        input.extend_from_slice(H256::from(recipient).as_bytes());
+        input.extend_from_slice(H256::from_low_u64_be(1_000).as_bytes());
+        let tx = Transaction {
+            hash: H256::repeat_byte(0x66),
+            from: sender,
+            to: Some(token),
+            input: Bytes::from(input),
+            ..Default::default()
+        };
+        let filter = filter(&[token], &[sender], &[]);
+        let failed =
+            Transfer::from_failed_tx(&tx, &reverted(&tx), 100, 0, &filter, FilterMode::Whitelist)
+                .unwrap();
+
+        assert_eq!(failed.status, STATUS_FAILED);
+        assert_eq!(failed.amount, BigDecimal::from(1_000));
+        assert_eq!(failed.to_address.as_str(), format!("{:?}", recipient));
+        assert_eq!(
+            failed.contract_address.as_ref().map(|c| c.as_str().to_owned()),
+            Some(format!("{:?}", token))
+        );
+    }
+
+    #[test]
+    fn reverted_tx_of_unmonitored_sender_is_not_recorded() {
+        let tx = Transaction {
+            from: H160::repeat_byte(0x07),
+            to: Some(H160::repeat_byte(0x08)),
+            value: 1u64.into(),
+            ..Default::default()
+        };
+        let filter = filter(&[], &[H160::repeat_byte(0x01)], &[]);
+        assert!(
+            Transfer::from_failed_tx(&tx, &reverted(&tx), 100, 0, &filter, FilterMode::Whitelist)
+                .is_none()
+        );
+    }
+
+    /// 系统交易形态：from 为零地址、没有签名（v = r = s = 0），携带 ETH
+    fn system_tx() -> Transaction {
+        let mut tx = Transaction {
+            hash: H256::repeat_byte(0x77),
+            from: H160::zero(),
+            to: Some(H160::repeat_byte(0x02)),
+            value: 1_000u64.into(),
+            ..Default::default()
+        };
+        // 解析前先尝试从签名恢复，无签名时仍为零地址
+        assert!(!crate::utils::recover_sender(&mut tx));
+        tx
+    }
+
+    fn process_system_tx(policy: &str) -> Vec<Transfer> {
+        let filter = filter(&[], &[H160::repeat_byte(0x02)], &[]);
+        let config = test_config(serde_json::json!({ "unknown_sender": policy }));
+        let events = TransferEventRegistry::new(&[]);
+        let parsers = ContractParserRegistry::new();
+        let ctx = TransferContext {
+            block_number: 100,
+            timestamp: 1_700_000_000,
+            filter: &filter,
+            events: &events,
+            parsers: &parsers,
+            config: &config,
+        };
+        let tx = system_tx();
+        let receipt = TransactionReceipt {
+            transaction_hash: tx.hash,
+            status: Some(1.into()),
+            ..Default::default()
+        };
+        Transfer::process_transaction(tx, receipt, &ctx)
+    }
+
+    #[test]
+    fn unknown_sender_is_flagged_by_default() {
+        let transfers = process_system_tx("flag");
+        assert_eq!(transfers.len(), 1);
+        assert!(transfers[0].sender_unknown);
+        assert_eq!(transfers[0].from_address.as_str(), format!("{:?}", H160::zero()));
+        assert_eq!(transfers[0].amount, BigDecimal::from(1_000));
+    }
+
+    #[test]
+    fn unknown_sender_is_skipped_when_configured() {
+        assert!(process_system_tx("skip").is_empty());
+    }
 }
@@ -0,0 +1,109 @@
+use crate::errors::error::AppError;
+use ethers_core::types::{H160, H256};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// 校验 `0x` + 指定长度十六进制，并统一为小写（与入库时 `{:#x}` 格式一致）
+fn normalize_hex(value: &str, hex_len: usize) -> Option<String> {
+    let hex = value.strip_prefix("0x").or_else(|| value.strip_prefix("0X"))?;
+    (hex.len() == hex_len && hex.bytes().all(|b| b.is_ascii_hexdigit()))
+        .then(|| format!("0x{}", hex.to_ascii_lowercase()))
+}
+
+/// 已校验的地址字符串（`0x` + 40 位小写十六进制）
+/// 与 `TxHashStr` 区分类型，避免 `Transfer` 多个字符串字段传参顺序错位
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct AddressStr(String);
+
+impl AddressStr {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl FromStr for AddressStr {
+    type Err = AppError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        normalize_hex(s, 40)
+            .map(Self)
+            .ok_or_else(|| AppError::InvalidAddress(s.to_string()))
+    }
+}
+
+impl TryFrom<String> for AddressStr {
+    type Error = AppError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+/// H160 总是合法地址，`TryFrom<H160>` 由该实现自动提供
+impl From<H160> for AddressStr {
+    fn from(address: H160) -> Self {
+        Self(format!("{:#x}", address))
+    }
+}
+
+impl From<AddressStr> for String {
+    fn from(address: AddressStr) -> Self {
+        address.0
+    }
+}
+
+impl fmt::Display for AddressStr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// 已校验的交易哈希字符串（`0x` + 64 位小写十六进制）
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct TxHashStr(String);
+
+impl TxHashStr {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl FromStr for TxHashStr {
+    type Err = AppError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        normalize_hex(s, 64)
+            .map(Self)
+            .ok_or_else(|| AppError::InvalidTxHash(s.to_string()))
+    }
+}
+
+impl TryFrom<String> for TxHashStr {
+    type Error = AppError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+/// H256 总是合法哈希，`TryFrom<H256>` 由该实现自动提供
+impl From<H256> for TxHashStr {
+    fn from(hash: H256) -> Self {
+        Self(format!("{:#x}", hash))
+    }
+}
+
+impl From<TxHashStr> for String {
+    fn from(hash: TxHashStr) -> Self {
+        hash.0
+    }
+}
+
+impl fmt::Display for TxHashStr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
@@ -0,0 +1,123 @@
+use crate::errors::error::AppError;
+use crate::models::Transfer;
+use crate::models::address_stats_db::{AddressStatsInsert, AddressStatsRow};
+use crate::models::schema::address_stats_db;
+use diesel::sql_types::{Array, BigInt, Text};
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+use ethers_core::types::H160;
+
+diesel::define_sql_function! {
+    fn least(a: BigInt, b: BigInt) -> BigInt;
+}
+
+diesel::define_sql_function! {
+    fn greatest(a: BigInt, b: BigInt) -> BigInt;
+}
+
+/// 地址活跃度汇总（address_stats），查询某地址是否活跃 / 最近出现时间时无需扫描 eth_transfer
+///
+/// 回滚区块删除转账时，在同一事务中按剩余转账重算受影响地址的统计
+#[derive(Clone, Default)]
+pub struct AddressStatsRepository {}
+
+impl AddressStatsRepository {
+    pub fn new() -> Self {
+        Self {}
+    }
+
+    /// 按一批转账 upsert 地址统计，需与转账写入在同一事务中调用以保持一致
+    pub async fn upsert_from_transfers(
+        &self,
+        conn: &mut AsyncPgConnection,
+        chain: i64,
+        transfers: &[Transfer],
+    ) -> Result<(), AppError> {
+        use crate::models::schema::address_stats::dsl::*;
+        use diesel::upsert::excluded;
+        use diesel::ExpressionMethods;
+
+        let mut rows = AddressStatsInsert::from_transfers(chain, transfers);
+        // 固定加锁顺序，避免并发事务按不同顺序更新同一批地址时死锁
+        rows.sort_by(|a, b| a.address.cmp(&b.address));
+        for chunk in rows.chunks(1000) {
+            diesel::insert_into(address_stats_db)
+                .values(chunk)
+                .on_conflict((address, chain_id))
+                .do_update()
+                .set((
+                    first_seen_block.eq(least(first_seen_block, excluded(first_seen_block))),
+                    last_seen_block.eq(greatest(last_seen_block, excluded(last_seen_block))),
+                    total_transfers.eq(total_transfers + excluded(total_transfers)),
+                    updated_at.eq(diesel::dsl::now),
+                ))
+                .execute(conn)
+                .await
+                .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+        }
+        Ok(())
+    }
+
+    /// 按 eth_transfer 中剩余的转账重算一批地址的统计（回滚删除转账后、同一事务中调用），
+    /// 已没有任何转账的地址删除统计记录
+    pub async fn recompute_for_addresses(
+        &self,
+        conn: &mut AsyncPgConnection,
+        chain: i64,
+        addrs: &[String],
+    ) -> Result<(), AppError> {
+        use crate::models::schema::address_stats::dsl::*;
+        use diesel::{ExpressionMethods, QueryDsl};
+
+        let mut addrs = addrs.to_vec();
+        // 与 upsert 相同的加锁顺序
+        addrs.sort();
+        addrs.dedup();
+        for chunk in addrs.chunks(1000) {
+            diesel::delete(
+                address_stats
+                    .filter(chain_id.eq(chain))
+                    .filter(address.eq_any(chunk)),
+            )
+            .execute(conn)
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+            // 计数口径与 AddressStatsInsert::from_transfers 一致：发送方未知时不计发送方，自转账只计一次
+            diesel::sql_query(
+                "INSERT INTO address_stats \
+                     (address, chain_id, first_seen_block, last_seen_block, total_transfers) \
+                 SELECT a.address, $1, MIN(t.block_number), MAX(t.block_number), COUNT(*) \
+                 FROM unnest($2::varchar[]) AS a(address) \
+                 JOIN eth_transfer t \
+                   ON (t.from_address = a.address AND NOT t.sender_unknown) \
+                   OR t.to_address = a.address \
+                 GROUP BY a.address",
+            )
+            .bind::<BigInt, _>(chain)
+            .bind::<Array<Text>, _>(chunk)
+            .execute(conn)
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+        }
+        Ok(())
+    }
+
+    /// 查询单个地址的统计，地址从未出现时返回 None
+    pub async fn get(
+        &self,
+        conn: &mut AsyncPgConnection,
+        addr: &H160,
+        chain: i64,
+    ) -> Result<Option<AddressStatsRow>, AppError> {
+        use crate::models::schema::address_stats::dsl::*;
+        use diesel::{ExpressionMethods, OptionalExtension, QueryDsl};
+
+        // 入库时统一使用 {:#x} 小写格式
+        address_stats
+            .filter(address.eq(format!("{:#x}", addr)))
+            .filter(chain_id.eq(chain))
+            .first::<AddressStatsRow>(conn)
+            .await
+            .optional()
+            .map_err(|e| AppError::DatabaseError(e.to_string()))
+    }
+}
@@ -0,0 +1,168 @@
+use crate::errors::error::AppError;
+use crate::models::schema::block_queue_db;
+use diesel::QueryableByName;
+use diesel::sql_types::{BigInt, Text};
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+
+/// 队列状态：待处理
+pub const QUEUE_PENDING: i16 = 0;
+/// 队列状态：已被 worker 领取
+pub const QUEUE_PROCESSING: i16 = 1;
+
+#[derive(QueryableByName)]
+struct ClaimedBlock {
+    #[diesel(sql_type = BigInt)]
+    block_number: i64,
+}
+
+/// Postgres 持久化的区块处理队列
+/// 确认（ack）即删除，队列中只保留待处理与处理中的区块
+#[derive(Clone, Default)]
+pub struct BlockQueueRepository {}
+
+impl BlockQueueRepository {
+    pub fn new() -> Self {
+        Self {}
+    }
+
+    /// 将 [from, to] 区间的高度入队，已存在的高度忽略
+    pub async fn enqueue_range(
+        &self,
+        conn: &mut AsyncPgConnection,
+        from: i64,
+        to: i64,
+    ) -> Result<usize, AppError> {
+        use crate::models::schema::block_queue::dsl::*;
+        use diesel::ExpressionMethods;
+
+        if from > to {
+            return Ok(0);
+        }
+        let rows: Vec<_> = (from..=to).map(|n| block_number.eq(n)).collect();
+        let mut inserted = 0;
+        for chunk in rows.chunks(1000) {
+            inserted += diesel::insert_into(block_queue_db)
+                .values(chunk)
+                .on_conflict(block_number)
+                .do_nothing()
+                .execute(conn)
+                .await
+                .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+        }
+        Ok(inserted)
+    }
+
+    /// 队列中（待处理 + 处理中）的区块数
+    pub async fn len(&self, conn: &mut AsyncPgConnection) -> Result<i64, AppError> {
+        use diesel::QueryDsl;
+
+        block_queue_db
+            .count()
+            .get_result(conn)
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))
+    }
+
+    /// 队列中最大的区块号
+    pub async fn max_block_number(
+        &self,
+        conn: &mut AsyncPgConnection,
+    ) -> Result<Option<i64>, AppError> {
+        use crate::models::schema::block_queue::dsl::*;
+        use diesel::QueryDsl;
+
+        block_queue
+            .select(diesel::dsl::max(block_number))
+            .first(conn)
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))
+    }
+
+    /// 队列中最小的区块号
+    pub async fn min_block_number(
+        &self,
+        conn: &mut AsyncPgConnection,
+    ) -> Result<Option<i64>, AppError> {
+        use crate::models::schema::block_queue::dsl::*;
+        use diesel::QueryDsl;
+
+        block_queue
+            .select(diesel::dsl::min(block_number))
+            .first(conn)
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))
+    }
+
+    /// 领取最小的待处理区块；`SKIP LOCKED` 保证多个 worker 并发领取时互不阻塞、不重复
+    pub async fn claim(
+        &self,
+        conn: &mut AsyncPgConnection,
+        worker: &str,
+    ) -> Result<Option<i64>, AppError> {
+        let claimed = diesel::sql_query(
+            "UPDATE block_queue \
+             SET status = 1, attempts = attempts + 1, worker_id = $1, locked_at = now() \
+             WHERE block_number = ( \
+                 SELECT block_number FROM block_queue WHERE status = 0 \
+                 ORDER BY block_number LIMIT 1 FOR UPDATE SKIP LOCKED) \
+             RETURNING block_number",
+        )
+        .bind::<Text, _>(worker.to_string())
+        .load::<ClaimedBlock>(conn)
+        .await
+        .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        Ok(claimed.into_iter().next().map(|c| c.block_number))
+    }
+
+    /// 确认处理完成（与区块入库在同一事务中调用）
+    pub async fn ack(&self, conn: &mut AsyncPgConnection, height: i64) -> Result<(), AppError> {
+        use crate::models::schema::block_queue::dsl::*;
+        use diesel::{ExpressionMethods, QueryDsl};
+
+        diesel::delete(block_queue.filter(block_number.eq(height)))
+            .execute(conn)
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+        Ok(())
+    }
+
+    /// 处理失败，放回待处理状态并记录原因
+    pub async fn release(
+        &self,
+        conn: &mut AsyncPgConnection,
+        height: i64,
+        error: &str,
+    ) -> Result<(), AppError> {
+        use crate::models::schema::block_queue::dsl::*;
+        use diesel::{ExpressionMethods, QueryDsl};
+
+        diesel::update(block_queue.filter(block_number.eq(height)))
+            .set((
+                status.eq(QUEUE_PENDING),
+                worker_id.eq(None::<String>),
+                locked_at.eq(None::<chrono::NaiveDateTime>),
+                last_error.eq(Some(error)),
+            ))
+            .execute(conn)
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+        Ok(())
+    }
+
+    /// 回收租约过期的区块（worker 崩溃后遗留的处理中记录），返回回收数量
+    pub async fn reclaim_expired(
+        &self,
+        conn: &mut AsyncPgConnection,
+        lease_secs: i64,
+    ) -> Result<usize, AppError> {
+        diesel::sql_query(
+            "UPDATE block_queue SET status = 0, worker_id = NULL, locked_at = NULL \
+             WHERE status = 1 AND locked_at < now() - make_interval(secs => $1)",
+        )
+        .bind::<BigInt, _>(lease_secs)
+        .execute(conn)
+        .await
+        .map_err(|e| AppError::DatabaseError(e.to_string()))
+    }
+}
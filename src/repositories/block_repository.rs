@@ -1,9 +1,10 @@
 use crate::errors::error::AppError;
 use crate::models::BlockDomain;
-use crate::models::block_db::{BlockInsert, BlockRow};
+use crate::models::block_db::{BlockInsert, BlockRawInsert, BlockRow};
 use crate::models::schema::eth_block::block_number;
-use crate::models::schema::eth_block_db;
+use crate::models::schema::{eth_block_db, eth_block_raw, eth_block_raw_db};
 use crate::repositories::traits::repository::Repository;
+use crate::utils::time::validate_time_range;
 use async_trait::async_trait;
 use diesel_async::{AsyncPgConnection, RunQueryDsl};
 
@@ -15,6 +16,37 @@ impl BlockRepository {
         Self {}
     }
 
+    /// 写入区块，同高度已存在时忽略；返回是否实际写入
+    /// 带有原始 JSON 时一并写入 eth_block_raw（调用方在同一事务中执行）
+    pub async fn insert(
+        &self,
+        conn: &mut AsyncPgConnection,
+        block: &BlockDomain,
+    ) -> Result<bool, AppError> {
+        let diesel_block: BlockInsert = block.clone().try_into()?;
+        let inserted = diesel::insert_into(eth_block_db)
+            .values(&diesel_block)
+            .on_conflict(block_number)
+            .do_nothing()
+            .execute(conn) // 直接在异步连接上执行
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+        if let (true, Some(raw)) = (inserted > 0, &block.raw) {
+            diesel::insert_into(eth_block_raw_db)
+                .values(&BlockRawInsert {
+                    block_number: block.block_number,
+                    block_hash: block.block_hash.clone(),
+                    raw: raw.clone(),
+                })
+                .on_conflict(eth_block_raw::block_number)
+                .do_nothing()
+                .execute(conn)
+                .await
+                .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+        }
+        Ok(inserted > 0)
+    }
+
     pub async fn get_last_block_number(
         &self,
         conn: &mut AsyncPgConnection,
@@ -23,13 +55,106 @@ impl BlockRepository {
         use diesel::{ExpressionMethods, OptionalExtension, QueryDsl};
 
         eth_block
-            .select((block_number, block_hash, parent_hash))
+            .select((block_number, block_hash, parent_hash, timestamp))
             .order_by(block_number.desc())
             .first::<BlockRow>(conn)
             .await
             .optional()
             .map_err(|e| AppError::DatabaseError(e.to_string()))
     }
+
+    /// 本地最早的区块号
+    pub async fn get_first_block_number(
+        &self,
+        conn: &mut AsyncPgConnection,
+    ) -> Result<Option<i64>, AppError> {
+        use crate::models::schema::eth_block::dsl::*;
+        use diesel::QueryDsl;
+
+        eth_block
+            .select(diesel::dsl::min(block_number))
+            .first(conn)
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))
+    }
+
+    /// 按区块号查询单个区块
+    pub async fn get_block(
+        &self,
+        conn: &mut AsyncPgConnection,
+        height: i64,
+    ) -> Result<Option<BlockRow>, AppError> {
+        use crate::models::schema::eth_block::dsl::*;
+        use diesel::{ExpressionMethods, OptionalExtension, QueryDsl};
+
+        eth_block
+            .select((block_number, block_hash, parent_hash, timestamp))
+            .filter(block_number.eq(height))
+            .first::<BlockRow>(conn)
+            .await
+            .optional()
+            .map_err(|e| AppError::DatabaseError(e.to_string()))
+    }
+
+    /// 按区块时间查询 [from_ts, to_ts] 区间内的区块（unix 秒，闭区间），按时间倒序分页
+    pub async fn find_blocks_by_time_range(
+        &self,
+        conn: &mut AsyncPgConnection,
+        from_ts: i64,
+        to_ts: i64,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<BlockRow>, AppError> {
+        use crate::models::schema::eth_block::dsl::*;
+        use diesel::{ExpressionMethods, QueryDsl};
+
+        validate_time_range(from_ts, to_ts)?;
+        eth_block
+            .select((block_number, block_hash, parent_hash, timestamp))
+            .filter(timestamp.between(from_ts, to_ts))
+            .order_by((timestamp.desc(), block_number.desc()))
+            .limit(limit)
+            .offset(offset)
+            .load::<BlockRow>(conn)
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))
+    }
+
+    /// 最近入库的 `limit` 个区块，按区块号倒序
+    pub async fn get_recent_blocks(
+        &self,
+        conn: &mut AsyncPgConnection,
+        limit: i64,
+    ) -> Result<Vec<BlockRow>, AppError> {
+        use crate::models::schema::eth_block::dsl::*;
+        use diesel::{ExpressionMethods, QueryDsl};
+
+        eth_block
+            .select((block_number, block_hash, parent_hash, timestamp))
+            .order_by(block_number.desc())
+            .limit(limit)
+            .load::<BlockRow>(conn)
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))
+    }
+
+    /// 删除高于 `height` 的区块（及其原始 JSON），返回删除的区块数
+    pub async fn delete_above(
+        &self,
+        conn: &mut AsyncPgConnection,
+        height: i64,
+    ) -> Result<usize, AppError> {
+        use diesel::{ExpressionMethods, QueryDsl};
+
+        diesel::delete(eth_block_raw_db.filter(eth_block_raw::block_number.gt(height)))
+            .execute(conn)
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+        diesel::delete(eth_block_db.filter(block_number.gt(height)))
+            .execute(conn)
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))
+    }
 }
 
 #[async_trait]
@@ -44,14 +169,7 @@ impl Repository<BlockDomain, i64> for BlockRepository {
         conn: &mut AsyncPgConnection,
         block: &BlockDomain,
     ) -> Result<(), AppError> {
-        let diesel_block: BlockInsert = block.clone().try_into()?;
-        diesel::insert_into(eth_block_db)
-            .values(&diesel_block)
-            .on_conflict(block_number)
-            .do_nothing()
-            .execute(conn) // 直接在异步连接上执行
-            .await
-            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+        self.insert(conn, block).await?;
         Ok(())
     }
 
@@ -0,0 +1,63 @@
+use crate::errors::error::AppError;
+use crate::models::deployment_db::ContractDeploymentInsert;
+use crate::models::domain::deployment::ContractDeployment;
+use crate::models::schema::contract_deployment_db;
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+use ethers_core::types::H160;
+
+#[derive(Clone, Default)]
+pub struct ContractDeploymentRepository {}
+
+impl ContractDeploymentRepository {
+    pub fn new() -> Self {
+        Self {}
+    }
+
+    /// 批量写入部署记录，同一合约地址已存在时忽略（与区块写入在同一事务中调用）
+    pub async fn batch_save(
+        &self,
+        conn: &mut AsyncPgConnection,
+        deployments: &[ContractDeployment],
+    ) -> Result<(), AppError> {
+        use crate::models::schema::contract_deployment::dsl::*;
+
+        let rows: Vec<ContractDeploymentInsert> =
+            deployments.iter().cloned().map(Into::into).collect();
+        for chunk in rows.chunks(1000) {
+            diesel::insert_into(contract_deployment_db)
+                .values(chunk)
+                .on_conflict(contract_address)
+                .do_nothing()
+                .execute(conn)
+                .await
+                .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+        }
+        Ok(())
+    }
+
+    /// 指定部署者部署过的全部合约地址（启动时恢复自动监控的合约）
+    pub async fn contracts_deployed_by(
+        &self,
+        conn: &mut AsyncPgConnection,
+        deployers: &[H160],
+    ) -> Result<Vec<H160>, AppError> {
+        use crate::models::schema::contract_deployment::dsl::*;
+        use diesel::{ExpressionMethods, QueryDsl};
+
+        if deployers.is_empty() {
+            return Ok(vec![]);
+        }
+        // 入库时统一使用 {:#x} 小写格式
+        let deployers: Vec<String> = deployers.iter().map(|d| format!("{:#x}", d)).collect();
+        let addresses = contract_deployment
+            .select(contract_address)
+            .filter(deployer.eq_any(deployers))
+            .load::<String>(conn)
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+        Ok(addresses
+            .iter()
+            .filter_map(|a| a.parse::<H160>().ok())
+            .collect())
+    }
+}
@@ -1,3 +1,7 @@
+pub mod address_stats_repository;
+pub mod block_queue_repository;
 pub mod block_repository;
+pub mod contract_deployment_repository;
+pub mod sync_state_repository;
 pub mod traits;
 pub mod transaction_repository;
\ No newline at end of file
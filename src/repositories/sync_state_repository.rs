@@ -0,0 +1,48 @@
+use crate::errors::error::AppError;
+use crate::models::schema::sync_state_db;
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+
+/// sync_state 单行状态表
+#[derive(Clone, Default)]
+pub struct SyncStateRepository {}
+
+impl SyncStateRepository {
+    pub fn new() -> Self {
+        Self {}
+    }
+
+    /// 已入库数据对应的解析器版本，未记录时返回 None
+    pub async fn get_parser_version(
+        &self,
+        conn: &mut AsyncPgConnection,
+    ) -> Result<Option<i32>, AppError> {
+        use crate::models::schema::sync_state::dsl::*;
+        use diesel::{OptionalExtension, QueryDsl};
+
+        sync_state
+            .select(parser_version)
+            .first::<i32>(conn)
+            .await
+            .optional()
+            .map_err(|e| AppError::DatabaseError(e.to_string()))
+    }
+
+    pub async fn set_parser_version(
+        &self,
+        conn: &mut AsyncPgConnection,
+        version: i32,
+    ) -> Result<(), AppError> {
+        use crate::models::schema::sync_state::dsl::*;
+        use diesel::ExpressionMethods;
+
+        diesel::insert_into(sync_state_db)
+            .values((id.eq(1i16), parser_version.eq(version)))
+            .on_conflict(id)
+            .do_update()
+            .set((parser_version.eq(version), updated_at.eq(diesel::dsl::now)))
+            .execute(conn)
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+        Ok(())
+    }
+}
@@ -1,51 +1,381 @@
+use crate::config::{AmountOverflowPolicy, AmountPrecisionConfig};
 use crate::errors::error::AppError;
-use crate::models::domain::transfer::Transfer;
+use crate::log_error;
+use crate::models::domain::transfer::{STATUS_CONFIRMED, STATUS_CONFIRMING, Transfer};
 use crate::models::schema::eth_transfer::{log_index, tx_hash};
-use crate::models::schema::eth_transfer_db;
-use crate::models::transfer_db::EthTransferInsert;
+use crate::models::schema::{eth_transfer_db, eth_transfer_rejected_db};
+use crate::models::transfer_db::{
+    EthTransferInsert, EthTransferRejectedInsert, EthTransferRow, TransferSummary,
+};
 use crate::repositories::traits::repository::Repository;
+use crate::utils::time::validate_time_range;
 use async_trait::async_trait;
+use bigdecimal::BigDecimal;
 use diesel_async::{AsyncPgConnection, RunQueryDsl};
+use ethers_core::types::H160;
+use std::borrow::Cow;
+use std::collections::HashSet;
 
 #[derive(Clone)]
-pub struct TransactionRepository {}
+pub struct TransactionRepository {
+    /// 写入前的 amount 精度校验，默认不校验
+    amount_precision: AmountPrecisionConfig,
+}
 
 impl TransactionRepository {
     pub fn new() -> Self {
-        Self {}
+        Self {
+            amount_precision: AmountPrecisionConfig::default(),
+        }
     }
-}
 
-#[async_trait]
-impl Repository<Transfer, i64> for TransactionRepository {
-    async fn find_by_id(&self, id: i64) -> Result<Option<Transfer>, AppError> {
-        todo!()
+    pub fn with_amount_precision(mut self, amount_precision: AmountPrecisionConfig) -> Self {
+        self.amount_precision = amount_precision;
+        self
     }
 
-    async fn save(&self, conn: &mut AsyncPgConnection, entity: &Transfer) -> Result<(), AppError> {
-        todo!()
+    /// 按 `amount_precision` 处理超出列精度的金额：Reject 返回 Validation 错误，
+    /// Skip 从待写入列表中移除并返回对应的死信记录；都未超出时不复制
+    fn check_amounts<'a>(
+        &self,
+        transfers: &'a [Transfer],
+    ) -> Result<(Cow<'a, [Transfer]>, Vec<EthTransferRejectedInsert>), AppError> {
+        let Some(precision) = self.amount_precision.precision else {
+            return Ok((Cow::Borrowed(transfers), vec![]));
+        };
+        let fits = |t: &Transfer| t.amount.digits() <= precision;
+        if transfers.iter().all(fits) {
+            return Ok((Cow::Borrowed(transfers), vec![]));
+        }
+
+        let policy = self.amount_precision.on_overflow;
+        let mut checked = Vec::with_capacity(transfers.len());
+        let mut rejected = Vec::new();
+        for transfer in transfers {
+            if fits(transfer) {
+                checked.push(transfer.clone());
+                continue;
+            }
+            log_error!(
+                "转账金额超出 amount 列精度 {} 位（{:?}）: tx={}, log_index={}, amount={}",
+                precision,
+                policy,
+                transfer.tx_hash,
+                transfer.log_index,
+                transfer.amount
+            );
+            let reason = format!(
+                "交易 {} (log_index {}) 的金额 {} 超出 amount 列精度 {} 位",
+                transfer.tx_hash, transfer.log_index, transfer.amount, precision
+            );
+            match policy {
+                AmountOverflowPolicy::Reject => return Err(AppError::Validation(reason)),
+                AmountOverflowPolicy::Skip => {
+                    rejected.push(EthTransferRejectedInsert::new(transfer, reason));
+                }
+            }
+        }
+        Ok((Cow::Owned(checked), rejected))
     }
 
-    async fn batch_save(
+    /// 批量写入转账，已存在的 (tx_hash, log_index) 忽略；返回本次实际写入的转账
+    /// 按 Skip 策略丢弃的转账写入 eth_transfer_rejected，不计入返回值
+    pub async fn insert_new(
         &self,
         conn: &mut AsyncPgConnection,
-        transfers: &Vec<Transfer>,
-    ) -> Result<(), AppError> {
+        transfers: &[Transfer],
+    ) -> Result<Vec<Transfer>, AppError> {
+        let (transfers, rejected) = self.check_amounts(transfers)?;
+        if !rejected.is_empty() {
+            diesel::insert_into(eth_transfer_rejected_db)
+                .values(&rejected)
+                .on_conflict_do_nothing()
+                .execute(conn)
+                .await
+                .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+        }
         let diesel_transfers: Vec<EthTransferInsert> = transfers
             .iter()
             .map(|t| t.clone().try_into())
             .collect::<Result<Vec<_>, _>>()?;
 
+        let mut inserted: HashSet<(String, i64)> = HashSet::new();
         for chunk in diesel_transfers.chunks(1000) {
-            diesel::insert_into(eth_transfer_db)
+            let keys: Vec<(String, i64)> = diesel::insert_into(eth_transfer_db)
                 .values(chunk)
                 .on_conflict((tx_hash, log_index))
                 .do_nothing()
-                .execute(conn)
+                .returning((tx_hash, log_index))
+                .get_results(conn)
                 .await
                 .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+            inserted.extend(keys);
         }
-        Ok(())
+        Ok(transfers
+            .iter()
+            .filter(|t| inserted.contains(&(t.tx_hash.as_str().to_string(), t.log_index)))
+            .cloned()
+            .collect())
+    }
+
+    /// 查询某地址最近的转账记录（转出 + 转入），按区块号、log_index 倒序
+    ///
+    /// 这里刻意使用 UNION 而不是 `from_address = $1 OR to_address = $1`：
+    /// OR 条件会让 PG 放弃单列索引退化为全表扫描。拆成两条子查询后，
+    /// 各自走 `idx_eth_transfer_from_address` / `idx_eth_transfer_to_address`，
+    /// 每个分支最多取 `limit` 条，再在合并结果上排序截断。
+    ///
+    /// 预期执行计划（EXPLAIN）：
+    /// ```text
+    /// Limit
+    ///   -> Sort (block_number DESC, log_index DESC)
+    ///     -> HashAggregate / Unique
+    ///       -> Append
+    ///         -> Limit -> Index Scan using idx_eth_transfer_from_address on eth_transfer
+    ///         -> Limit -> Index Scan using idx_eth_transfer_to_address on eth_transfer
+    /// ```
+    /// 若出现 `Seq Scan on eth_transfer`，说明索引迁移未执行。
+    pub async fn recent_transfers_for(
+        &self,
+        conn: &mut AsyncPgConnection,
+        address: &H160,
+        limit: i64,
+    ) -> Result<Vec<Transfer>, AppError> {
+        use crate::models::schema::eth_transfer::dsl::*;
+        use diesel::query_dsl::positional_order_dsl::{OrderColumn, PositionalOrderDsl};
+        use diesel::{CombineDsl, ExpressionMethods, QueryDsl};
+
+        // 入库时统一使用 {:#x} 小写格式
+        let addr = format!("{:#x}", address);
+        let columns = (
+            block_number,
+            tx_hash,
+            from_address,
+            to_address,
+            amount,
+            contract_address,
+            timestamp,
+            gas,
+            max_fee_per_gas,
+            status,
+            log_index,
+            access_list,
+            fee_paid,
+            sender_unknown,
+            kind,
+            to_contract,
+            validator_index,
+        );
+
+        let outgoing = eth_transfer
+            .select(columns)
+            .filter(from_address.eq(addr.clone()))
+            .order_by((block_number.desc(), log_index.desc()))
+            .limit(limit);
+        let incoming = eth_transfer
+            .select(columns)
+            .filter(to_address.eq(addr))
+            .order_by((block_number.desc(), log_index.desc()))
+            .limit(limit);
+
+        // 第 1 列 block_number，第 11 列 log_index
+        let rows = outgoing
+            .union(incoming)
+            .positional_order_by((OrderColumn(1).desc(), OrderColumn(11).desc()))
+            .limit(limit)
+            .load::<EthTransferRow>(conn)
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        rows.into_iter().map(Transfer::try_from).collect()
+    }
+
+    /// 按区块时间查询 [from_ts, to_ts] 区间内的转账（unix 秒，闭区间），按时间倒序分页
+    /// 走 `idx_eth_transfer_timestamp` 索引
+    pub async fn find_transfers_by_time_range(
+        &self,
+        conn: &mut AsyncPgConnection,
+        from_ts: i64,
+        to_ts: i64,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<Transfer>, AppError> {
+        use crate::models::schema::eth_transfer::dsl::*;
+        use diesel::{ExpressionMethods, QueryDsl};
+
+        validate_time_range(from_ts, to_ts)?;
+        let rows = eth_transfer
+            .select((
+                block_number,
+                tx_hash,
+                from_address,
+                to_address,
+                amount,
+                contract_address,
+                timestamp,
+                gas,
+                max_fee_per_gas,
+                status,
+                log_index,
+                access_list,
+                fee_paid,
+                sender_unknown,
+                kind,
+                to_contract,
+                validator_index,
+            ))
+            .filter(timestamp.between(from_ts, to_ts))
+            .order_by((timestamp.desc(), block_number.desc(), log_index.desc()))
+            .limit(limit)
+            .offset(offset)
+            .load::<EthTransferRow>(conn)
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        rows.into_iter().map(Transfer::try_from).collect()
+    }
+
+    /// 与 [`Self::recent_transfers_for`] 相同的查询，只投影 (tx_hash, amount, block_number)
+    ///
+    /// log_index 仅用于排序和区分同一交易中的多条日志，不返回给调用方
+    pub async fn list_summaries(
+        &self,
+        conn: &mut AsyncPgConnection,
+        address: &H160,
+        limit: i64,
+    ) -> Result<Vec<TransferSummary>, AppError> {
+        use crate::models::schema::eth_transfer::dsl::*;
+        use diesel::query_dsl::positional_order_dsl::{OrderColumn, PositionalOrderDsl};
+        use diesel::{CombineDsl, ExpressionMethods, QueryDsl};
+
+        let addr = format!("{:#x}", address);
+        let columns = (tx_hash, amount, block_number, log_index);
+
+        let outgoing = eth_transfer
+            .select(columns)
+            .filter(from_address.eq(addr.clone()))
+            .order_by((block_number.desc(), log_index.desc()))
+            .limit(limit);
+        let incoming = eth_transfer
+            .select(columns)
+            .filter(to_address.eq(addr))
+            .order_by((block_number.desc(), log_index.desc()))
+            .limit(limit);
+
+        // 第 3 列 block_number，第 4 列 log_index
+        let rows = outgoing
+            .union(incoming)
+            .positional_order_by((OrderColumn(3).desc(), OrderColumn(4).desc()))
+            .limit(limit)
+            .load::<(String, BigDecimal, i64, i64)>(conn)
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        Ok(rows
+            .into_iter()
+            .map(|(hash, value, height, _)| (hash, value, height))
+            .collect())
+    }
+
+    /// 区块内已入库转账的 (tx_hash, log_index)
+    pub async fn keys_in_block(
+        &self,
+        conn: &mut AsyncPgConnection,
+        height: i64,
+    ) -> Result<Vec<(String, i64)>, AppError> {
+        use crate::models::schema::eth_transfer::dsl::*;
+        use diesel::{ExpressionMethods, QueryDsl};
+
+        eth_transfer
+            .filter(block_number.eq(height))
+            .select((tx_hash, log_index))
+            .load(conn)
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))
+    }
+
+    /// 区块号高于 `height` 的转账涉及的地址（发送方未知时不含发送方），回滚前据此重算地址统计
+    pub async fn addresses_above(
+        &self,
+        conn: &mut AsyncPgConnection,
+        height: i64,
+    ) -> Result<Vec<String>, AppError> {
+        use crate::models::schema::eth_transfer::dsl::*;
+        use diesel::{CombineDsl, ExpressionMethods, QueryDsl};
+
+        let senders = eth_transfer
+            .select(from_address)
+            .filter(block_number.gt(height))
+            .filter(sender_unknown.eq(false));
+        let receivers = eth_transfer
+            .select(to_address)
+            .filter(block_number.gt(height));
+        senders
+            .union(receivers)
+            .load(conn)
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))
+    }
+
+    /// 删除区块号高于 `height` 的转账记录及死信记录（回滚孤块时使用），返回删除的转账行数
+    pub async fn delete_above(
+        &self,
+        conn: &mut AsyncPgConnection,
+        height: i64,
+    ) -> Result<usize, AppError> {
+        use crate::models::schema::eth_transfer::dsl::*;
+        use crate::models::schema::eth_transfer_rejected;
+        use diesel::{ExpressionMethods, QueryDsl};
+
+        diesel::delete(
+            eth_transfer_rejected_db.filter(eth_transfer_rejected::block_number.gt(height)),
+        )
+        .execute(conn)
+        .await
+        .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+        diesel::delete(eth_transfer.filter(block_number.gt(height)))
+            .execute(conn)
+            .await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))
+    }
+
+    /// 将 `up_to` 及以下区块中确认中（status = 2）的转账提升为已确认（status = 1），返回更新条数
+    pub async fn promote_confirmed(
+        &self,
+        conn: &mut AsyncPgConnection,
+        up_to: i64,
+    ) -> Result<usize, AppError> {
+        use crate::models::schema::eth_transfer::dsl::*;
+        use diesel::{ExpressionMethods, QueryDsl};
+
+        diesel::update(
+            eth_transfer
+                .filter(status.eq(STATUS_CONFIRMING))
+                .filter(block_number.le(up_to)),
+        )
+        .set(status.eq(STATUS_CONFIRMED))
+        .execute(conn)
+        .await
+        .map_err(|e| AppError::DatabaseError(e.to_string()))
+    }
+}
+
+#[async_trait]
+impl Repository<Transfer, i64> for TransactionRepository {
+    async fn find_by_id(&self, id: i64) -> Result<Option<Transfer>, AppError> {
+        todo!()
+    }
+
+    async fn save(&self, conn: &mut AsyncPgConnection, entity: &Transfer) -> Result<(), AppError> {
+        todo!()
+    }
+
+    async fn batch_save(
+        &self,
+        conn: &mut AsyncPgConnection,
+        transfers: &Vec<Transfer>,
+    ) -> Result<(), AppError> {
+        self.insert_new(conn, transfers).await.map(|_| ())
     }
 
     async fn delete(&self, id: i64) -> Result<(), AppError> {
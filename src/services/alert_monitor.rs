@@ -0,0 +1,146 @@
+use crate::config::AlertConfig;
+use crate::infrastructure::alert::{Alert, AlertKind, Alerter};
+use crate::infrastructure::provider::ProviderTrait;
+use crate::services::block_service::BlockService;
+use crate::{log_error, log_info};
+use ethers_core::types::U256;
+use std::collections::HashMap;
+use std::sync::atomic::Ordering;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::task::JoinHandle;
+
+/// 按配置阈值定期检查同步延迟、RPC 连续失败、重组深度与余额下限，超限时通过 Alerter 发送告警
+///
+/// 同一告警 key 在 `debounce_secs` 内只发送一次，条件持续存在时按该间隔重复提醒
+pub struct AlertMonitor {
+    config: AlertConfig,
+    provider: Arc<dyn ProviderTrait>,
+    block_service: Arc<BlockService>,
+    alerters: Vec<Arc<dyn Alerter>>,
+    /// 告警 key → 上次发送时间
+    last_fired: Mutex<HashMap<String, Instant>>,
+}
+
+impl AlertMonitor {
+    pub fn new(
+        config: AlertConfig,
+        provider: Arc<dyn ProviderTrait>,
+        block_service: Arc<BlockService>,
+        alerters: Vec<Arc<dyn Alerter>>,
+    ) -> Self {
+        Self {
+            config,
+            provider,
+            block_service,
+            alerters,
+            last_fired: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// 启动后台检查任务
+    pub fn spawn(self: Arc<Self>) -> JoinHandle<()> {
+        let interval = Duration::from_secs(self.config.interval_secs.max(1));
+        log_info!(
+            "告警监控已启动: interval={:?}, debounce={}s, 渠道 {} 个",
+            interval,
+            self.config.debounce_secs,
+            self.alerters.len()
+        );
+        tokio::spawn(async move {
+            loop {
+                tokio::time::sleep(interval).await;
+                for alert in self.evaluate().await {
+                    self.fire(alert).await;
+                }
+            }
+        })
+    }
+
+    /// 按阈值检查当前状态，返回需要告警的条目（未去抖）
+    pub async fn evaluate(&self) -> Vec<Alert> {
+        let mut alerts = Vec::new();
+
+        if let Some(max_lag) = self.config.max_sync_lag_blocks {
+            match self.provider.get_last_block_number().await {
+                Ok(head) => {
+                    let synced = self.block_service.last_synced.load(Ordering::Relaxed);
+                    let lag = head.as_u64().saturating_sub(synced);
+                    if lag > max_lag {
+                        alerts.push(Alert::new(
+                            AlertKind::SyncLag,
+                            format!(
+                                "同步延迟 {}，链头 {}，本地 {}，阈值 {}",
+                                self.block_service.describe_lag(lag).await,
+                                head,
+                                synced,
+                                max_lag
+                            ),
+                        ));
+                    }
+                }
+                Err(e) => log_error!("告警检查获取链头失败: {}", e),
+            }
+        }
+
+        if let Some(max_failures) = self.config.max_consecutive_rpc_failures {
+            let failures = self.provider.consecutive_failures();
+            if failures >= max_failures {
+                alerts.push(Alert::new(
+                    AlertKind::RpcFailures,
+                    format!("RPC 已连续失败 {} 次，阈值 {}", failures, max_failures),
+                ));
+            }
+        }
+
+        // 读取后清零，每次重组只告警一次
+        let depth = self.block_service.reorg_depth.swap(0, Ordering::Relaxed);
+        if let Some(max_depth) = self.config.max_reorg_depth {
+            if depth > max_depth {
+                alerts.push(Alert::new(
+                    AlertKind::ReorgDepth,
+                    format!("检测到深度 {} 的链重组，阈值 {}", depth, max_depth),
+                ));
+            }
+        }
+
+        for floor in &self.config.balance_floors {
+            let floor_wei = U256::from(floor.floor_gwei) * U256::exp10(9);
+            match self.provider.get_balance(floor.address).await {
+                Ok(balance) if balance < floor_wei => alerts.push(
+                    Alert::new(
+                        AlertKind::LowBalance,
+                        format!(
+                            "地址 {:?} 余额 {} wei 低于下限 {} gwei",
+                            floor.address, balance, floor.floor_gwei
+                        ),
+                    )
+                    .with_key(format!("{}:{:?}", AlertKind::LowBalance, floor.address)),
+                ),
+                Ok(_) => {}
+                Err(e) => log_error!("告警检查查询 {:?} 余额失败: {}", floor.address, e),
+            }
+        }
+        alerts
+    }
+
+    /// 去抖后发送到所有渠道；单个渠道失败只记录日志
+    async fn fire(&self, alert: Alert) {
+        {
+            let mut last_fired = self.last_fired.lock().unwrap();
+            let debounce = Duration::from_secs(self.config.debounce_secs);
+            if last_fired
+                .get(&alert.key)
+                .is_some_and(|at| at.elapsed() < debounce)
+            {
+                return;
+            }
+            last_fired.insert(alert.key.clone(), Instant::now());
+        }
+        for alerter in &self.alerters {
+            if let Err(e) = alerter.send(&alert).await {
+                log_error!("告警发送失败 [{}]: {}", alert.kind, e);
+            }
+        }
+    }
+}
@@ -0,0 +1,192 @@
+use crate::config::BlockQueueConfig;
+use crate::database::diesel::DbService;
+use crate::errors::error::AppError;
+use crate::repositories::block_queue_repository::BlockQueueRepository;
+use crate::services::block_service::{BlockService, CommitOutcome};
+use crate::{log_error, log_info, log_warn};
+use std::sync::Arc;
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+
+/// 队列模式的区块同步：生产者把安全高度写入 block_queue，多个 worker 领取、并行解析，
+/// 再按严格升序提交并在同一事务中确认。进程崩溃后未确认的区块仍在队列中，租约过期后被重新领取
+pub struct BlockQueueService {
+    block_service: Arc<BlockService>,
+    queue_repository: Arc<BlockQueueRepository>,
+    db_service: Arc<DbService>,
+    config: BlockQueueConfig,
+}
+
+impl BlockQueueService {
+    pub fn new(
+        block_service: Arc<BlockService>,
+        queue_repository: Arc<BlockQueueRepository>,
+        db_service: Arc<DbService>,
+        config: BlockQueueConfig,
+    ) -> Self {
+        Self {
+            block_service,
+            queue_repository,
+            db_service,
+            config,
+        }
+    }
+
+    /// 启动生产者、租约回收与 `workers` 个处理任务
+    pub fn spawn(self: Arc<Self>) -> Vec<JoinHandle<()>> {
+        let poll = Duration::from_millis(self.config.poll_interval_ms);
+        let mut handles = Vec::with_capacity(self.config.workers + 1);
+
+        let producer = Arc::clone(&self);
+        handles.push(tokio::spawn(async move {
+            loop {
+                if let Err(e) = producer.produce_once().await {
+                    log_error!("区块入队失败: {}", e);
+                }
+                if let Err(e) = producer.reclaim_expired().await {
+                    log_error!("回收过期队列项失败: {}", e);
+                }
+                tokio::time::sleep(poll).await;
+            }
+        }));
+
+        for i in 0..self.config.workers {
+            let worker = Arc::clone(&self);
+            let worker_id = format!("{}-{}", std::process::id(), i);
+            handles.push(tokio::spawn(async move {
+                loop {
+                    match worker.work_once(&worker_id).await {
+                        Ok(true) => {}
+                        Ok(false) => tokio::time::sleep(poll).await,
+                        Err(e) => {
+                            log_error!("worker {} 处理失败: {}", worker_id, e);
+                            tokio::time::sleep(poll).await;
+                        }
+                    }
+                }
+            }));
+        }
+        log_info!("区块队列已启动: workers={}", self.config.workers);
+        handles
+    }
+
+    /// 将新的安全高度入队；队列长度受 `max_pending` 限制，避免落后太多时一次写入过多
+    pub async fn produce_once(&self) -> Result<usize, AppError> {
+        let eth_config = &self.block_service.config;
+        let chain_head = self.block_service.provider.get_last_block_number().await?;
+        // 两阶段状态与按合约确认数暂缓都依据链头判断
+        self.block_service
+            .chain_head
+            .fetch_max(chain_head.as_u64(), Ordering::Relaxed);
+        let mut safe_height = chain_head
+            .as_u64()
+            .saturating_sub(eth_config.delay.max(0) as u64);
+        if let Some(stop) = eth_config.stop_at_block {
+            safe_height = safe_height.min(stop);
+        }
+        let safe_height = safe_height as i64;
+
+        let mut conn = self
+            .db_service
+            .pool
+            .get()
+            .await
+            .map_err(|e| AppError::Internal(e.to_string()))?;
+
+        let pending = self.queue_repository.len(&mut conn).await?;
+        let capacity = self.config.max_pending as i64 - pending;
+        if capacity <= 0 {
+            return Ok(0);
+        }
+
+        let last_stored = self
+            .block_service
+            .block_repository
+            .get_last_block_number(&mut conn)
+            .await?
+            .map(|b| b.block_number);
+        if let Some(n) = last_stored {
+            self.block_service
+                .last_synced
+                .fetch_max(n as u64, Ordering::Relaxed);
+        }
+        let last_queued = self.queue_repository.max_block_number(&mut conn).await?;
+        let next = match last_stored.max(last_queued) {
+            Some(n) => n + 1,
+            None if eth_config.start_from_head => safe_height,
+            None => eth_config.init_height as i64,
+        };
+        let to = safe_height.min(next + capacity - 1);
+
+        let inserted = self.queue_repository.enqueue_range(&mut conn, next, to).await?;
+        if inserted > 0 {
+            log_info!("区块入队: {} → {}（{} 个）", next, to, inserted);
+        }
+        Ok(inserted)
+    }
+
+    async fn reclaim_expired(&self) -> Result<(), AppError> {
+        let mut conn = self
+            .db_service
+            .pool
+            .get()
+            .await
+            .map_err(|e| AppError::Internal(e.to_string()))?;
+        let reclaimed = self
+            .queue_repository
+            .reclaim_expired(&mut conn, self.config.lease_secs as i64)
+            .await?;
+        if reclaimed > 0 {
+            log_warn!("回收 {} 个租约过期的队列项", reclaimed);
+        }
+        Ok(())
+    }
+
+    /// 领取并处理一个区块；队列为空时返回 Ok(false)
+    pub async fn work_once(&self, worker_id: &str) -> Result<bool, AppError> {
+        let claimed = {
+            let mut conn = self
+                .db_service
+                .pool
+                .get()
+                .await
+                .map_err(|e| AppError::Internal(e.to_string()))?;
+            self.queue_repository.claim(&mut conn, worker_id).await?
+        };
+        let Some(height) = claimed else {
+            return Ok(false);
+        };
+
+        if let Err(e) = self.process(height).await {
+            log_warn!("区块 {} 处理失败，放回队列: {}", height, e);
+            let mut conn = self
+                .db_service
+                .pool
+                .get()
+                .await
+                .map_err(|e| AppError::Internal(e.to_string()))?;
+            self.queue_repository
+                .release(&mut conn, height, &e.to_string())
+                .await?;
+            return Err(e);
+        }
+        Ok(true)
+    }
+
+    /// 解析可以并行，提交必须等前一个区块入库
+    async fn process(&self, height: i64) -> Result<(), AppError> {
+        let prepared = self.block_service.prepare_block(height as u64).await?;
+        let poll = Duration::from_millis(self.config.poll_interval_ms);
+        loop {
+            match self
+                .block_service
+                .commit_in_order(&prepared, Arc::clone(&self.queue_repository))
+                .await?
+            {
+                CommitOutcome::Committed | CommitOutcome::AlreadyCommitted => return Ok(()),
+                CommitOutcome::Waiting => tokio::time::sleep(poll).await,
+            }
+        }
+    }
+}
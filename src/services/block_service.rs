@@ -1,30 +1,188 @@
 use crate::config::EthereumConfig;
 use crate::config::filter_config::{FilterConfig, FilterConfigContainer};
 use crate::database::diesel::{DbService, TransactionExecutor};
-use crate::errors::error::AppError;
-use crate::infrastructure::parser::EventParser;
+use crate::errors::error::{AppError, SyncError};
+use crate::infrastructure::parser::{EventParser, PARSER_VERSION};
 use crate::infrastructure::provider::ProviderTrait;
 use crate::models::BlockDomain;
 use crate::models::domain::block::BlockQuery;
+use crate::models::Transfer;
+use crate::models::domain::transfer::{STATUS_CONFIRMED, STATUS_CONFIRMING};
+use crate::models::domain::deployment::ContractDeployment;
+use crate::repositories::address_stats_repository::AddressStatsRepository;
+use crate::repositories::block_queue_repository::BlockQueueRepository;
+use crate::repositories::contract_deployment_repository::ContractDeploymentRepository;
+use crate::repositories::sync_state_repository::SyncStateRepository;
 use crate::repositories::block_repository::BlockRepository;
 use crate::repositories::traits::repository::Repository;
 use crate::repositories::transaction_repository::TransactionRepository;
-use crate::utils::{is_target_transaction, opt_u256_to_i64_loose, option_u64_to_i64, u256_to_i64};
+use crate::utils::time::format_duration_approx;
+use crate::utils::{h256_to_string, is_target_transaction, opt_u256_to_i64_loose, option_u64_to_i64, u256_to_i64};
 use crate::{log_error, log_info, log_warn};
 use anyhow::Context;
+use futures_util::{Stream, StreamExt, stream};
 use ethers::prelude::U64;
-use ethers_core::types::Transaction;
-use std::sync::Arc;
-use std::time::Duration;
+use diesel_async::AsyncPgConnection;
+use ethers_core::types::{Block, H160, H256, Log, Transaction};
+use std::collections::{BTreeMap, HashSet};
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+use tracing::{Instrument, Span, info_span, instrument};
+
+/// 估算出块时间时采样的区块跨度
+const BLOCK_TIME_SAMPLE_SPAN: u64 = 100;
+/// 出块时间估算的缓存有效期
+const BLOCK_TIME_REFRESH: Duration = Duration::from_secs(600);
+
+/// 按合约确认数暂缓入库的转账：(区块高度, 合约) → 转账
+type HeldTransfers = BTreeMap<(u64, H160), Vec<Transfer>>;
+
+/// 区块转账按合约确认数拆分后的提交计划
+struct HoldPlan {
+    /// 本区块可直接入库的转账
+    ready: Vec<Transfer>,
+    /// 本区块需暂缓的转账，提交成功后放入缓冲区
+    held: HeldTransfers,
+    /// 随本区块一起提交的已成熟暂缓转账，及其在缓冲区中的键
+    matured_keys: Vec<(u64, H160)>,
+    matured: Vec<Transfer>,
+}
+
+/// 写入已成熟的暂缓转账；暂缓时未计入地址统计，只为本次实际写入的行补上，
+/// 重复提交（启动恢复后再次放回缓冲区、事务重试）不会重复计数
+async fn save_matured(
+    conn: &mut AsyncPgConnection,
+    tx_repo: &TransactionRepository,
+    stats_repo: &AddressStatsRepository,
+    chain_id: i64,
+    matured: &[Transfer],
+) -> Result<(), AppError> {
+    if matured.is_empty() {
+        return Ok(());
+    }
+    let inserted = tx_repo.insert_new(conn, matured).await?;
+    if !inserted.is_empty() {
+        stats_repo.upsert_from_transfers(conn, chain_id, &inserted).await?;
+    }
+    Ok(())
+}
+
+/// `idle_at` 的哨兵值：未处于空闲状态
+const NOT_IDLE: u64 = u64::MAX;
+
+/// 流水线模式下单个区块拉取 / 解析的最大尝试次数，之后本轮同步返回错误
+const PIPELINE_PREPARE_ATTEMPTS: u32 = 3;
+
+/// 以 `window` 为并发窗口准备 [from, to] 的区块，结果按高度顺序产出（与完成先后无关），附带对应高度
+fn prepare_in_order<'a, T, Fut>(
+    from: u64,
+    to: u64,
+    window: usize,
+    mut prepare: impl FnMut(u64) -> Fut + 'a,
+) -> impl Stream<Item = (u64, T)> + 'a
+where
+    Fut: Future<Output = T> + 'a,
+    T: 'a,
+{
+    stream::iter(from..=to)
+        .map(move |block_number| {
+            let prepared = prepare(block_number);
+            async move { (block_number, prepared.await) }
+        })
+        .buffered(window)
+}
+
+/// 队列模式下已拉取并解析、等待按序提交的区块
+pub struct PreparedBlock {
+    pub block: BlockDomain,
+    pub transfers: Arc<Vec<Transfer>>,
+    pub deployments: Arc<Vec<ContractDeployment>>,
+    pub skipped: usize,
+}
+
+/// 区块流产出的解析结果，不入库，由调用方自行处理
+#[derive(Debug, Clone)]
+pub struct ParsedBlock {
+    pub block: BlockDomain,
+    pub transfers: Vec<Transfer>,
+}
+
+/// 区块流的内部状态
+struct StreamState {
+    next: u64,
+    safe: u64,
+    /// 上一个产出区块的 (哈希, 时间戳)，用于父哈希与时间戳校验
+    parent: Option<(String, i64)>,
+    /// 上一次产出的是错误，重试前先等待，避免调用方持续轮询时空转
+    failed: bool,
+}
+
+impl StreamState {
+    fn fail(mut self, e: AppError) -> (Result<ParsedBlock, AppError>, Self) {
+        self.failed = true;
+        (Err(e), self)
+    }
+}
+
+/// 回滚本地数据时在同一事务中使用的仓储
+pub struct RewindRepositories {
+    block_repository: Arc<BlockRepository>,
+    transaction_repository: Arc<TransactionRepository>,
+    address_stats_repository: Arc<AddressStatsRepository>,
+    chain_id: i64,
+}
+
+impl RewindRepositories {
+    /// 删除高于 `keep` 的区块与转账，并按剩余转账重算受影响地址的统计；返回 (区块数, 转账数)
+    pub async fn delete_above(
+        &self,
+        conn: &mut AsyncPgConnection,
+        keep: i64,
+    ) -> Result<(usize, usize), AppError> {
+        let addresses = self.transaction_repository.addresses_above(conn, keep).await?;
+        let transfers = self.transaction_repository.delete_above(conn, keep).await?;
+        let blocks = self.block_repository.delete_above(conn, keep).await?;
+        self.address_stats_repository
+            .recompute_for_addresses(conn, self.chain_id, &addresses)
+            .await?;
+        Ok((blocks, transfers))
+    }
+}
+
+/// 按序提交的结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CommitOutcome {
+    Committed,
+    /// 前一个区块尚未提交，稍后重试
+    Waiting,
+    /// 该区块已被其他 worker 提交（租约过期后被重复领取）
+    AlreadyCommitted,
+}
 
 pub struct BlockService {
     pub config: Arc<EthereumConfig>,
     pub filter_config: Arc<FilterConfigContainer>,
     pub block_repository: Arc<BlockRepository>,
     pub transaction_repository: Arc<TransactionRepository>,
+    pub address_stats_repository: Arc<AddressStatsRepository>,
+    pub deployment_repository: Arc<ContractDeploymentRepository>,
+    pub sync_state_repository: Arc<SyncStateRepository>,
     pub db_service: Arc<DbService>,
     pub provider: Arc<dyn ProviderTrait>,
     pub event_parser: Arc<EventParser>,
+    /// 最近一次成功入库的区块高度（供 /ready 计算同步延迟）
+    pub last_synced: Arc<AtomicU64>,
+    /// 上次告警检查以来观察到的最大重组深度（告警检查读取后清零）
+    pub reorg_depth: Arc<AtomicU64>,
+    /// 最近观察到的链头高度（两阶段转账状态据此判断是否仍在重组窗口内）
+    pub chain_head: Arc<AtomicU64>,
+    /// 估算的平均出块间隔（秒）及其计算时间
+    block_time: Mutex<Option<(Instant, f64)>>,
+    /// 已追上的安全高度（空闲状态），`NOT_IDLE` 表示仍有区块待同步
+    idle_at: AtomicU64,
+    /// 暂缓入库的转账，达到合约所需确认数后随下一个区块一起提交
+    held_transfers: Mutex<HeldTransfers>,
 }
 
 impl BlockService {
@@ -42,22 +200,303 @@ impl BlockService {
             filter_config,
             block_repository,
             transaction_repository,
+            address_stats_repository: Arc::new(AddressStatsRepository::new()),
+            deployment_repository: Arc::new(ContractDeploymentRepository::new()),
+            sync_state_repository: Arc::new(SyncStateRepository::new()),
             db_service,
             provider,
             event_parser,
+            last_synced: Arc::new(AtomicU64::new(0)),
+            reorg_depth: Arc::new(AtomicU64::new(0)),
+            chain_head: Arc::new(AtomicU64::new(0)),
+            block_time: Mutex::new(None),
+            idle_at: AtomicU64::new(NOT_IDLE),
+            held_transfers: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    /// 回滚本地数据所需的仓储，在 `execute_tx` 闭包中调用 [`RewindRepositories::delete_above`]
+    pub fn rewind_repositories(&self) -> RewindRepositories {
+        RewindRepositories {
+            block_repository: Arc::clone(&self.block_repository),
+            transaction_repository: Arc::clone(&self.transaction_repository),
+            address_stats_repository: Arc::clone(&self.address_stats_repository),
+            chain_id: self.config.chain_id as i64,
+        }
+    }
+
+    /// 回滚本地数据后重置同步状态：本地最新高度、空闲状态，并丢弃高于 `keep` 的暂缓转账
+    pub fn reset_after_rewind(&self, keep: i64) {
+        self.last_synced.store(keep.max(0) as u64, Ordering::Relaxed);
+        self.idle_at.store(NOT_IDLE, Ordering::Relaxed);
+        self.held_transfers
+            .lock()
+            .unwrap()
+            .retain(|(block, _), _| (*block as i64) <= keep);
+    }
+
+    /// 拆分出需要暂缓入库的转账：合约配置的确认数尚未达到（区块高度 + 所需确认数 > 链头）
+    fn split_immature(
+        &self,
+        block_number: u64,
+        transfers: Vec<Transfer>,
+        filter: &FilterConfig,
+    ) -> (Vec<Transfer>, HeldTransfers) {
+        let mut held = HeldTransfers::new();
+        if filter.contract_confirmations.is_empty() {
+            return (transfers, held);
+        }
+        let head = self.chain_head.load(Ordering::Relaxed);
+        let mut ready = Vec::with_capacity(transfers.len());
+        for transfer in transfers {
+            let contract = transfer
+                .contract_address
+                .as_ref()
+                .and_then(|c| c.as_str().parse::<H160>().ok());
+            match contract.and_then(|c| filter.required_confirmations(&c).map(|n| (c, n))) {
+                Some((contract, required)) if block_number + required > head => {
+                    held.entry((block_number, contract)).or_default().push(transfer);
+                }
+                _ => ready.push(transfer),
+            }
+        }
+        (ready, held)
+    }
+
+    /// 已达到所需确认数的暂缓转账（不从缓冲区移除，提交成功后再移除）；
+    /// 热重载后合约不再配置覆盖时视为已成熟
+    fn matured_held(&self, filter: &FilterConfig) -> (Vec<(u64, H160)>, Vec<Transfer>) {
+        let head = self.chain_head.load(Ordering::Relaxed);
+        let held = self.held_transfers.lock().unwrap();
+        let mut keys = Vec::new();
+        let mut matured = Vec::new();
+        for ((block, contract), transfers) in held.iter() {
+            let required = filter.required_confirmations(contract).unwrap_or(0);
+            if block + required <= head {
+                keys.push((*block, *contract));
+                let mut transfers = transfers.clone();
+                self.mark_confirming(*block, &mut transfers);
+                matured.extend(transfers);
+            }
+        }
+        (keys, matured)
+    }
+
+    /// 拆分本区块需暂缓的转账，并取出已成熟、应随本区块提交的暂缓转账
+    fn plan_hold(&self, block_number: u64, transfers: Vec<Transfer>) -> HoldPlan {
+        let filter = self.filter_config.load();
+        let (ready, held) = self.split_immature(block_number, transfers, &filter);
+        let (matured_keys, matured) = self.matured_held(&filter);
+        HoldPlan {
+            ready,
+            held,
+            matured_keys,
+            matured,
+        }
+    }
+
+    /// 提交成功后更新暂缓缓冲区：移除已提交的成熟转账，放入本区块暂缓的转账
+    fn apply_hold(
+        &self,
+        block_number: u64,
+        matured_keys: &[(u64, H160)],
+        held: HeldTransfers,
+        matured: usize,
+    ) {
+        let held_count: usize = held.values().map(Vec::len).sum();
+        {
+            let mut buffer = self.held_transfers.lock().unwrap();
+            matured_keys.iter().for_each(|key| {
+                buffer.remove(key);
+            });
+            buffer.extend(held);
+        }
+        if held_count > 0 || matured > 0 {
+            log_info!(
+                "区块 {}：按合约确认数暂缓转账 {} 笔，提交已成熟的暂缓转账 {} 笔",
+                block_number,
+                held_count,
+                matured
+            );
+        }
+    }
+
+    /// 启动时重建暂缓缓冲区：重新解析本地最近 max(合约确认数) 个区块，
+    /// 把配置了确认数、且尚未入库的转账放回缓冲区（暂缓转账只保存在内存中，重启会丢失）
+    pub async fn restore_held_transfers(&self) -> Result<usize, AppError> {
+        let filter = self.filter_config.load();
+        let Some(max_required) = filter.contract_confirmations.values().copied().max() else {
+            return Ok(0);
+        };
+        let mut conn = self
+            .db_service
+            .pool
+            .get()
+            .await
+            .map_err(|e| AppError::Internal(e.to_string()))?;
+        let Some(last) = self
+            .block_repository
+            .get_last_block_number(&mut conn)
+            .await?
+            .map(BlockQuery::try_from)
+            .transpose()?
+        else {
+            return Ok(0);
+        };
+        let head = self.provider.get_last_block_number().await?;
+        self.chain_head.fetch_max(head.as_u64(), Ordering::Relaxed);
+
+        let tip = last.block_number.as_u64();
+        let mut restored = 0;
+        for block_number in tip.saturating_sub(max_required) + 1..=tip {
+            let Some(block) = self.provider.get_block_with_txs(block_number).await? else {
+                continue;
+            };
+            let block_domain = BlockDomain::from_ethers(&block, &self.config.block_fields)?;
+            let (transfers, _) = self
+                .event_parser
+                .parse_transfers_from_block(
+                    &block,
+                    block_domain.block_number,
+                    block_domain.timestamp,
+                    &filter,
+                )
+                .await?;
+            let (_, held) = self.split_immature(block_number, transfers, &filter);
+            if held.is_empty() {
+                continue;
+            }
+            let stored: HashSet<(String, i64)> = self
+                .transaction_repository
+                .keys_in_block(&mut conn, block_domain.block_number)
+                .await?
+                .into_iter()
+                .collect();
+            let mut buffer = self.held_transfers.lock().unwrap();
+            for (key, transfers) in held {
+                let pending: Vec<Transfer> = transfers
+                    .into_iter()
+                    .filter(|t| !stored.contains(&(t.tx_hash.as_str().to_string(), t.log_index)))
+                    .collect();
+                if !pending.is_empty() {
+                    restored += pending.len();
+                    buffer.insert(key, pending);
+                }
+            }
+        }
+        Ok(restored)
+    }
+
+    /// 是否已追上安全高度；同步循环据此按 `poll_interval_ms` 等待，而不是立即重新进入
+    pub fn is_idle(&self) -> bool {
+        self.idle_at.load(Ordering::Relaxed) != NOT_IDLE
+    }
+
+    /// 链头高度减去 delay，配置了 stop_at_block 时不超过该高度
+    fn safe_height(&self, head: U64) -> U64 {
+        let safe = head.saturating_sub(self.config.delay.into());
+        match self.config.stop_at_block {
+            Some(stop) => safe.min(U64::from(stop)),
+            None => safe,
+        }
+    }
+
+    /// 平均出块间隔（秒）：取链头与其前 `BLOCK_TIME_SAMPLE_SPAN` 个区块的时间戳求平均，
+    /// 结果缓存 `BLOCK_TIME_REFRESH` 后重新采样；采样失败时沿用旧值，从未成功过则返回 None
+    pub async fn estimated_block_time_secs(&self) -> Option<f64> {
+        let cached = *self.block_time.lock().unwrap();
+        if let Some((at, secs)) = cached {
+            if at.elapsed() < BLOCK_TIME_REFRESH {
+                return Some(secs);
+            }
+        }
+
+        match self.sample_block_time().await {
+            Ok(secs) => {
+                *self.block_time.lock().unwrap() = Some((Instant::now(), secs));
+                Some(secs)
+            }
+            Err(e) => {
+                log_warn!("估算出块时间失败: {}", e);
+                cached.map(|(_, secs)| secs)
+            }
+        }
+    }
+
+    async fn sample_block_time(&self) -> Result<f64, AppError> {
+        let head = self.provider.get_last_block_number().await?.as_u64();
+        let span = BLOCK_TIME_SAMPLE_SPAN.min(head);
+        if span == 0 {
+            return Err(AppError::Internal("链上区块不足，无法估算出块时间".into()));
+        }
+        let timestamp = |number: u64| async move {
+            self.provider
+                .get_block_with_txs(number)
+                .await?
+                .map(|b| b.timestamp.as_u64())
+                .ok_or_else(|| AppError::NotFound(format!("区块 {} 不存在", number)))
+        };
+        let newest = timestamp(head).await?;
+        let oldest = timestamp(head - span).await?;
+        Ok(newest.saturating_sub(oldest) as f64 / span as f64)
+    }
+
+    /// 将落后区块数描述为 "N 个区块（约 X 小时）"，出块时间未知时只给出区块数
+    pub async fn describe_lag(&self, lag: u64) -> String {
+        match self.estimated_block_time_secs().await {
+            Some(secs) => format!(
+                "{} 个区块（{}）",
+                lag,
+                format_duration_approx((lag as f64 * secs) as u64)
+            ),
+            None => format!("{} 个区块", lag),
         }
     }
 
+    #[instrument(skip(self))]
     pub async fn sync_blocks(&self) -> anyhow::Result<()> {
+        // 空闲状态：只查询链头，安全高度越过死区前不做节点同步检查、也不查询本地游标
+        let idle_at = self.idle_at.load(Ordering::Relaxed);
+        if idle_at != NOT_IDLE {
+            let head = self
+                .provider
+                .get_last_block_number()
+                .await
+                .context("获取链上最新区块号失败")?;
+            self.chain_head.fetch_max(head.as_u64(), Ordering::Relaxed);
+            let safe = self.safe_height(head).as_u64();
+            if safe <= idle_at.saturating_add(self.config.idle_deadband_blocks) {
+                return Ok(());
+            }
+            self.idle_at.store(NOT_IDLE, Ordering::Relaxed);
+        }
+
+        // 节点自身仍在同步时，它报告的最新高度落后于真实链头，本轮不推进安全高度
+        match self.provider.is_node_syncing().await {
+            Ok(Some(status)) => {
+                log_warn!(
+                    "RPC 节点仍在同步 (current={}, highest={}, 落后 {} 个区块)，暂不推进同步",
+                    status.current_block,
+                    status.highest_block,
+                    status.highest_block.saturating_sub(status.current_block)
+                );
+                return Ok(());
+            }
+            Ok(None) => {}
+            Err(e) => log_warn!("eth_syncing 查询失败，按已同步处理: {}", e),
+        }
+
         // 获取网络最新高度（已自动带重试）
         let current_net_block = self
             .provider
             .get_last_block_number()
             .await
             .context("获取链上最新区块号失败")?;
+        self.chain_head
+            .fetch_max(current_net_block.as_u64(), Ordering::Relaxed);
 
-        // 安全高度（延迟确认数）
-        let max_safe_block = current_net_block.saturating_sub(self.config.delay.into());
+        // 安全高度（延迟确认数），配置了 stop_at_block 时不超过该高度
+        let max_safe_block = self.safe_height(current_net_block);
 
         let mut conn = self
             .db_service
@@ -73,11 +512,24 @@ impl BlockService {
             .map(BlockQuery::try_from)
             .transpose()?;
 
+        if let Some(b) = local_block.as_ref() {
+            self.last_synced
+                .fetch_max(b.block_number.as_u64(), Ordering::Relaxed);
+        }
+
         let mut next_block = match local_block.as_ref() {
+            None if self.config.start_from_head => {
+                log_info!("本地无数据，从当前安全高度 {} 开始索引", max_safe_block);
+                max_safe_block
+            }
             None => U64::from(self.config.init_height),
             Some(b) => b.block_number + 1,
         };
 
+        if self.stop_reached() {
+            return Ok(());
+        }
+
         //如果本地高度大于等于安全高度则跳过
         if next_block > max_safe_block {
             log_info!(
@@ -85,16 +537,62 @@ impl BlockService {
                 next_block,
                 max_safe_block
             );
+            self.idle_at.store(max_safe_block.as_u64(), Ordering::Relaxed);
             return Ok(());
         }
 
-        log_info!("开始同步区块: {} → {}", next_block, max_safe_block);
+        let lag = current_net_block.as_u64().saturating_sub(next_block.as_u64());
+        log_info!(
+            "开始同步区块: {} → {}，落后链头 {}",
+            next_block,
+            max_safe_block,
+            self.describe_lag(lag).await
+        );
+
+        if self.config.pipeline_window > 1
+            && !self.fast_mode_applies(next_block.as_u64(), max_safe_block.as_u64())
+        {
+            return self
+                .sync_pipelined(next_block.as_u64(), max_safe_block.as_u64(), local_block)
+                .await;
+        }
+
+        // 本轮预算：处理的区块数或耗时达到上限后返回，由外层循环重新进入，
+        // 避免长时间回填时一次调用占住任务、延迟退出
+        let started = Instant::now();
+        let mut processed = 0u64;
+        // 快速模式当前区间的 (结束高度, 按区块分组的日志)；eth_getLogs 失败后本轮不再尝试
+        let mut fast_range: Option<(u64, BTreeMap<u64, Vec<Log>>)> = None;
+        let mut fast_disabled = false;
 
         //本地高度小于或等于安全目标时，继续同步
         //主同步循环
         while next_block <= max_safe_block {
+            if self.budget_exhausted(processed, started) {
+                log_info!(
+                    "本轮同步预算用尽（{} 个区块，耗时 {:?}），下一轮从 {} 继续",
+                    processed,
+                    started.elapsed(),
+                    next_block
+                );
+                return Ok(());
+            }
             let block_number = next_block.as_u64();
 
+            if fast_range.as_ref().is_some_and(|(end, _)| block_number > *end) {
+                fast_range = None;
+            }
+            if fast_range.is_none() && !fast_disabled {
+                fast_range = self
+                    .fetch_fast_range(block_number, max_safe_block.as_u64())
+                    .await
+                    .unwrap_or_else(|e| {
+                        log_warn!("快速模式 eth_getLogs 失败，本轮回退为完整解析: {:?}", e);
+                        fast_disabled = true;
+                        None
+                    });
+            }
+
             // 如果最终仍失败，会直接返回 AppError，被外层捕获
             let block_data = match self.provider.get_block_with_txs(block_number).await {
                 Ok(Some(block)) => block, // 成功获取区块
@@ -123,6 +621,8 @@ impl BlockService {
                         prev.block_hash,
                         block_data.parent_hash
                     );
+                    // 同步时只能确定至少 1 个区块被重组，实际深度由启动校验回滚时得出
+                    self.reorg_depth.fetch_max(1, Ordering::Relaxed);
 
                     //这里先用延迟解析的方式来简单解决分叉的问题--后续加回滚块、交易来处理
                     return Err(anyhow::anyhow!(
@@ -131,25 +631,770 @@ impl BlockService {
                     ));
                 }
             }
-            self.process_and_save_block(U64::from(block_number), block_data.clone())
-                .await
-                .with_context(|| format!("处理区块 {} 失败", block_number))?;
+            // 时间戳校验：明显异常时中断本轮同步，不提交错误数据
+            let parent_timestamp = local_block.as_ref().map(|b| b.timestamp);
+            let block_domain = BlockDomain::from_ethers(&block_data, &self.config.block_fields)?;
+            if let Err(e) =
+                block_domain.validate_timestamp(parent_timestamp, &self.config.timestamp_check)
+            {
+                log_error!("区块 {} 时间戳校验失败: {}", block_number, e);
+                return Err(e.into());
+            }
+
+            let sampled = self
+                .config
+                .sampling
+                .is_sampled(block_number, block_data.transactions.len());
+            let saved = match fast_range.as_mut() {
+                // 未被采样：只写入区块，保持父哈希链连续，不解析交易
+                _ if !sampled => {
+                    let skipped = block_data.transactions.len();
+                    self.save_block(
+                        U64::from(block_number),
+                        block_domain.clone(),
+                        vec![],
+                        vec![],
+                        skipped,
+                        "采样跳过",
+                    )
+                    .await
+                }
+                Some((_, logs)) => {
+                    let logs = logs.remove(&block_number).unwrap_or_default();
+                    self.save_block_from_logs(U64::from(block_number), &block_data, logs)
+                        .instrument(info_span!("sync_block_fast", block = block_number))
+                        .await
+                }
+                None => {
+                    self.process_and_save_block(U64::from(block_number), block_data.clone())
+                        .instrument(info_span!("sync_block", block = block_number))
+                        .await
+                }
+            };
+            saved?;
 
             let block_hash = block_data
                 .hash
                 .ok_or_else(|| anyhow::anyhow!("block {} missing hash", block_number))?;
 
             //推进本地状态
+            self.last_synced.store(block_number, Ordering::Relaxed);
             local_block = Some(BlockQuery {
                 block_number: next_block,
                 block_hash,
+                timestamp: block_domain.timestamp,
             });
             next_block += U64::from(1);
+            processed += 1;
         }
         log_info!("区块同步完成，当前安全高度 {}", max_safe_block);
+        self.idle_at.store(max_safe_block.as_u64(), Ordering::Relaxed);
         Ok(())
     }
 
+    /// 从 `from` 起落后安全高度是否超过 `fast_mode_threshold`（0 表示关闭快速模式）
+    fn fast_mode_applies(&self, from: u64, safe: u64) -> bool {
+        let threshold = self.config.fast_mode_threshold;
+        threshold > 0 && safe.saturating_sub(from) > threshold
+    }
+
+    /// 流水线同步：以 `pipeline_window` 为窗口并发拉取区块与收据，完成的区块严格按高度顺序校验父哈希后入库
+    ///
+    /// 单个区块失败时在自己的任务内重试，不阻塞窗口内其他区块的拉取；
+    /// 重试耗尽、父哈希不一致或预算用尽时结束本轮，未提交的预取结果直接丢弃，下一轮从本地最新高度继续
+    async fn sync_pipelined(
+        &self,
+        from: u64,
+        safe: u64,
+        mut local_block: Option<BlockQuery>,
+    ) -> anyhow::Result<()> {
+        let window = self.config.pipeline_window;
+        log_info!("流水线同步 {} → {}，窗口 {}", from, safe, window);
+        let started = Instant::now();
+        let mut processed = 0u64;
+        let prepared_blocks =
+            prepare_in_order(from, safe, window, |n| self.prepare_block_with_retry(n));
+        let mut prepared_blocks = std::pin::pin!(prepared_blocks);
+
+        while let Some((block_number, prepared)) = prepared_blocks.next().await {
+            if self.budget_exhausted(processed, started) {
+                log_info!(
+                    "本轮同步预算用尽（{} 个区块，耗时 {:?}），下一轮从 {} 继续",
+                    processed,
+                    started.elapsed(),
+                    from + processed
+                );
+                return Ok(());
+            }
+            let prepared = prepared?;
+            let block = prepared.block;
+
+            if let Some(prev) = local_block.as_ref() {
+                if h256_to_string(prev.block_hash) != block.parent_hash {
+                    log_warn!(
+                        "链分叉检测到！区块 {} 本地父哈希 {:?} ≠ 链上父哈希 {}",
+                        block_number,
+                        prev.block_hash,
+                        block.parent_hash
+                    );
+                    self.reorg_depth.fetch_max(1, Ordering::Relaxed);
+                    return Err(anyhow::anyhow!(
+                        "Chain re-org detected at block {}",
+                        block_number
+                    ));
+                }
+            }
+            if let Err(e) = block.validate_timestamp(
+                local_block.as_ref().map(|b| b.timestamp),
+                &self.config.timestamp_check,
+            ) {
+                log_error!("区块 {} 时间戳校验失败: {}", block_number, e);
+                return Err(e.into());
+            }
+
+            let block_hash: H256 = block
+                .block_hash
+                .parse()
+                .with_context(|| format!("区块 {} 哈希无效: {}", block_number, block.block_hash))?;
+            let timestamp = block.timestamp;
+            let transfers = Arc::unwrap_or_clone(prepared.transfers);
+            let deployments = Arc::unwrap_or_clone(prepared.deployments);
+            self.save_block(
+                U64::from(block_number),
+                block,
+                transfers,
+                deployments,
+                prepared.skipped,
+                "流水线",
+            )
+            .await?;
+
+            self.last_synced.store(block_number, Ordering::Relaxed);
+            local_block = Some(BlockQuery {
+                block_number: U64::from(block_number),
+                block_hash,
+                timestamp,
+            });
+            processed += 1;
+        }
+        log_info!("区块同步完成，当前安全高度 {}", safe);
+        self.idle_at.store(safe, Ordering::Relaxed);
+        Ok(())
+    }
+
+    /// 拉取并解析区块，失败时等待后重试，最多 `PIPELINE_PREPARE_ATTEMPTS` 次
+    async fn prepare_block_with_retry(&self, block_number: u64) -> Result<PreparedBlock, AppError> {
+        let mut attempt = 1;
+        loop {
+            match self.prepare_block(block_number).await {
+                Ok(prepared) => return Ok(prepared),
+                Err(e) if attempt < PIPELINE_PREPARE_ATTEMPTS => {
+                    log_warn!(
+                        "流水线准备区块 {} 失败（第 {} 次），稍后重试: {}",
+                        block_number,
+                        attempt,
+                        e
+                    );
+                    tokio::time::sleep(Duration::from_secs(3)).await;
+                    attempt += 1;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    /// 落后安全高度超过 `fast_mode_threshold` 时，拉取下一段快速模式区间的日志
+    ///
+    /// 区间为 [from, min(from + fast_mode_range - 1, safe - threshold)]，保证快速模式只用于
+    /// 远离链头的历史区块，接近链头的部分仍走完整解析；不满足条件时返回 None
+    async fn fetch_fast_range(
+        &self,
+        from: u64,
+        safe: u64,
+    ) -> Result<Option<(u64, BTreeMap<u64, Vec<Log>>)>, AppError> {
+        if !self.fast_mode_applies(from, safe) {
+            return Ok(None);
+        }
+        let threshold = self.config.fast_mode_threshold;
+        let end = (from + self.config.fast_mode_range.max(1) - 1).min(safe - threshold);
+        let current_filter = self.filter_config.load();
+        let logs = self
+            .event_parser
+            .fetch_transfer_logs(from, end, &current_filter)
+            .await?;
+        log_info!(
+            "落后安全高度 {} 个区块，快速模式解析 {} → {}（日志 {} 条）",
+            safe - from,
+            from,
+            end,
+            logs.len()
+        );
+        let mut by_block: BTreeMap<u64, Vec<Log>> = BTreeMap::new();
+        for log in logs {
+            if let Some(number) = log.block_number {
+                by_block.entry(number.as_u64()).or_default().push(log);
+            }
+        }
+        Ok(Some((end, by_block)))
+    }
+
+    /// 工厂部署者部署的新合约加入监控合约列表（部署记录入库后调用）
+    fn monitor_deployed(&self, deployments: &[ContractDeployment]) {
+        let deployers = self.config.deployment_tracking.auto_monitor_sources();
+        let contracts: Vec<_> = deployments
+            .iter()
+            .filter(|d| {
+                d.deployer
+                    .as_str()
+                    .parse()
+                    .is_ok_and(|deployer| deployers.contains(&deployer))
+            })
+            .filter_map(|d| d.contract_address.as_str().parse().ok())
+            .collect();
+        for d in deployments {
+            log_info!(
+                "记录合约部署: {} 由 {} 部署 (tx={})",
+                d.contract_address,
+                d.deployer,
+                d.tx_hash
+            );
+        }
+        let added = self.filter_config.add_contracts(contracts);
+        if added > 0 {
+            log_info!("自动加入监控合约 {} 个（工厂部署）", added);
+        }
+    }
+
+    /// 启动时恢复工厂部署者部署过的合约、工厂创建的子合约到监控列表（运行时加入的合约不写回配置文件）
+    pub async fn restore_deployed_contracts(&self) -> Result<usize, AppError> {
+        let deployers: Vec<_> = self
+            .config
+            .deployment_tracking
+            .auto_monitor_sources()
+            .into_iter()
+            .collect();
+        if deployers.is_empty() {
+            return Ok(0);
+        }
+        let mut conn = self
+            .db_service
+            .pool
+            .get()
+            .await
+            .map_err(|e| AppError::Internal(e.to_string()))?;
+        let contracts = self
+            .deployment_repository
+            .contracts_deployed_by(&mut conn, &deployers)
+            .await?;
+        Ok(self.filter_config.add_contracts(contracts))
+    }
+
+    /// 开启两阶段状态时，重组窗口内区块的已确认转账改为确认中，等待后台任务提升
+    /// 链头未知时一律视为在窗口内
+    fn mark_confirming(&self, block_number: u64, transfers: &mut [Transfer]) {
+        let status_config = &self.config.transfer_status;
+        if !status_config.enabled {
+            return;
+        }
+        let head = self.chain_head.load(Ordering::Relaxed);
+        if block_number + status_config.finality_depth <= head {
+            return;
+        }
+        transfers
+            .iter_mut()
+            .filter(|t| t.status == STATUS_CONFIRMED)
+            .for_each(|t| t.status = STATUS_CONFIRMING);
+    }
+
+    /// 本轮 sync_blocks 是否已用尽区块数或时间预算
+    fn budget_exhausted(&self, processed: u64, started: Instant) -> bool {
+        let blocks = self.config.sync_budget_blocks;
+        let secs = self.config.sync_budget_secs;
+        (blocks > 0 && processed >= blocks)
+            || (secs > 0 && started.elapsed() >= Duration::from_secs(secs))
+    }
+
+    /// 是否已同步到配置的 stop_at_block（未配置时永远为 false）
+    pub fn stop_reached(&self) -> bool {
+        self.config
+            .stop_at_block
+            .is_some_and(|stop| self.last_synced.load(Ordering::Relaxed) >= stop)
+    }
+
+    /// 回查本地最近 `delay` 个区块的哈希，回滚到最后一个与链上一致的区块
+    ///
+    /// 进程在重组期间崩溃时，本地最新的几个区块可能已成为孤块；从高到低逐个比对，
+    /// 找到第一个哈希一致的区块后，在同一事务中删除其之上的区块与转账。
+    /// 窗口内全部不一致时回滚整个窗口，更深的分叉由同步时的父哈希校验继续发现。
+    /// 返回回滚后的本地最新高度（本地无数据时为 None）
+    pub async fn rewind_to_last_valid(&self) -> Result<Option<u64>, AppError> {
+        let window = i64::from(self.config.delay.max(1));
+        let recent = {
+            let mut conn = self
+                .db_service
+                .pool
+                .get()
+                .await
+                .map_err(|e| AppError::Internal(e.to_string()))?;
+            self.block_repository.get_recent_blocks(&mut conn, window).await?
+        };
+        let Some(tip) = recent.first().map(|b| b.block_number) else {
+            return Ok(None);
+        };
+
+        let mut last_valid = None;
+        for stored in &recent {
+            let chain_hash = self
+                .provider
+                .get_block_with_txs(stored.block_number as u64)
+                .await?
+                .and_then(|b| b.hash)
+                .map(|h| format!("{:#x}", h));
+            if chain_hash.as_deref() == Some(stored.block_hash.as_str()) {
+                last_valid = Some(stored.block_number);
+                break;
+            }
+            log_warn!(
+                "启动校验：区块 {} 本地哈希 {} 与链上 {:?} 不一致",
+                stored.block_number,
+                stored.block_hash,
+                chain_hash
+            );
+        }
+
+        let rewind_to = match last_valid {
+            Some(height) => height,
+            None => {
+                let oldest = recent.last().map_or(tip, |b| b.block_number);
+                log_error!(
+                    "启动校验：最近 {} 个区块全部不一致，回滚到 {} 之前",
+                    recent.len(),
+                    oldest
+                );
+                oldest - 1
+            }
+        };
+        if rewind_to == tip {
+            log_info!("启动校验通过，本地最新区块 {} 与链上一致", tip);
+            return Ok(Some(tip as u64));
+        }
+
+        let repos = self.rewind_repositories();
+        let (blocks, transfers) = self
+            .db_service
+            .execute_tx(move |conn| Box::pin(async move { repos.delete_above(conn, rewind_to).await }))
+            .await?;
+        self.reorg_depth
+            .fetch_max((tip - rewind_to) as u64, Ordering::Relaxed);
+        self.reset_after_rewind(rewind_to);
+        log_warn!(
+            "启动校验：回滚到区块 {}，删除区块 {} 个、转账 {} 笔",
+            rewind_to,
+            blocks,
+            transfers
+        );
+        Ok((rewind_to >= 0).then_some(rewind_to as u64))
+    }
+
+    /// 启动时比较解析器版本：编译进来的 PARSER_VERSION 高于已入库数据的版本时，
+    /// 开启 `reprocess_on_version_bump` 则删除最近 `reprocess_window` 个区块（0 为全部）交由同步循环重新处理，
+    /// 未开启只记录告警、不更新版本，之后开启仍会触发。本地无数据时直接记录当前版本。
+    /// 队列模式下删除区块会与队列状态冲突，只告警不处理；返回删除后的本地最新高度
+    pub async fn reprocess_on_version_bump(&self) -> Result<Option<u64>, AppError> {
+        let mut conn = self
+            .db_service
+            .pool
+            .get()
+            .await
+            .map_err(|e| AppError::Internal(e.to_string()))?;
+        let stored = self.sync_state_repository.get_parser_version(&mut conn).await?;
+        let Some(tip) = self
+            .block_repository
+            .get_last_block_number(&mut conn)
+            .await?
+            .map(|b| b.block_number)
+        else {
+            self.sync_state_repository
+                .set_parser_version(&mut conn, PARSER_VERSION)
+                .await?;
+            return Ok(None);
+        };
+        // 引入版本记录之前入库的数据视为版本 0
+        let stored = stored.unwrap_or(0);
+        if stored >= PARSER_VERSION {
+            return Ok(Some(tip as u64));
+        }
+        if !self.config.reprocess_on_version_bump || self.config.block_queue.enabled {
+            log_warn!(
+                "已入库数据由解析器版本 {} 生成，当前版本 {}；未重新处理（需开启 reprocess_on_version_bump，且不支持队列模式）",
+                stored,
+                PARSER_VERSION
+            );
+            return Ok(Some(tip as u64));
+        }
+        // start_from_head 时本地删空会从链头重新开始，因此保留最早的一个区块，保证删除的区间会被重新处理
+        let min_keep = if self.config.start_from_head {
+            self.block_repository
+                .get_first_block_number(&mut conn)
+                .await?
+                .unwrap_or(tip)
+        } else {
+            self.config.init_height as i64 - 1
+        };
+        drop(conn);
+
+        let keep = match self.config.reprocess_window {
+            0 => min_keep,
+            window => (tip - window as i64).max(min_keep),
+        };
+        let repos = self.rewind_repositories();
+        let state_repo = Arc::clone(&self.sync_state_repository);
+        let (blocks, transfers) = self
+            .db_service
+            .execute_tx(move |conn| {
+                Box::pin(async move {
+                    let (blocks, transfers) = repos.delete_above(conn, keep).await?;
+                    state_repo.set_parser_version(conn, PARSER_VERSION).await?;
+                    Ok((blocks, transfers))
+                })
+            })
+            .await?;
+        self.reset_after_rewind(keep);
+        log_warn!(
+            "解析器版本 {} → {}：删除区块 {} 个、转账 {} 笔，从区块 {} 起重新处理",
+            stored,
+            PARSER_VERSION,
+            blocks,
+            transfers,
+            keep + 1
+        );
+        Ok((keep >= 0).then_some(keep as u64))
+    }
+
+    /// 按哈希获取区块，节点不认识该哈希时返回 NotFound
+    pub async fn fetch_block_by_hash(&self, hash: H256) -> Result<Block<Transaction>, AppError> {
+        self.provider
+            .get_block_by_hash(hash)
+            .await?
+            .ok_or_else(|| AppError::NotFound(format!("节点上不存在区块 {:#x}", hash)))
+    }
+
+    /// 从分叉链上的区块 `tip` 开始沿 parent_hash 回溯，找到与本地已入库区块哈希一致的共同祖先
+    ///
+    /// 最多回溯 `max_depth` 个区块；返回共同祖先的高度，超过深度或本地在该高度没有数据时返回 None
+    pub async fn find_fork_point(&self, tip: H256, max_depth: u64) -> Result<Option<u64>, AppError> {
+        let mut conn = self
+            .db_service
+            .pool
+            .get()
+            .await
+            .map_err(|e| AppError::Internal(e.to_string()))?;
+        let mut hash = tip;
+        for _ in 0..=max_depth {
+            let block = self.fetch_block_by_hash(hash).await?;
+            let height = option_u64_to_i64(block.number)?;
+            let Some(stored) = self.block_repository.get_block(&mut conn, height).await? else {
+                return Ok(None);
+            };
+            if stored.block_hash == format!("{:#x}", hash) {
+                return Ok(Some(height as u64));
+            }
+            if height == 0 {
+                break;
+            }
+            hash = block.parent_hash;
+        }
+        Ok(None)
+    }
+
+    /// 按哈希拉取并入库单个区块（外部系统引用的区块）
+    ///
+    /// 只接受当前规范链上的区块：同高度的规范区块哈希不一致时返回 `AppError::ChainReorg`，不写入孤块。
+    /// 已入库的同高度区块保持不变（与同步路径相同的冲突忽略策略）
+    #[instrument(skip(self))]
+    pub async fn index_block_by_hash(&self, hash: H256) -> Result<BlockDomain, AppError> {
+        let block = self.fetch_block_by_hash(hash).await?;
+        let number = block
+            .number
+            .ok_or_else(|| AppError::NotFound(format!("区块 {:#x} 尚未打包", hash)))?;
+        let canonical = self
+            .provider
+            .get_block_with_txs(number.as_u64())
+            .await?
+            .and_then(|b| b.hash);
+        if canonical != Some(hash) {
+            return Err(AppError::ChainReorg {
+                block: number.as_u64(),
+                local: format!("{:#x}", hash),
+                network: canonical.map_or_else(String::new, |h| format!("{:#x}", h)),
+            });
+        }
+
+        let block_domain = BlockDomain::from_ethers(&block, &self.config.block_fields)?;
+        self.process_and_save_block(number, block).await?;
+        Ok(block_domain)
+    }
+
+    /// 队列模式：拉取并解析区块，不写库；多个 worker 可以并行执行
+    #[instrument(skip(self))]
+    pub async fn prepare_block(&self, block_number: u64) -> Result<PreparedBlock, AppError> {
+        let block = self
+            .provider
+            .get_block_with_txs(block_number)
+            .await
+            .and_then(|block| {
+                block.ok_or_else(|| AppError::NotFound(format!("区块 {} 暂未同步到节点", block_number)))
+            })
+            .map_err(SyncError::fetch(block_number))?;
+        let block_domain = BlockDomain::from_ethers(&block, &self.config.block_fields)
+            .map_err(SyncError::parse(block_number))?;
+        if !self
+            .config
+            .sampling
+            .is_sampled(block_number, block.transactions.len())
+        {
+            return Ok(PreparedBlock {
+                block: block_domain,
+                transfers: Arc::new(vec![]),
+                deployments: Arc::new(vec![]),
+                skipped: block.transactions.len(),
+            });
+        }
+        let current_filter = self.filter_config.load();
+        let (mut transfers, skipped) = self
+            .event_parser
+            .parse_transfers_from_block(
+                &block,
+                block_domain.block_number,
+                block_domain.timestamp,
+                &current_filter,
+            )
+            .await
+            .map_err(SyncError::parse(block_number))?;
+        self.mark_confirming(block_number, &mut transfers);
+        let deployments = self
+            .event_parser
+            .parse_deployments(
+                &block,
+                block_domain.block_number,
+                block_domain.timestamp,
+                &current_filter,
+            )
+            .await
+            .map_err(SyncError::parse(block_number))?;
+        Ok(PreparedBlock {
+            block: block_domain,
+            transfers: Arc::new(transfers),
+            deployments: Arc::new(deployments),
+            skipped,
+        })
+    }
+
+    /// 从 `from` 开始按序拉取并解析区块，以流的形式产出，不写数据库（供嵌入本库的调用方自建管道）
+    ///
+    /// 与 `sync_blocks` 一致：只产出安全高度（链头 - delay）以内的区块，配置了 stop_at_block 时到达后结束；
+    /// 父哈希不一致时产出 `AppError::ChainReorg` 并回退一个区块重新产出，调用方应丢弃该高度之前一个区块起的已接收数据。
+    /// 单个区块获取或解析失败时产出错误并在下一次轮询重试同一高度，流不会因此结束
+    pub fn block_stream(
+        &self,
+        from: u64,
+    ) -> impl Stream<Item = Result<ParsedBlock, AppError>> + Send + '_ {
+        let state = StreamState {
+            next: from,
+            safe: 0,
+            parent: None,
+            failed: false,
+        };
+        stream::unfold(state, move |mut state| async move {
+            if std::mem::take(&mut state.failed) {
+                tokio::time::sleep(Duration::from_secs(1)).await;
+            }
+            loop {
+                if self.config.stop_at_block.is_some_and(|stop| state.next > stop) {
+                    return None;
+                }
+                if state.next > state.safe {
+                    match self.provider.get_last_block_number().await {
+                        Ok(head) => {
+                            let mut safe = head.as_u64().saturating_sub(self.config.delay.max(0) as u64);
+                            if let Some(stop) = self.config.stop_at_block {
+                                safe = safe.min(stop);
+                            }
+                            state.safe = safe;
+                        }
+                        Err(e) => return Some(state.fail(e)),
+                    }
+                    if state.next > state.safe {
+                        tokio::time::sleep(Duration::from_secs(1)).await;
+                        continue;
+                    }
+                }
+
+                let block_number = state.next;
+                let block = match self.provider.get_block_with_txs(block_number).await {
+                    Ok(Some(block)) => block,
+                    Ok(None) => {
+                        tokio::time::sleep(Duration::from_secs(1)).await;
+                        continue;
+                    }
+                    Err(e) => return Some(state.fail(e)),
+                };
+                let block_domain = match BlockDomain::from_ethers(&block, &self.config.block_fields) {
+                    Ok(b) => b,
+                    Err(e) => return Some(state.fail(e)),
+                };
+
+                if let Some((parent_hash, _)) = &state.parent {
+                    if block_domain.parent_hash != *parent_hash {
+                        let err = AppError::ChainReorg {
+                            block: block_number,
+                            local: parent_hash.clone(),
+                            network: block_domain.parent_hash.clone(),
+                        };
+                        self.reorg_depth.fetch_max(1, Ordering::Relaxed);
+                        state.next = block_number.saturating_sub(1).max(from);
+                        state.parent = None;
+                        return Some((Err(err), state));
+                    }
+                }
+                let parent_timestamp = state.parent.as_ref().map(|(_, ts)| *ts);
+                if let Err(e) =
+                    block_domain.validate_timestamp(parent_timestamp, &self.config.timestamp_check)
+                {
+                    return Some(state.fail(e));
+                }
+
+                let current_filter = self.filter_config.load();
+                let parsed = self
+                    .event_parser
+                    .parse_transfers_from_block(
+                        &block,
+                        block_domain.block_number,
+                        block_domain.timestamp,
+                        &current_filter,
+                    )
+                    .await;
+                let transfers = match parsed {
+                    Ok((transfers, _)) => transfers,
+                    Err(e) => return Some(state.fail(e)),
+                };
+
+                state.parent = Some((block_domain.block_hash.clone(), block_domain.timestamp));
+                state.next += 1;
+                return Some((
+                    Ok(ParsedBlock {
+                        block: block_domain,
+                        transfers,
+                    }),
+                    state,
+                ));
+            }
+        })
+    }
+
+    /// 队列模式：按严格升序提交已解析的区块，并在同一事务中确认队列项
+    ///
+    /// 只有本地最新区块恰好是 n-1 且哈希等于本块父哈希时才写入，
+    /// 保证并行解析下父哈希校验与时间戳单调校验仍然成立
+    pub async fn commit_in_order(
+        &self,
+        prepared: &PreparedBlock,
+        queue_repository: Arc<BlockQueueRepository>,
+    ) -> Result<CommitOutcome, AppError> {
+        let block_repo = Arc::clone(&self.block_repository);
+        let tx_repo = Arc::clone(&self.transaction_repository);
+        let stats_repo = Arc::clone(&self.address_stats_repository);
+        let deployment_repo = Arc::clone(&self.deployment_repository);
+        let chain_id = self.config.chain_id as i64;
+        let block = prepared.block.clone();
+        // 与 save_block 相同的暂缓逻辑；只有提交成功才更新缓冲区，Waiting 时下次重新计算
+        let plan = self.plan_hold(
+            prepared.block.block_number as u64,
+            prepared.transfers.as_ref().clone(),
+        );
+        let transfers = Arc::new(plan.ready);
+        let transfers_for_tx = Arc::clone(&transfers);
+        let matured = Arc::new(plan.matured);
+        let matured_for_tx = Arc::clone(&matured);
+        let deployments = Arc::clone(&prepared.deployments);
+        let init_height = self.config.init_height as i64;
+        let start_from_head = self.config.start_from_head;
+        let timestamp_check = self.config.timestamp_check.clone();
+        let reorg_depth = Arc::clone(&self.reorg_depth);
+
+        let outcome = self
+            .db_service
+            .execute_tx(move |conn| {
+                Box::pin(async move {
+                    let height = block.block_number;
+                    let last = block_repo.get_last_block_number(conn).await?;
+                    // 本地无数据时的首个区块：init_height，或 start_from_head 时队列中最小的高度
+                    let first_height = match (&last, start_from_head) {
+                        (None, true) => queue_repository
+                            .min_block_number(conn)
+                            .await?
+                            .unwrap_or(height),
+                        _ => init_height,
+                    };
+                    let parent_timestamp = match &last {
+                        Some(l) if l.block_number >= height => {
+                            queue_repository.ack(conn, height).await?;
+                            return Ok(CommitOutcome::AlreadyCommitted);
+                        }
+                        Some(l) if l.block_number < height - 1 => {
+                            return Ok(CommitOutcome::Waiting);
+                        }
+                        None if height > first_height => return Ok(CommitOutcome::Waiting),
+                        Some(l) => {
+                            if l.block_hash != block.parent_hash {
+                                reorg_depth.fetch_max(1, Ordering::Relaxed);
+                                return Err(AppError::Conflict(format!(
+                                    "Chain re-org detected at block {}: 本地父哈希 {} ≠ 链上父哈希 {}",
+                                    height, l.block_hash, block.parent_hash
+                                )));
+                            }
+                            Some(l.timestamp)
+                        }
+                        None => None,
+                    };
+                    block.validate_timestamp(parent_timestamp, &timestamp_check)?;
+
+                    let inserted = block_repo.insert(conn, &block).await?;
+                    if !transfers_for_tx.is_empty() {
+                        tx_repo.batch_save(conn, &transfers_for_tx).await?;
+                        if inserted {
+                            stats_repo
+                                .upsert_from_transfers(conn, chain_id, &transfers_for_tx)
+                                .await?;
+                        }
+                    }
+                    if !deployments.is_empty() {
+                        deployment_repo.batch_save(conn, &deployments).await?;
+                    }
+                    save_matured(conn, &tx_repo, &stats_repo, chain_id, &matured_for_tx).await?;
+                    queue_repository.ack(conn, height).await?;
+                    Ok(CommitOutcome::Committed)
+                })
+            })
+            .instrument(info_span!("db_commit", block = prepared.block.block_number))
+            .await?;
+
+        if outcome == CommitOutcome::Committed {
+            self.monitor_deployed(&prepared.deployments);
+            let block_number = prepared.block.block_number as u64;
+            self.apply_hold(block_number, &plan.matured_keys, plan.held, matured.len());
+            self.last_synced.fetch_max(block_number, Ordering::Relaxed);
+            log_info!(
+                "区块 {} 入库成功，转账 {} 笔，跳过 {} 笔（队列模式）",
+                prepared.block.block_number,
+                transfers.len(),
+                prepared.skipped
+            );
+        }
+        Ok(outcome)
+    }
+
+    #[instrument(skip_all, fields(block = %block_height, transfers))]
     async fn process_and_save_block(
         &self,
         block_height: U64,
@@ -157,7 +1402,9 @@ impl BlockService {
     ) -> Result<(), AppError> {
         log_info!("当前解析入库区块:{}", block_height);
         let current_filter = self.filter_config.load();
-        let block_domain = BlockDomain::from_ethers(&block)?;
+        let number = block_height.as_u64();
+        let block_domain = BlockDomain::from_ethers(&block, &self.config.block_fields)
+            .map_err(SyncError::parse(number))?;
         let (tx, skipped_count) = self
             .event_parser
             .parse_transfers_from_block(
@@ -166,32 +1413,185 @@ impl BlockService {
                 block_domain.timestamp,
                 &current_filter,
             )
-            .await?;
+            .await
+            .map_err(SyncError::parse(number))?;
+
+        let deployments = self
+            .event_parser
+            .parse_deployments(
+                &block,
+                block_domain.block_number,
+                block_domain.timestamp,
+                &current_filter,
+            )
+            .await
+            .map_err(SyncError::parse(number))?;
+        self.save_block(block_height, block_domain, tx, deployments, skipped_count, "事务提交")
+            .await
+    }
+
+    /// 快速模式：用 eth_getLogs 取回的日志解析并入库区块，不获取收据
+    ///
+    /// 日志的区块哈希与区块不一致说明取日志后发生了重组，返回 ChainReorg，不写入不完整的数据
+    #[instrument(skip_all, fields(block = %block_height, transfers))]
+    async fn save_block_from_logs(
+        &self,
+        block_height: U64,
+        block: &Block<Transaction>,
+        logs: Vec<Log>,
+    ) -> Result<(), AppError> {
+        if let Some(log) = logs.iter().find(|log| log.block_hash != block.hash) {
+            return Err(AppError::ChainReorg {
+                block: block_height.as_u64(),
+                local: log.block_hash.map_or_else(String::new, |h| format!("{:#x}", h)),
+                network: block.hash.map_or_else(String::new, |h| format!("{:#x}", h)),
+            });
+        }
+        let current_filter = self.filter_config.load();
+        let block_domain = BlockDomain::from_ethers(block, &self.config.block_fields)
+            .map_err(SyncError::parse(block_height.as_u64()))?;
+        let (tx, skipped_count) = self.event_parser.parse_transfers_from_logs(
+            block,
+            block_domain.block_number,
+            block_domain.timestamp,
+            logs,
+            &current_filter,
+        );
+        // 工厂子合约照常记录；其在当前快速区间剩余区块中的转账不在已拉取的日志里，从下一个区间开始索引
+        let children = self
+            .event_parser
+            .parse_factory_children(block, block_domain.block_number, block_domain.timestamp)
+            .await
+            .map_err(SyncError::parse(block_height.as_u64()))?;
+        self.save_block(block_height, block_domain, tx, children, skipped_count, "快速模式")
+            .await
+    }
 
-        let transfers = Arc::new(tx);
+    /// 在同一事务中写入区块、转账与合约部署记录
+    async fn save_block(
+        &self,
+        block_height: U64,
+        block_domain: BlockDomain,
+        mut transfers: Vec<Transfer>,
+        deployments: Vec<ContractDeployment>,
+        skipped_count: usize,
+        mode: &str,
+    ) -> Result<(), AppError> {
+        self.mark_confirming(block_height.as_u64(), &mut transfers);
+        // 合约确认数未达到的转账暂缓，已成熟的暂缓转账随本区块一起提交
+        let plan = self.plan_hold(block_height.as_u64(), transfers);
+        let matured = Arc::new(plan.matured);
+        let matured_for_tx = Arc::clone(&matured);
+        let transfers = Arc::new(plan.ready);
+        Span::current().record("transfers", transfers.len());
         let transfers_for_tx = Arc::clone(&transfers);
 
         let block_repo = Arc::clone(&self.block_repository);
         let tx_repo = Arc::clone(&self.transaction_repository);
+        let stats_repo = Arc::clone(&self.address_stats_repository);
+        let deployment_repo = Arc::clone(&self.deployment_repository);
+        let chain_id = self.config.chain_id as i64;
+        let deployments = Arc::new(deployments);
+        let deployments_for_tx = Arc::clone(&deployments);
+        let committing = transfers.len() + matured.len();
+
+        let block_domain = Arc::new(block_domain);
 
+        // 暂时性数据库错误时整个事务会重新执行，闭包每次执行都克隆一份共享数据
         self.db_service
-            .execute_tx(move |conn| {
+            .execute_tx_retry(move |conn| {
+                let block_repo = Arc::clone(&block_repo);
+                let tx_repo = Arc::clone(&tx_repo);
+                let stats_repo = Arc::clone(&stats_repo);
+                let deployment_repo = Arc::clone(&deployment_repo);
+                let block_domain = Arc::clone(&block_domain);
+                let transfers_for_tx = Arc::clone(&transfers_for_tx);
+                let deployments_for_tx = Arc::clone(&deployments_for_tx);
+                let matured_for_tx = Arc::clone(&matured_for_tx);
                 Box::pin(async move {
-                    block_repo.save(conn, &block_domain).await?;
+                    // 区块已入库（如按哈希重复索引）时转账写入被忽略，统计也不再累加
+                    let inserted = block_repo.insert(conn, &block_domain).await?;
                     if !transfers_for_tx.is_empty() {
                         tx_repo.batch_save(conn, &transfers_for_tx).await?;
+                        if inserted {
+                            stats_repo
+                                .upsert_from_transfers(conn, chain_id, &transfers_for_tx)
+                                .await?;
+                        }
                     }
-                    Ok(())
+                    if !deployments_for_tx.is_empty() {
+                        deployment_repo.batch_save(conn, &deployments_for_tx).await?;
+                    }
+                    save_matured(conn, &tx_repo, &stats_repo, chain_id, &matured_for_tx).await
                 })
             })
-            .await?;
+            .instrument(info_span!("db_commit"))
+            .await
+            .map_err(SyncError::commit(block_height.as_u64(), committing))?;
+        self.monitor_deployed(&deployments);
+        self.apply_hold(block_height.as_u64(), &plan.matured_keys, plan.held, matured.len());
 
         log_info!(
-            "区块 {} 入库成功，转账 {} 笔，跳过 {} 笔（事务提交）",
+            "区块 {} 入库成功，转账 {} 笔，跳过 {} 笔（{}）",
             block_height,
             transfers.len(),
-            skipped_count
+            skipped_count,
+            mode
         );
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::AtomicUsize;
+
+    #[tokio::test]
+    async fn pipelined_blocks_are_yielded_in_height_order() {
+        // 高度越大完成越快，完成顺序与高度相反
+        let completed = Arc::new(Mutex::new(Vec::new()));
+        let in_flight = Arc::new(AtomicUsize::new(0));
+        let max_in_flight = Arc::new(AtomicUsize::new(0));
+        let prepared = prepare_in_order(10, 17, 4, |n| {
+            let completed = completed.clone();
+            let in_flight = in_flight.clone();
+            let max_in_flight = max_in_flight.clone();
+            async move {
+                let now = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                max_in_flight.fetch_max(now, Ordering::SeqCst);
+                tokio::time::sleep(Duration::from_millis(5 * (18 - n))).await;
+                in_flight.fetch_sub(1, Ordering::SeqCst);
+                completed.lock().unwrap().push(n);
+                n * 100
+            }
+        });
+        let yielded: Vec<(u64, u64)> = prepared.collect().await;
+
+        assert_eq!(yielded, (10..=17).map(|n| (n, n * 100)).collect::<Vec<_>>());
+        let completed = completed.lock().unwrap().clone();
+        assert_ne!(completed, (10..=17).collect::<Vec<_>>(), "完成顺序应与高度不同");
+        assert_eq!(max_in_flight.load(Ordering::SeqCst), 4);
+    }
+
+    #[tokio::test]
+    async fn pipelined_failure_surfaces_at_its_height() {
+        let prepared = prepare_in_order(1, 5, 3, |n| async move {
+            tokio::time::sleep(Duration::from_millis(5 * (6 - n))).await;
+            if n == 3 { Err(n) } else { Ok(n) }
+        });
+        let mut prepared = std::pin::pin!(prepared);
+        let mut committed = Vec::new();
+        while let Some((n, result)) = prepared.next().await {
+            match result {
+                Ok(v) => committed.push(v),
+                Err(failed) => {
+                    assert_eq!((n, failed), (3, 3));
+                    break;
+                }
+            }
+        }
+        // 失败前只提交了更低的高度，后面已完成的预取结果被丢弃
+        assert_eq!(committed, vec![1, 2]);
+    }
+}
@@ -0,0 +1,159 @@
+use crate::config::ConsistencyCheckConfig;
+use crate::database::diesel::TransactionExecutor;
+use crate::errors::error::AppError;
+use crate::services::block_service::BlockService;
+use crate::{log_error, log_info, log_warn};
+use ethers_core::types::H256;
+use rand::Rng;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+use tokio::task::JoinHandle;
+
+/// 跨节点一致性校验：多节点轮询时，某个节点返回的区块可能与其他节点不一致（负载均衡后端不同步等）。
+/// 定期从已最终确认的已入库区块中随机抽样，逐个节点查询哈希并与本地比对，发现不一致时记录并按配置回滚重同步
+pub struct ConsistencyChecker {
+    block_service: Arc<BlockService>,
+    config: ConsistencyCheckConfig,
+    /// 队列模式下回滚会与队列状态冲突，只记录不回滚
+    queue_mode: bool,
+    /// 累计发现的不一致次数（用于监控）
+    mismatches: AtomicU64,
+}
+
+impl ConsistencyChecker {
+    pub fn new(
+        block_service: Arc<BlockService>,
+        config: ConsistencyCheckConfig,
+        queue_mode: bool,
+    ) -> Self {
+        Self {
+            block_service,
+            config,
+            queue_mode,
+            mismatches: AtomicU64::new(0),
+        }
+    }
+
+    pub fn mismatch_count(&self) -> u64 {
+        self.mismatches.load(Ordering::Relaxed)
+    }
+
+    pub fn spawn(self: Arc<Self>) -> JoinHandle<()> {
+        let interval = Duration::from_secs(self.config.interval_secs.max(1));
+        log_info!(
+            "跨节点一致性校验已启动: interval={:?}, sample_size={}",
+            interval,
+            self.config.sample_size
+        );
+        tokio::spawn(async move {
+            loop {
+                tokio::time::sleep(interval).await;
+                if let Err(e) = self.check_once().await {
+                    log_error!("一致性校验失败: {}", e);
+                }
+            }
+        })
+    }
+
+    /// 抽样校验一次，返回本次发现的不一致区块数
+    pub async fn check_once(&self) -> Result<usize, AppError> {
+        let tip = self.block_service.last_synced.load(Ordering::Relaxed);
+        let newest = tip.saturating_sub(self.config.min_depth);
+        let oldest = newest
+            .saturating_sub(self.config.window)
+            .max(self.block_service.config.init_height);
+        if newest < oldest || newest == 0 {
+            return Ok(0);
+        }
+
+        let samples: Vec<u64> = {
+            let mut rng = rand::thread_rng();
+            (0..self.config.sample_size)
+                .map(|_| rng.gen_range(oldest..=newest))
+                .collect()
+        };
+
+        let mut found = 0;
+        for height in samples {
+            if self.check_block(height).await? {
+                found += 1;
+            }
+        }
+        Ok(found)
+    }
+
+    /// 比对单个区块，不一致时返回 true
+    async fn check_block(&self, height: u64) -> Result<bool, AppError> {
+        let stored = {
+            let mut conn = self
+                .block_service
+                .db_service
+                .pool
+                .get()
+                .await
+                .map_err(|e| AppError::Internal(e.to_string()))?;
+            self.block_service
+                .block_repository
+                .get_block(&mut conn, height as i64)
+                .await?
+        };
+        let Some(stored) = stored else {
+            return Ok(false);
+        };
+
+        let hashes = self.block_service.provider.block_hash_per_provider(height).await;
+        let stored_hash = stored.block_hash.as_str();
+        let disagreeing: Vec<(usize, H256)> = hashes
+            .iter()
+            .enumerate()
+            .filter_map(|(i, h)| h.map(|h| (i, h)))
+            .filter(|(_, h)| format!("{:#x}", h) != stored_hash)
+            .collect();
+        if disagreeing.is_empty() {
+            return Ok(false);
+        }
+
+        self.mismatches.fetch_add(1, Ordering::Relaxed);
+        let responded = hashes.iter().filter(|h| h.is_some()).count();
+        log_error!(
+            "一致性校验：区块 {} 本地哈希 {}，{}/{} 个节点返回不同哈希: {:?}",
+            height,
+            stored_hash,
+            disagreeing.len(),
+            responded,
+            disagreeing
+        );
+
+        // 只有所有响应的节点都给出同一个不同哈希时，才认为本地数据错误
+        let unanimous = disagreeing.len() == responded
+            && disagreeing.windows(2).all(|w| w[0].1 == w[1].1);
+        if unanimous && self.config.reprocess_on_mismatch {
+            if self.queue_mode {
+                log_warn!("一致性校验：队列模式下不自动回滚区块 {}，请人工处理", height);
+            } else {
+                self.rewind_before(height).await?;
+            }
+        }
+        Ok(true)
+    }
+
+    /// 删除 `height` 及之后的区块与转账，由同步循环重新处理
+    async fn rewind_before(&self, height: u64) -> Result<(), AppError> {
+        let keep = height as i64 - 1;
+        let repos = self.block_service.rewind_repositories();
+        let (blocks, transfers) = self
+            .block_service
+            .db_service
+            .execute_tx(move |conn| Box::pin(async move { repos.delete_above(conn, keep).await }))
+            .await?;
+        self.block_service.reset_after_rewind(keep);
+        log_warn!(
+            "一致性校验：回滚到区块 {} 重新同步，删除区块 {} 个、转账 {} 笔",
+            keep,
+            blocks,
+            transfers
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,114 @@
+use crate::config::filter_config::FilterConfigContainer;
+use crate::database::diesel::DbService;
+use crate::errors::error::AppError;
+use crate::infrastructure::provider::ProviderTrait;
+use crate::models::Transfer;
+use crate::models::block_db::BlockRow;
+use crate::repositories::block_repository::BlockRepository;
+use crate::repositories::transaction_repository::TransactionRepository;
+use crate::services::block_service::BlockService;
+use crate::services::tx::types::{TxOptions, TxResult};
+use crate::services::tx_service::TxService;
+use ethers::prelude::U64;
+use ethers_core::types::{Address, H160, U256};
+use std::sync::Arc;
+
+/// 统一入口：持有共享的 provider / 数据库 / 地址过滤配置，并把操作委托给各个专用服务
+/// 作为库使用时只需要这一个结构体；需要更细粒度控制时仍可直接使用各个服务
+pub struct EthereumService {
+    pub provider: Arc<dyn ProviderTrait>,
+    pub db_service: Arc<DbService>,
+    pub filter_config: Arc<FilterConfigContainer>,
+    pub block_repository: Arc<BlockRepository>,
+    pub transaction_repository: Arc<TransactionRepository>,
+    pub block_service: Arc<BlockService>,
+    /// 发送交易需要签名器，纯索引部署可不配置
+    pub tx_service: Option<Arc<TxService>>,
+}
+
+impl EthereumService {
+    pub fn new(
+        provider: Arc<dyn ProviderTrait>,
+        db_service: Arc<DbService>,
+        filter_config: Arc<FilterConfigContainer>,
+        block_repository: Arc<BlockRepository>,
+        transaction_repository: Arc<TransactionRepository>,
+        block_service: Arc<BlockService>,
+        tx_service: Option<Arc<TxService>>,
+    ) -> Self {
+        Self {
+            provider,
+            db_service,
+            filter_config,
+            block_repository,
+            transaction_repository,
+            block_service,
+            tx_service,
+        }
+    }
+
+    /// 同步区块到当前安全高度
+    pub async fn sync_blocks(&self) -> anyhow::Result<()> {
+        self.block_service.sync_blocks().await
+    }
+
+    /// 链上最新区块号
+    pub async fn chain_head(&self) -> Result<U64, AppError> {
+        self.provider.get_last_block_number().await
+    }
+
+    /// 本地已入库的最新区块
+    pub async fn latest_block(&self) -> Result<Option<BlockRow>, AppError> {
+        let mut conn = self
+            .db_service
+            .query_pool
+            .get()
+            .await
+            .map_err(|e| AppError::Internal(e.to_string()))?;
+        self.block_repository.get_last_block_number(&mut conn).await
+    }
+
+    /// 某地址最近的转账记录
+    pub async fn recent_transfers_for(
+        &self,
+        address: &H160,
+        limit: i64,
+    ) -> Result<Vec<Transfer>, AppError> {
+        let mut conn = self
+            .db_service
+            .query_pool
+            .get()
+            .await
+            .map_err(|e| AppError::Internal(e.to_string()))?;
+        self.transaction_repository
+            .recent_transfers_for(&mut conn, address, limit)
+            .await
+    }
+
+    pub async fn transfer_eth(
+        &self,
+        to: Address,
+        amount: U256,
+        options: Option<TxOptions>,
+    ) -> Result<TxResult, AppError> {
+        self.tx_service()?.transfer_eth(to, amount, options).await
+    }
+
+    pub async fn erc20_transfer(
+        &self,
+        token_address: Address,
+        to: Address,
+        amount: U256,
+        options: Option<TxOptions>,
+    ) -> Result<TxResult, AppError> {
+        self.tx_service()?
+            .erc20_transfer(token_address, to, amount, options)
+            .await
+    }
+
+    fn tx_service(&self) -> Result<&Arc<TxService>, AppError> {
+        self.tx_service
+            .as_ref()
+            .ok_or_else(|| AppError::InvalidRequest("未配置交易发送服务 (TxService)".to_string()))
+    }
+}
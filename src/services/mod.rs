@@ -1,6 +1,17 @@
-mod block_service;
-mod token_service;
-mod tx_service;
-mod tx;
+pub mod alert_monitor;
+pub mod block_queue_service;
+pub mod block_service;
+pub mod consistency_service;
+pub mod ethereum_service;
+pub mod status_promoter;
+pub mod token_service;
+pub mod tx_service;
+pub mod tx;
 
+pub use alert_monitor::AlertMonitor;
+pub use block_queue_service::BlockQueueService;
 pub use block_service::*;
+pub use consistency_service::ConsistencyChecker;
+pub use ethereum_service::EthereumService;
+pub use status_promoter::TransferStatusPromoter;
+pub use tx_service::TxService;
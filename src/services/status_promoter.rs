@@ -0,0 +1,70 @@
+use crate::config::TransferStatusConfig;
+use crate::errors::error::AppError;
+use crate::services::block_service::BlockService;
+use crate::{log_error, log_info};
+use std::sync::Arc;
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+
+/// 两阶段转账状态的提升任务：定期把超过最终确认深度的确认中（status = 2）转账更新为已确认（status = 1）
+///
+/// 重组窗口内的转账若所在区块被回滚，会随区块一起删除，不会被提升
+pub struct TransferStatusPromoter {
+    block_service: Arc<BlockService>,
+    config: TransferStatusConfig,
+}
+
+impl TransferStatusPromoter {
+    pub fn new(block_service: Arc<BlockService>, config: TransferStatusConfig) -> Self {
+        Self {
+            block_service,
+            config,
+        }
+    }
+
+    pub fn spawn(self: Arc<Self>) -> JoinHandle<()> {
+        let interval = Duration::from_secs(self.config.interval_secs.max(1));
+        log_info!(
+            "转账确认状态提升任务已启动: interval={:?}, finality_depth={}",
+            interval,
+            self.config.finality_depth
+        );
+        tokio::spawn(async move {
+            loop {
+                if let Err(e) = self.promote_once().await {
+                    log_error!("转账确认状态提升失败: {}", e);
+                }
+                tokio::time::sleep(interval).await;
+            }
+        })
+    }
+
+    /// 执行一次提升，返回更新的转账数
+    pub async fn promote_once(&self) -> Result<usize, AppError> {
+        let head = self.block_service.provider.get_last_block_number().await?.as_u64();
+        self.block_service
+            .chain_head
+            .fetch_max(head, Ordering::Relaxed);
+        let Some(up_to) = head.checked_sub(self.config.finality_depth) else {
+            return Ok(0);
+        };
+
+        let mut conn = self
+            .block_service
+            .db_service
+            .pool
+            .get()
+            .await
+            .map_err(|e| AppError::Internal(e.to_string()))?;
+        let promoted = self
+            .block_service
+            .transaction_repository
+            .promote_confirmed(&mut conn, up_to as i64)
+            .await?;
+        if promoted > 0 {
+            log_info!("{} 笔转账已超过确认深度（区块 ≤ {}），更新为已确认", promoted, up_to);
+        }
+        Ok(promoted)
+    }
+}
@@ -0,0 +1,215 @@
+// services/token_service.rs
+use crate::errors::error::AppError;
+use crate::infrastructure::protocol::constants::EIP1967_IMPLEMENTATION_SLOT;
+use crate::infrastructure::provider::ProviderTrait;
+use crate::log_warn;
+use crate::models::domain::token::TokenMetadata;
+use ethers_core::abi::{ParamType, Token, decode};
+use ethers_core::types::transaction::eip2718::TypedTransaction;
+use ethers_core::types::{Address, Bytes, Eip1559TransactionRequest, U256};
+use ethers_core::utils::keccak256;
+use hashlink::LruCache;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::OnceCell;
+
+/// 代理实现地址缓存有效期（代理升级后最迟在此时间后生效）
+const IMPLEMENTATION_CACHE_TTL: Duration = Duration::from_secs(3600);
+/// 代理实现地址缓存的最大条目数，超出后按 LRU 淘汰
+const IMPLEMENTATION_CACHE_CAPACITY: usize = 1024;
+
+/// (链 ID, 合约地址) → (查询时间, 实现地址)
+type ImplementationCache = LruCache<(u64, Address), (Instant, Option<Address>)>;
+
+/// ERC20 只读查询（eth_call）
+pub struct TokenService {
+    provider: Arc<dyn ProviderTrait>,
+    /// 节点所在链的 ID，首次读取实现地址时查询
+    chain_id: OnceCell<u64>,
+    implementations: Mutex<ImplementationCache>,
+}
+
+impl TokenService {
+    pub fn new(provider: Arc<dyn ProviderTrait>) -> Self {
+        Self {
+            provider,
+            chain_id: OnceCell::new(),
+            implementations: Mutex::new(LruCache::new(IMPLEMENTATION_CACHE_CAPACITY)),
+        }
+    }
+
+    /// balanceOf(owner)
+    pub async fn balance_of(&self, token: Address, owner: Address) -> Result<U256, AppError> {
+        let mut data = keccak256("balanceOf(address)")[..4].to_vec();
+        data.extend_from_slice(&ethers::abi::encode(&[ethers::abi::Token::Address(owner)]));
+        let output = self.call(token, data).await?;
+        if output.len() < 32 {
+            return Err(AppError::ProviderError(format!(
+                "代币 {:?} balanceOf 返回数据长度异常: {}",
+                token,
+                output.len()
+            )));
+        }
+        Ok(U256::from_big_endian(&output[..32]))
+    }
+
+    /// 查询 name / symbol / decimals；name、symbol 调用失败或无法解码时为 None，decimals 失败时返回错误
+    pub async fn metadata(&self, token: Address) -> Result<TokenMetadata, AppError> {
+        let name = self.string_property(token, "name()").await;
+        let symbol = self.string_property(token, "symbol()").await;
+        let output = self.call(token, keccak256("decimals()")[..4].to_vec()).await?;
+        if output.len() < 32 {
+            return Err(AppError::ProviderError(format!(
+                "代币 {:?} decimals 返回数据长度异常: {}",
+                token,
+                output.len()
+            )));
+        }
+        let decimals = U256::from_big_endian(&output[..32]);
+        if decimals > U256::from(u8::MAX) {
+            return Err(AppError::ProviderError(format!(
+                "代币 {:?} decimals 超出范围: {}",
+                token, decimals
+            )));
+        }
+        let implementation = self.implementation(token).await.unwrap_or_else(|e| {
+            log_warn!("代币 {:?} 读取代理实现地址失败: {}", token, e);
+            None
+        });
+        Ok(TokenMetadata {
+            address: token.into(),
+            name,
+            symbol,
+            decimals: decimals.as_u32() as u8,
+            implementation: implementation.map(Into::into),
+        })
+    }
+
+    /// 读取 EIP-1967 实现槽位，返回代理指向的实现合约地址；槽位为零（非代理合约）时返回 None
+    /// 结果按 (链 ID, 合约) 缓存 `IMPLEMENTATION_CACHE_TTL`
+    pub async fn implementation(&self, contract: Address) -> Result<Option<Address>, AppError> {
+        let chain_id = *self
+            .chain_id
+            .get_or_try_init(|| async {
+                Ok::<_, AppError>(self.provider.get_chain_id().await?.as_u64())
+            })
+            .await?;
+        let key = (chain_id, contract);
+        if let Some((at, cached)) = self.implementations.lock().unwrap().get(&key) {
+            if at.elapsed() < IMPLEMENTATION_CACHE_TTL {
+                return Ok(*cached);
+            }
+        }
+        let slot = self
+            .provider
+            .get_storage_at(contract, *EIP1967_IMPLEMENTATION_SLOT)
+            .await?;
+        let implementation = Some(Address::from(slot)).filter(|a| !a.is_zero());
+        self.implementations
+            .lock()
+            .unwrap()
+            .insert(key, (Instant::now(), implementation));
+        Ok(implementation)
+    }
+
+    /// 调用无参的 name() / symbol()，兼容返回 string 与 bytes32 的代币
+    async fn string_property(&self, token: Address, signature: &str) -> Option<String> {
+        match self.call(token, keccak256(signature)[..4].to_vec()).await {
+            Ok(output) => decode_string_or_bytes32(&output),
+            Err(e) => {
+                log_warn!("代币 {:?} 调用 {} 失败: {}", token, signature, e);
+                None
+            }
+        }
+    }
+
+    async fn call(&self, token: Address, data: Vec<u8>) -> Result<Bytes, AppError> {
+        let call = TypedTransaction::Eip1559(Eip1559TransactionRequest::new().to(token).data(data));
+        self.provider.call(&call).await
+    }
+}
+
+/// 解码 name() / symbol() 的返回值：先按标准 ABI `string` 解码，失败时按 `bytes32` 解码并去掉末尾的 0
+/// （MKR 等老代币返回 bytes32）。两种方式都得不到有效 UTF-8 时返回 None
+pub fn decode_string_or_bytes32(output: &[u8]) -> Option<String> {
+    if let Ok(tokens) = decode(&[ParamType::String], output) {
+        if let Some(Token::String(s)) = tokens.into_iter().next() {
+            return Some(s);
+        }
+    }
+    if output.len() != 32 {
+        return None;
+    }
+    let end = output.iter().rposition(|b| *b != 0).map_or(0, |i| i + 1);
+    String::from_utf8(output[..end].to_vec()).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::provider::{EthereumProvider, RetryAdapter};
+    use ethers_core::abi::encode;
+    use ethers_core::types::H256;
+    use ethers_providers::MockProvider;
+
+    fn bytes32(s: &str) -> Vec<u8> {
+        let mut word = vec![0u8; 32];
+        word[..s.len()].copy_from_slice(s.as_bytes());
+        word
+    }
+
+    #[test]
+    fn decodes_abi_string() {
+        let output = encode(&[Token::String("Wrapped Ether".to_string())]);
+        assert_eq!(decode_string_or_bytes32(&output).as_deref(), Some("Wrapped Ether"));
+    }
+
+    #[test]
+    fn decodes_zero_padded_bytes32() {
+        // MKR 的 symbol() 返回 bytes32，右侧补 0
+        assert_eq!(decode_string_or_bytes32(&bytes32("MKR")).as_deref(), Some("MKR"));
+        assert_eq!(decode_string_or_bytes32(&bytes32("Maker")).as_deref(), Some("Maker"));
+    }
+
+    #[test]
+    fn decodes_full_width_bytes32() {
+        let name = "ABCDEFGHIJKLMNOPQRSTUVWXYZ012345";
+        assert_eq!(decode_string_or_bytes32(&bytes32(name)).as_deref(), Some(name));
+    }
+
+    #[test]
+    fn rejects_invalid_utf8_and_unexpected_length() {
+        let mut word = bytes32("");
+        word[0] = 0xff;
+        assert_eq!(decode_string_or_bytes32(&word), None);
+        assert_eq!(decode_string_or_bytes32(&bytes32("MKR")[..31]), None);
+    }
+
+    /// 依次返回 chain_id 与 `slots` 中的存储槽位值
+    fn token_service(slots: &[H256]) -> TokenService {
+        let mock = MockProvider::new();
+        // MockProvider 从队尾弹出响应
+        for slot in slots.iter().rev() {
+            mock.push(*slot).unwrap();
+        }
+        mock.push(U256::from(1)).unwrap();
+        let provider = Arc::new(EthereumProvider::from_transports(vec![mock]));
+        TokenService::new(Arc::new(RetryAdapter::new(provider, 1, Duration::ZERO)))
+    }
+
+    #[tokio::test]
+    async fn implementation_is_read_from_eip1967_slot() {
+        let implementation = Address::repeat_byte(0xbb);
+        let service = token_service(&[H256::from(implementation)]);
+        let proxy = Address::repeat_byte(0xaa);
+        assert_eq!(service.implementation(proxy).await.unwrap(), Some(implementation));
+        // 第二次命中缓存，不再请求节点（mock 已无响应）
+        assert_eq!(service.implementation(proxy).await.unwrap(), Some(implementation));
+    }
+
+    #[tokio::test]
+    async fn zero_slot_means_not_a_proxy() {
+        let service = token_service(&[H256::zero()]);
+        assert_eq!(service.implementation(Address::repeat_byte(0xaa)).await.unwrap(), None);
+    }
+}
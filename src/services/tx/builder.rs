@@ -0,0 +1,113 @@
+// services/tx/builder.rs
+
+use crate::errors::error::AppError;
+use crate::infrastructure::provider::ConfirmationTarget;
+use crate::services::tx::gas::gas_strategy::TxPriority;
+use crate::services::tx::types::{TxContext, TxOptions};
+use ethers_core::abi::{Token, encode};
+use ethers_core::types::{Bytes, H160, U256};
+use ethers_core::utils::keccak256;
+
+/// 任意交易的构造器，产出的 [`TxContext`] 交给 `TxService::send` 发送
+///
+/// ```ignore
+/// let ctx = TxBuilder::new()
+///     .to(router)
+///     .call("approve(address,uint256)", &[Token::Address(spender), Token::Uint(amount)])
+///     .priority(TxPriority::High)
+///     .confirmations(3)
+///     .build()?;
+/// let result = tx_service.send(ctx).await?;
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct TxBuilder {
+    to: Option<H160>,
+    value: U256,
+    data: Bytes,
+    options: TxOptions,
+}
+
+impl TxBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 目标地址（合约或收款方），必填
+    pub fn to(mut self, to: H160) -> Self {
+        self.to = Some(to);
+        self
+    }
+
+    /// 随交易发送的 ETH（wei）
+    pub fn value(mut self, value: U256) -> Self {
+        self.value = value;
+        self
+    }
+
+    /// 原始 calldata
+    pub fn data(mut self, data: impl Into<Bytes>) -> Self {
+        self.data = data.into();
+        self
+    }
+
+    /// 按函数签名与参数编码 calldata，如 `call("approve(address,uint256)", &[..])`
+    pub fn call(self, signature: &str, args: &[Token]) -> Self {
+        let mut data = keccak256(signature)[..4].to_vec();
+        data.extend_from_slice(&encode(args));
+        self.data(data)
+    }
+
+    /// 替换全部发送选项，之后的 `priority` 等调用在其基础上修改
+    pub fn options(mut self, options: TxOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    pub fn priority(mut self, priority: TxPriority) -> Self {
+        self.options.priority = priority;
+        self
+    }
+
+    /// 所需确认数，未调用时按优先级取默认值
+    pub fn confirmations(mut self, confirmations: u64) -> Self {
+        self.options.confirmations = Some(confirmations);
+        self
+    }
+
+    /// 确认策略（如 safe / finalized），优先于确认数
+    pub fn confirmation_target(mut self, target: ConfirmationTarget) -> Self {
+        self.options.confirmation_target = Some(target);
+        self
+    }
+
+    /// gas limit 缓冲百分比，例如 120 表示 +20%
+    pub fn gas_limit_buffer(mut self, percent: u64) -> Self {
+        self.options.gas_limit_buffer = percent;
+        self
+    }
+
+    /// 等待确认的超时秒数
+    pub fn timeout_secs(mut self, secs: u64) -> Self {
+        self.options.timeout_secs = secs;
+        self
+    }
+
+    /// 幂等键（需为 TxService 配置 SentTxStore）
+    pub fn idempotency_key(mut self, key: impl Into<String>) -> Self {
+        self.options.idempotency_key = Some(key.into());
+        self
+    }
+
+    /// 生成交易上下文；未设置目标地址时返回 `AppError::Validation`
+    pub fn build(self) -> Result<TxContext, AppError> {
+        let to = self
+            .to
+            .ok_or_else(|| AppError::Validation("交易缺少目标地址 to".to_string()))?;
+        Ok(TxContext {
+            to,
+            value: self.value,
+            data: self.data,
+            options: self.options,
+        })
+    }
+}
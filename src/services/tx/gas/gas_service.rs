@@ -1,16 +1,19 @@
 // services/tx/gas/gas_service.rs
 
+use crate::config::TxConfig;
 use crate::errors::error::AppError;
 use crate::services::tx::gas::gas_strategy::TxPriority;
 use ethers_core::types::U256;
 use ethers_providers::Middleware;
-use crate::infrastructure::provider::ProviderTrait;
+use crate::infrastructure::provider::{ProviderTrait, RpcMethod};
 
 /// Gas 费用计算服务（纯整数运算，无浮点风险）
 #[derive(Clone, Copy, Debug)]
 pub struct GasService {
     /// 全局对 tip 的额外调整百分比（100 = 无调整，110 = +10%，90 = -10%）
     base_tip_percent: u128,
+    /// max_fee_per_gas 的绝对上限（wei），None 时只按优先级倍率限制
+    max_fee_cap: Option<U256>,
 }
 
 impl Default for GasService {
@@ -23,7 +26,20 @@ impl GasService {
     /// 构造函数：传入百分比整数
     /// 示例：GasService::new(110) 表示全局 tip +10%
     pub fn new(base_tip_percent: u128) -> Self {
-        Self { base_tip_percent }
+        Self {
+            base_tip_percent,
+            max_fee_cap: None,
+        }
+    }
+
+    /// 按 `[tx]` 配置构造：全局 tip 百分比与 max_fee 绝对上限
+    pub fn from_config(config: &TxConfig) -> Self {
+        Self {
+            base_tip_percent: config.base_tip_percent,
+            max_fee_cap: config
+                .max_fee_cap_gwei
+                .map(|gwei| U256::from(gwei) * U256::exp10(9)),
+        }
     }
 
     /// 便捷构造函数：无额外调整
@@ -37,6 +53,13 @@ impl GasService {
         provider: &dyn ProviderTrait,
         priority: TxPriority,
     ) -> Result<(U256, U256), AppError> {
+        // 节点不支持 eth_feeHistory 时无法估算 EIP-1559 费用，直接返回明确错误
+        if !provider.supports(RpcMethod::FeeHistory) {
+            return Err(AppError::MethodNotSupported(
+                "eth_feeHistory 不可用，无法估算 EIP1559 费用".to_string(),
+            ));
+        }
+
         // 1. 获取链上建议的费用
         let (max_fee_per_gas, base_priority_fee) = provider
             .estimate_eip1559_fees(None)
@@ -69,7 +92,14 @@ impl GasService {
             / U256::from(100);
 
         // 取链上建议值与我们安全上限的较小值（保守策略）
-        let final_max_fee_per_gas = max_fee_per_gas.min(max_allowed_fee);
+        let mut final_max_fee_per_gas = max_fee_per_gas.min(max_allowed_fee);
+        let mut adjusted_priority_fee = adjusted_priority_fee;
+
+        // 4. 配置的绝对上限：tip 不能超过 max_fee，两者一起截断
+        if let Some(cap) = self.max_fee_cap {
+            final_max_fee_per_gas = final_max_fee_per_gas.min(cap);
+            adjusted_priority_fee = adjusted_priority_fee.min(cap);
+        }
 
         Ok((final_max_fee_per_gas, adjusted_priority_fee))
     }
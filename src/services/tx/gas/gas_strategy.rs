@@ -33,4 +33,14 @@ impl TxPriority {
             TxPriority::Urgent => 300, // ×3.0（允许更高以确保上链）
         }
     }
+
+    /// 未显式指定确认数时的默认值：低优先级的批量操作多等几个区块，其余上链即返回
+    pub fn default_confirmations(&self) -> u64 {
+        match self {
+            TxPriority::Low => 3,
+            TxPriority::Normal => 1,
+            TxPriority::High => 1,
+            TxPriority::Urgent => 1, // 至少 1 个确认才能拿到回执
+        }
+    }
 }
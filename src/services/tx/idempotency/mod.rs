@@ -0,0 +1 @@
+pub mod sent_tx_store;
\ No newline at end of file
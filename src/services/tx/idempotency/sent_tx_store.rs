@@ -0,0 +1,117 @@
+// services/tx/idempotency/sent_tx_store.rs
+
+use crate::errors::error::AppError;
+use ethers_core::types::H256;
+use redis::aio::ConnectionManager;
+use std::str::FromStr;
+
+/// 幂等记录默认保留时间（秒）
+pub const DEFAULT_SENT_TX_TTL_SECS: u64 = 24 * 3600;
+
+/// 预占但尚未签名广播时写入的占位值
+const RESERVED: &str = "reserved";
+
+/// idempotency_key 对应的发送记录
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SentTx {
+    /// 已预占，交易尚未广播（另一个调用正在处理）
+    Reserved,
+    /// 已广播的交易哈希（加价重发后为最新一笔）
+    Sent(H256),
+}
+
+/// idempotency_key → tx hash 的 Redis 存储，防止调用方重试导致重复发送
+///
+/// 发送前用 `SET NX` 预占 key，签名后、广播前写入交易哈希；
+/// 广播前失败时删除 key，允许调用方用同一个 key 重试
+#[derive(Clone)]
+pub struct SentTxStore {
+    conn: ConnectionManager,
+    ttl_secs: u64,
+}
+
+impl SentTxStore {
+    pub fn new(conn: ConnectionManager) -> Self {
+        Self {
+            conn,
+            ttl_secs: DEFAULT_SENT_TX_TTL_SECS,
+        }
+    }
+
+    pub fn with_ttl(mut self, ttl_secs: u64) -> Self {
+        self.ttl_secs = ttl_secs;
+        self
+    }
+
+    fn redis_key(key: &str) -> String {
+        format!("tx:idempotency:{}", key)
+    }
+
+    /// 预占 key；返回 None 表示预占成功，Some 为已有的发送记录
+    pub async fn reserve(&self, key: &str) -> Result<Option<SentTx>, AppError> {
+        let mut conn = self.conn.clone();
+        let redis_key = Self::redis_key(key);
+        loop {
+            let reserved: Option<String> = redis::cmd("SET")
+                .arg(&redis_key)
+                .arg(RESERVED)
+                .arg("NX")
+                .arg("EX")
+                .arg(self.ttl_secs)
+                .query_async(&mut conn)
+                .await?;
+            if reserved.is_some() {
+                return Ok(None);
+            }
+
+            let existing: Option<String> = redis::cmd("GET")
+                .arg(&redis_key)
+                .query_async(&mut conn)
+                .await?;
+            return match existing {
+                // 记录恰好在两次命令之间过期，重新预占
+                None => continue,
+                Some(value) if value == RESERVED => Ok(Some(SentTx::Reserved)),
+                Some(value) => H256::from_str(&value)
+                    .map(|hash| Some(SentTx::Sent(hash)))
+                    .map_err(|e| AppError::Conversion(format!("幂等记录 {} 解析失败: {}", key, e))),
+            };
+        }
+    }
+
+    /// 记录即将广播的交易哈希（在广播之前调用）
+    pub async fn record(&self, key: &str, tx_hash: H256) -> Result<(), AppError> {
+        let mut conn = self.conn.clone();
+        let _: () = redis::cmd("SET")
+            .arg(Self::redis_key(key))
+            .arg(format!("{:?}", tx_hash))
+            .arg("EX")
+            .arg(self.ttl_secs)
+            .query_async(&mut conn)
+            .await?;
+        Ok(())
+    }
+
+    /// 删除记录，允许用同一个 key 重新发送
+    pub async fn release(&self, key: &str) -> Result<(), AppError> {
+        let mut conn = self.conn.clone();
+        let _: () = redis::cmd("DEL")
+            .arg(Self::redis_key(key))
+            .query_async(&mut conn)
+            .await?;
+        Ok(())
+    }
+
+    /// 仅当交易尚未广播（仍为占位值）时删除记录
+    pub async fn release_if_reserved(&self, key: &str) -> Result<(), AppError> {
+        let mut conn = self.conn.clone();
+        let existing: Option<String> = redis::cmd("GET")
+            .arg(Self::redis_key(key))
+            .query_async(&mut conn)
+            .await?;
+        if existing.as_deref() == Some(RESERVED) {
+            self.release(key).await?;
+        }
+        Ok(())
+    }
+}
@@ -1,5 +1,7 @@
 pub mod types;
+pub mod builder;
 pub mod gas;
 pub mod nonce;
 pub mod simulation;
 pub mod signer;
+pub mod idempotency;
@@ -1,9 +1,12 @@
 // services/tx/nonce/nonce_service.rs
 
 use crate::errors::error::AppError;
+use crate::infrastructure::provider::ProviderTrait;
+use crate::{log_info, log_warn};
 use ethers_core::types::{H160, U256};
 use ethers_providers::Middleware;
 use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
 use tokio::sync::Mutex;
 
 // 创建一次，永久共享
@@ -18,6 +21,24 @@ pub struct NonceService {
     current_nonce: AtomicU64,
     /// 防止并发同步链上 nonce 时冲突
     sync_lock: Mutex<()>,
+    /// 疑似缺失的 nonce（本地 > pending 时链上的 pending）及首次观察到它的时间；
+    /// pending 前移或空洞消失后清空，只有同一个 pending 停滞超过阈值才视为空洞
+    gap_since: std::sync::Mutex<Option<(u64, Instant)>>,
+    /// 累计自动修复次数（用于监控）
+    heal_count: AtomicU64,
+}
+
+/// 一次 nonce 对账的结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NonceReconcile {
+    /// 本地与链上一致
+    InSync,
+    /// 链上 pending 高于本地（有外部发送），已前移本地 nonce
+    Advanced { from: u64, to: u64 },
+    /// 存在空洞但尚未超过阈值，继续观察
+    GapObserved { local: u64, pending: u64, elapsed: Duration },
+    /// 空洞持续超过阈值，已将本地 nonce 重置为缺失的 nonce
+    Healed { missing_nonce: u64, previous_local: u64 },
 }
 
 impl NonceService {
@@ -32,6 +53,8 @@ impl NonceService {
             address,
             current_nonce: AtomicU64::new(chain_nonce.as_u64()),
             sync_lock: Mutex::new(()),
+            gap_since: std::sync::Mutex::new(None),
+            heal_count: AtomicU64::new(0),
         })
     }
 
@@ -70,4 +93,167 @@ impl NonceService {
     pub fn current(&self) -> u64 {
         self.current_nonce.load(Ordering::SeqCst)
     }
-}
\ No newline at end of file
+
+    /// 累计自动修复 nonce 空洞的次数（用于监控）
+    pub fn heal_count(&self) -> u64 {
+        self.heal_count.load(Ordering::Relaxed)
+    }
+
+    /// 只做前移：链上 pending 高于本地（外部发送或已上链的交易）时把本地 nonce 前移到 pending，
+    /// 本地 > pending 时不做任何处理（可能有其它发送方预占了 nonce 且交易尚在传播）
+    pub async fn catch_up(&self, provider: &dyn ProviderTrait) -> Result<NonceReconcile, AppError> {
+        let _guard = self.sync_lock.lock().await;
+        let address = format!("{:#x}", self.address);
+        let pending = provider
+            .get_pending_transaction_count(&address)
+            .await?
+            .as_u64();
+        let local = self.current_nonce.load(Ordering::SeqCst);
+        if local < pending {
+            self.current_nonce.store(pending, Ordering::SeqCst);
+            log_info!("nonce 对账: 链上 pending={} 高于本地 {}，已前移", pending, local);
+            return Ok(NonceReconcile::Advanced { from: local, to: pending });
+        }
+        Ok(NonceReconcile::InSync)
+    }
+
+    /// 对账本地 nonce 与链上 latest / pending nonce，检测并修复空洞
+    ///
+    /// 交易被内存池丢弃后，本地计数器会停在一个链上永远不会填上的位置，
+    /// 后续所有交易都会卡住。本地 > pending 是发送进行中的正常状态，
+    /// 只有 pending 停在同一个值上持续超过 `gap_threshold`（该 nonce 一直没有被填上）时，
+    /// 才将本地 nonce 重置为 pending（即缺失的 nonce）；pending 前移会重新计时。
+    pub async fn reconcile(
+        &self,
+        provider: &dyn ProviderTrait,
+        gap_threshold: Duration,
+    ) -> Result<NonceReconcile, AppError> {
+        let _guard = self.sync_lock.lock().await;
+        let address = format!("{:#x}", self.address);
+
+        let latest = provider.get_transaction_count(&address).await?.as_u64();
+        let pending = provider
+            .get_pending_transaction_count(&address)
+            .await?
+            .as_u64();
+        let local = self.current_nonce.load(Ordering::SeqCst);
+
+        if local <= pending {
+            *self.gap_since.lock().unwrap() = None;
+            if local < pending {
+                self.current_nonce.store(pending, Ordering::SeqCst);
+                log_info!("nonce 对账: 链上 pending={} 高于本地 {}，已前移", pending, local);
+                return Ok(NonceReconcile::Advanced { from: local, to: pending });
+            }
+            return Ok(NonceReconcile::InSync);
+        }
+
+        let elapsed = {
+            let mut gap_since = self.gap_since.lock().unwrap();
+            match *gap_since {
+                Some((stuck, since)) if stuck == pending => since.elapsed(),
+                _ => {
+                    *gap_since = Some((pending, Instant::now()));
+                    Duration::ZERO
+                }
+            }
+        };
+        if elapsed < gap_threshold {
+            log_warn!(
+                "nonce 空洞: 本地={}, pending={}, latest={}, 已持续 {:?}",
+                local,
+                pending,
+                latest,
+                elapsed
+            );
+            return Ok(NonceReconcile::GapObserved { local, pending, elapsed });
+        }
+
+        self.current_nonce.store(pending, Ordering::SeqCst);
+        *self.gap_since.lock().unwrap() = None;
+        let heals = self.heal_count.fetch_add(1, Ordering::Relaxed) + 1;
+        log_warn!(
+            "nonce 空洞持续 {:?} 超过阈值，已自动修复: 本地 {} → {} (latest={}, 累计修复 {} 次)",
+            elapsed,
+            local,
+            pending,
+            latest,
+            heals
+        );
+        Ok(NonceReconcile::Healed {
+            missing_nonce: pending,
+            previous_local: local,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::provider::{EthereumProvider, RetryAdapter};
+    use ethers_providers::{MockProvider, Provider};
+    use std::sync::Arc;
+
+    const THRESHOLD: Duration = Duration::from_millis(50);
+
+    /// 本地 nonce 从 `local` 开始；`counts` 按调用顺序给出链上返回的交易数
+    async fn setup(local: u64, counts: &[u64]) -> (NonceService, RetryAdapter<MockProvider>) {
+        let mock = MockProvider::new();
+        // MockProvider 从队尾弹出响应
+        for count in counts.iter().rev() {
+            mock.push(U256::from(*count)).unwrap();
+        }
+        let init = MockProvider::new();
+        init.push(U256::from(local)).unwrap();
+        let service = NonceService::new(&Provider::new(init), H160::repeat_byte(0x01))
+            .await
+            .unwrap();
+        let provider = Arc::new(EthereumProvider::from_transports(vec![mock]));
+        (service, RetryAdapter::new(provider, 1, Duration::ZERO))
+    }
+
+    #[tokio::test]
+    async fn busy_sender_with_advancing_pending_is_not_healed() {
+        // 每轮 (latest, pending)：pending 一直在前移，本地始终领先
+        let (service, provider) = setup(10, &[7, 7, 7, 8, 8, 9]).await;
+        for _ in 0..3 {
+            let outcome = service.reconcile(&provider, THRESHOLD).await.unwrap();
+            assert!(matches!(outcome, NonceReconcile::GapObserved { .. }), "{outcome:?}");
+            tokio::time::sleep(THRESHOLD * 2).await;
+        }
+        assert_eq!(service.current(), 10);
+        assert_eq!(service.heal_count(), 0);
+    }
+
+    #[tokio::test]
+    async fn pending_stuck_past_threshold_is_healed() {
+        let (service, provider) = setup(10, &[8, 8, 8, 8]).await;
+        let first = service.reconcile(&provider, THRESHOLD).await.unwrap();
+        assert!(matches!(first, NonceReconcile::GapObserved { pending: 8, .. }), "{first:?}");
+        tokio::time::sleep(THRESHOLD * 2).await;
+
+        let healed = service.reconcile(&provider, THRESHOLD).await.unwrap();
+        assert_eq!(
+            healed,
+            NonceReconcile::Healed {
+                missing_nonce: 8,
+                previous_local: 10
+            }
+        );
+        assert_eq!(service.current(), 8);
+    }
+
+    #[tokio::test]
+    async fn catch_up_only_moves_forward() {
+        let (service, provider) = setup(10, &[8, 12]).await;
+        // 本地领先（有发送进行中）：不动
+        assert_eq!(service.catch_up(&provider).await.unwrap(), NonceReconcile::InSync);
+        assert_eq!(service.current(), 10);
+        // 链上领先：前移
+        assert_eq!(
+            service.catch_up(&provider).await.unwrap(),
+            NonceReconcile::Advanced { from: 10, to: 12 }
+        );
+        assert_eq!(service.current(), 12);
+    }
+}
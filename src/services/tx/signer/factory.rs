@@ -0,0 +1,83 @@
+// services/tx/signer/factory.rs
+
+use crate::config::SignerConfig;
+use crate::errors::error::AppError;
+use crate::services::tx::signer::TxSigner;
+use crate::services::tx::signer::local_signer::LocalSigner;
+use ethers_core::types::PathOrString;
+use ethers_signers::coins_bip39::English;
+use ethers_signers::{LocalWallet, MnemonicBuilder, Signer};
+use std::path::Path;
+use std::sync::Arc;
+
+/// 按配置创建签名器：切换签名后端只需修改配置
+/// 所需的环境变量 / 文件在这里校验，缺失时启动即失败，而不是等到第一笔交易
+pub struct SignerFactory;
+
+impl SignerFactory {
+    pub async fn from_config(
+        config: &SignerConfig,
+        chain_id: u64,
+    ) -> Result<Arc<dyn TxSigner>, AppError> {
+        let signer: Arc<dyn TxSigner> = match config {
+            SignerConfig::Local { private_key_env } => {
+                let key = required_env(private_key_env)?;
+                let wallet = key
+                    .trim()
+                    .parse::<LocalWallet>()
+                    .map_err(|e| AppError::Validation(format!("{} 不是有效的私钥: {}", private_key_env, e)))?;
+                Arc::new(LocalSigner::new(wallet.with_chain_id(chain_id)))
+            }
+            SignerConfig::Keystore { path, password_env } => {
+                if !Path::new(path).is_file() {
+                    return Err(AppError::Validation(format!("keystore 文件不存在: {}", path)));
+                }
+                let password = required_env(password_env)?;
+                let wallet = LocalWallet::decrypt_keystore(path, password)
+                    .map_err(|e| AppError::Validation(format!("解密 keystore {} 失败: {}", path, e)))?;
+                Arc::new(LocalSigner::new(wallet.with_chain_id(chain_id)))
+            }
+            SignerConfig::Hd {
+                mnemonic_env,
+                index,
+                derivation_path,
+            } => {
+                let phrase = required_env(mnemonic_env)?;
+                let builder = MnemonicBuilder::<English>::default()
+                    .phrase(PathOrString::String(phrase.trim().to_string()));
+                let builder = match derivation_path {
+                    Some(path) => builder.derivation_path(path),
+                    None => builder.index(*index),
+                }
+                .map_err(|e| AppError::Validation(format!("无效的派生路径: {}", e)))?;
+                let wallet = builder
+                    .build()
+                    .map_err(|e| AppError::Validation(format!("{} 不是有效的助记词: {}", mnemonic_env, e)))?;
+                Arc::new(LocalSigner::new(wallet.with_chain_id(chain_id)))
+            }
+            #[cfg(feature = "kms")]
+            SignerConfig::Kms { key_id, region } => Arc::new(
+                crate::services::tx::signer::kms_signer::KmsSigner::new(
+                    key_id,
+                    region.as_deref(),
+                    chain_id,
+                )
+                .await?,
+            ),
+            #[cfg(not(feature = "kms"))]
+            SignerConfig::Kms { .. } => {
+                return Err(AppError::Validation(
+                    "signer.backend = \"kms\" 需要启用 kms feature 编译".to_string(),
+                ));
+            }
+        };
+        Ok(signer)
+    }
+}
+
+fn required_env(name: &str) -> Result<String, AppError> {
+    std::env::var(name)
+        .ok()
+        .filter(|v| !v.trim().is_empty())
+        .ok_or_else(|| AppError::Validation(format!("签名器需要环境变量 {}", name)))
+}
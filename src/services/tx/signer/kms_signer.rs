@@ -1 +1,52 @@
-//
\ No newline at end of file
+// services/tx/signer/kms_signer.rs
+
+use crate::errors::error::AppError;
+use crate::services::tx::signer::TxSigner;
+use ethers_core::types::transaction::eip2718::TypedTransaction;
+use ethers_core::types::{H160, Signature};
+use ethers_signers::{AwsSigner, Signer};
+use rusoto_core::Region;
+use rusoto_kms::KmsClient;
+use std::sync::Arc;
+
+/// AWS KMS 签名器：私钥不出 KMS，每次签名都是一次 KMS Sign 调用
+#[derive(Clone)]
+pub struct KmsSigner {
+    signer: Arc<AwsSigner>,
+}
+
+impl KmsSigner {
+    /// 创建时会请求一次 GetPublicKey 计算地址，密钥不存在或无权限时在启动阶段就失败
+    pub async fn new(key_id: &str, region: Option<&str>, chain_id: u64) -> Result<Self, AppError> {
+        let region = match region {
+            Some(r) => r
+                .parse::<Region>()
+                .map_err(|e| AppError::Validation(format!("无效的 AWS 区域 {}: {}", r, e)))?,
+            None => Region::default(),
+        };
+        let signer = AwsSigner::new(KmsClient::new(region), key_id, chain_id)
+            .await
+            .map_err(|e| AppError::Internal(format!("初始化 KMS 签名器失败: {}", e)))?;
+        Ok(Self {
+            signer: Arc::new(signer),
+        })
+    }
+}
+
+#[async_trait::async_trait]
+impl TxSigner for KmsSigner {
+    async fn sign_tx(&self, tx: &TypedTransaction) -> Result<Signature, AppError> {
+        self.signer
+            .sign_transaction(tx)
+            .await
+            .map_err(|e| AppError::Internal(format!("KMS signing failed: {}", e)))
+    }
+
+    fn address(&self) -> H160 {
+        self.signer.address()
+    }
+
+    fn chain_id(&self) -> Option<u64> {
+        Some(self.signer.chain_id())
+    }
+}
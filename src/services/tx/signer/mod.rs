@@ -1,6 +1,12 @@
 pub mod signer_trait;
 mod local_signer;
+#[cfg(feature = "kms")]
 mod kms_signer;
 mod hsm_signer;
+mod factory;
 
-pub use signer_trait::TxSigner;
\ No newline at end of file
+pub use signer_trait::TxSigner;
+pub use local_signer::LocalSigner;
+#[cfg(feature = "kms")]
+pub use kms_signer::KmsSigner;
+pub use factory::SignerFactory;
@@ -1,15 +1,25 @@
 // services/tx/types.rs
 
+use crate::config::TxConfig;
+use crate::errors::error::AppError;
 use ethers_core::types::{Bytes, H160, H256, TransactionReceipt, U256};
 use serde::{Deserialize, Serialize};
+use crate::infrastructure::provider::ConfirmationTarget;
 use crate::services::tx::gas::gas_strategy::TxPriority;
 
 #[derive(Debug, Clone)]
 pub struct TxOptions {
     pub priority: TxPriority,
     pub gas_limit_buffer: u64,     // 百分比，例如 120 表示 +20%
-    pub confirmations: u64,        // 所需确认数
+    /// 所需确认数，None 时按 `priority.default_confirmations()` 取值
+    pub confirmations: Option<u64>,
     pub timeout_secs: u64,         // 等待超时秒数
+    /// 单次调用指定的确认策略，None 时使用服务默认值，再回退为 Blocks(confirmations)
+    pub confirmation_target: Option<ConfirmationTarget>,
+    /// 广播前校验余额是否足以支付 value + 最大 gas 费用（ERC20 转账同时校验代币余额）
+    pub check_balance: bool,
+    /// 幂等键：设置后相同 key 的重复调用返回之前的结果，不会再次发送（需配置 SentTxStore）
+    pub idempotency_key: Option<String>,
 }
 
 impl Default for TxOptions {
@@ -17,12 +27,43 @@ impl Default for TxOptions {
         Self {
             priority: TxPriority::Normal,
             gas_limit_buffer: 120,
-            confirmations: 1,
+            confirmations: None,
             timeout_secs: 300,
+            confirmation_target: None,
+            check_balance: true,
+            idempotency_key: None,
         }
     }
 }
 
+impl TxOptions {
+    /// 以 `[tx]` 配置为默认值（未传 TxOptions 的调用使用）
+    pub fn from_config(config: &TxConfig) -> Self {
+        Self {
+            priority: config.default_priority,
+            gas_limit_buffer: config.gas_limit_buffer,
+            confirmations: config.confirmations,
+            timeout_secs: config.timeout_secs,
+            confirmation_target: None,
+            check_balance: config.check_balance,
+            idempotency_key: None,
+        }
+    }
+
+    /// 最终使用的确认数：显式指定 > 按优先级的默认值
+    pub fn effective_confirmations(&self) -> u64 {
+        self.confirmations
+            .unwrap_or_else(|| self.priority.default_confirmations())
+    }
+
+    /// 解析最终使用的确认策略：单次指定 > 服务默认 > Blocks(确认数)
+    pub fn resolve_confirmation(&self, default: Option<ConfirmationTarget>) -> ConfirmationTarget {
+        self.confirmation_target
+            .or(default)
+            .unwrap_or(ConfirmationTarget::Blocks(self.effective_confirmations()))
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct TxContext {
     pub to: H160,
@@ -35,4 +76,36 @@ pub struct TxContext {
 pub struct TxResult {
     pub tx_hash: H256,
     pub receipt: TransactionReceipt,
-}
\ No newline at end of file
+}
+
+/// 批量转账结果：`results` 按发送顺序记录每个已尝试接收方的结果；
+/// 严格模式遇错停止时，未尝试的接收方放在 `skipped` 中
+#[derive(Debug, Default)]
+pub struct BatchTransferReport {
+    pub results: Vec<(H160, U256, Result<H256, AppError>)>,
+    pub skipped: Vec<(H160, U256)>,
+}
+
+impl BatchTransferReport {
+    pub fn succeeded(&self) -> usize {
+        self.results.iter().filter(|(_, _, r)| r.is_ok()).count()
+    }
+
+    pub fn failed(&self) -> usize {
+        self.results.iter().filter(|(_, _, r)| r.is_err()).count()
+    }
+
+    pub fn is_all_succeeded(&self) -> bool {
+        self.skipped.is_empty() && self.failed() == 0
+    }
+
+    /// 需要重试的接收方（失败的 + 未尝试的），可直接作为下一次批量转账的输入
+    pub fn retry_list(&self) -> Vec<(H160, U256)> {
+        self.results
+            .iter()
+            .filter(|(_, _, r)| r.is_err())
+            .map(|(to, amount, _)| (*to, *amount))
+            .chain(self.skipped.iter().copied())
+            .collect()
+    }
+}
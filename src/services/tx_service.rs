@@ -1,16 +1,23 @@
 // services/tx/tx_service.rs
+use crate::config::{SpeedUpConfig, TxConfig};
+use crate::config::filter_config::FilterConfigContainer;
 use crate::errors::error::AppError;
-use crate::infrastructure::provider::ProviderTrait;
-use crate::log_info;
+use crate::infrastructure::provider::{ConfirmationTarget, ProviderTrait};
+use crate::{log_error, log_info, log_warn};
+use crate::services::token_service::TokenService;
 use crate::services::tx::gas::gas_service::GasService;
-use crate::services::tx::nonce::nonce_service::NonceService;
+use crate::services::tx::idempotency::sent_tx_store::{SentTx, SentTxStore};
+use crate::services::tx::nonce::nonce_service::{NonceReconcile, NonceService};
 use crate::services::tx::signer::TxSigner;
 use crate::services::tx::simulation::simulation_service::SimulationService;
-use crate::services::tx::types::{TxContext, TxOptions, TxResult};
+use crate::services::tx::types::{BatchTransferReport, TxContext, TxOptions, TxResult};
 use ethers_contract::EthEvent;
 use ethers_core::abi::RawLog;
-use ethers_core::types::{Address, Eip1559TransactionRequest, TransactionReceipt, U256, transaction::eip2718::TypedTransaction, Bytes};
+use ethers_core::types::{Address, Eip1559TransactionRequest, H256, TransactionReceipt, U256, transaction::eip2718::TypedTransaction, Bytes};
 use std::sync::Arc;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tracing::{Instrument, Span, info_span, instrument};
 use ethers_core::utils::keccak256;
 
 pub struct TxService {
@@ -19,6 +26,18 @@ pub struct TxService {
     pub gas_svc: Arc<GasService>,
     pub simulation: Arc<SimulationService>,
     pub provider: Arc<dyn ProviderTrait>,
+    /// 默认确认策略（TxOptions 未指定时使用）
+    pub default_confirmation: Option<ConfirmationTarget>,
+    /// 确认超时后的加价重发策略
+    pub speed_up: SpeedUpConfig,
+    /// 调用方未传 TxOptions 时使用的默认选项
+    pub default_options: TxOptions,
+    /// idempotency_key → tx hash 记录，未设置时不支持幂等发送
+    pub sent_tx_store: Option<Arc<SentTxStore>>,
+    /// 收款白名单来源（随 FilterConfig 热重载），未设置时不限制收款地址
+    pub recipient_filter: Option<Arc<FilterConfigContainer>>,
+    /// calldata 字节数上限，超过时在模拟前拒绝
+    pub max_calldata_bytes: usize,
 }
 
 #[derive(EthEvent, Debug)]
@@ -37,6 +56,7 @@ impl TxService {
         gas_svc: Arc<GasService>,
         simulation: Arc<SimulationService>,
         provider: Arc<dyn ProviderTrait>,
+        default_confirmation: Option<ConfirmationTarget>,
     ) -> Self {
         Self {
             signer,
@@ -44,10 +64,50 @@ impl TxService {
             gas_svc,
             simulation,
             provider,
+            default_confirmation,
+            speed_up: SpeedUpConfig::default(),
+            default_options: TxOptions::default(),
+            sent_tx_store: None,
+            recipient_filter: None,
+            max_calldata_bytes: TxConfig::default().max_calldata_bytes,
         }
     }
 
+    /// 设置加价重发策略（默认不加价）
+    pub fn with_speed_up(mut self, speed_up: SpeedUpConfig) -> Self {
+        self.speed_up = speed_up;
+        self
+    }
+
+    /// 按 `[tx]` 配置设置默认选项、默认确认策略与加价重发策略
+    pub fn with_config(mut self, config: &TxConfig) -> Self {
+        self.default_options = TxOptions::from_config(config);
+        if config.confirmation_target.is_some() {
+            self.default_confirmation = config.confirmation_target;
+        }
+        if let Some(speed_up) = &config.speed_up {
+            self.speed_up = speed_up.clone();
+        }
+        self.max_calldata_bytes = config.max_calldata_bytes;
+        self
+    }
+
+    /// 启用 idempotency_key 支持
+    pub fn with_sent_tx_store(mut self, store: Arc<SentTxStore>) -> Self {
+        self.sent_tx_store = Some(store);
+        self
+    }
+
+    /// 启用收款白名单：只允许向 FilterConfig.allowed_recipients 中的地址发送
+    pub fn with_recipient_allowlist(mut self, filter: Arc<FilterConfigContainer>) -> Self {
+        self.recipient_filter = Some(filter);
+        self
+    }
+
     /// 1. 集成 ETH 原生转账
+    ///
+    /// `TxOptions.check_balance` 为 true（默认）时，签名前校验余额覆盖 value + max_fee_per_gas × gas_limit，
+    /// 不足时返回 `AppError::Validation`，而不是等节点拒绝广播
     pub async fn transfer_eth(
         &self,
         to: Address,
@@ -58,7 +118,7 @@ impl TxService {
             to,
             value: amount,
             data: Bytes::default(), // ETH 转账 data 为空
-            options: options.unwrap_or_default(),
+            options: options.unwrap_or_else(|| self.default_options.clone()),
         };
 
         log_info!("发起 ETH 转账: 目标 {:?}, 金额 {}", to, amount);
@@ -92,7 +152,7 @@ impl TxService {
             to: token_address,
             value: U256::zero(),
             data: data.into(),
-            options: options.unwrap_or_default(),
+            options: options.unwrap_or_else(|| self.default_options.clone()),
         };
 
         log_info!("正在发起 ERC20 转账: 代币 {:?}, 目标 {:?}, 金额 {}", token_address, to, amount);
@@ -102,10 +162,200 @@ impl TxService {
         self.execute(ctx).await
     }
 
+    /// 发送任意交易（合约调用 / ETH 转账），通常由 [`TxBuilder`] 构造
+    ///
+    /// 与 `transfer_eth` / `erc20_transfer` 走同一流程：模拟预执行、nonce 管理、EIP-1559 费用、签名、广播与确认
+    ///
+    /// [`TxBuilder`]: crate::services::tx::builder::TxBuilder
+    pub async fn send(&self, ctx: TxContext) -> Result<TxResult, AppError> {
+        log_info!(
+            "发起交易: 目标 {:?}, value {}, calldata {} 字节",
+            ctx.to,
+            ctx.value,
+            ctx.data.len()
+        );
+        self.execute(ctx).await
+    }
+
+    /// 按顺序发送一批任意交易，结果与输入一一对应
+    ///
+    /// 单笔失败后立即与链上 pending nonce 对账；`stop_on_error` 为 true 时遇错停止，
+    /// 返回的结果数少于输入数，未返回结果的即为未发送
+    pub async fn send_batch(
+        &self,
+        calls: Vec<TxContext>,
+        stop_on_error: bool,
+    ) -> Vec<Result<TxResult, AppError>> {
+        let total = calls.len();
+        let mut results = Vec::with_capacity(total);
+        for (i, ctx) in calls.into_iter().enumerate() {
+            let to = ctx.to;
+            let result = self.send(ctx).await;
+            let failed = result.is_err();
+            if let Err(e) = &result {
+                log_warn!("批量交易第 {} 笔失败: to={:?}, {}", i, to, e);
+                if let Err(e) = self.nonce_svc.catch_up(&*self.provider).await {
+                    log_error!("批量交易 nonce 对账失败: {}", e);
+                }
+            }
+            results.push(result);
+            if failed && stop_on_error {
+                break;
+            }
+        }
+        log_info!(
+            "批量交易结束: 成功 {}，失败 {}，未发送 {}",
+            results.iter().filter(|r| r.is_ok()).count(),
+            results.iter().filter(|r| r.is_err()).count(),
+            total - results.len()
+        );
+        results
+    }
+
+    /// 批量 ERC20 转账（顺序发送）
+    /// 发送前一次性校验：接收方不能是零地址、金额总和不溢出、代币余额足以覆盖整批，
+    /// 任一不满足直接返回 `AppError::Validation` 并指出出错的序号，避免空投只发出一部分
+    ///
+    /// 预检通过后单笔失败不会中断整批（`stop_on_error` 为 true 时遇错停止），
+    /// 每笔结果记录在 [`BatchTransferReport`] 中，调用方可用 `retry_list()` 只重试失败部分
+    pub async fn batch_erc20_transfer(
+        &self,
+        token_address: Address,
+        recipients: Vec<(Address, U256)>,
+        delay_ms: Option<u64>,
+        stop_on_error: bool,
+        options: Option<TxOptions>,
+    ) -> Result<BatchTransferReport, AppError> {
+        let mut total = U256::zero();
+        let mut cumulative = Vec::with_capacity(recipients.len());
+        for (i, (to, amount)) in recipients.iter().enumerate() {
+            if to.is_zero() {
+                return Err(AppError::Validation(format!("第 {} 笔接收方为零地址", i)));
+            }
+            if !self.is_recipient_allowed(to) {
+                return Err(AppError::Unauthorized(format!("第 {} 笔接收方 {:?} 不在白名单中", i, to)));
+            }
+            total = total
+                .checked_add(*amount)
+                .ok_or_else(|| AppError::Validation(format!("第 {} 笔累计金额溢出", i)))?;
+            cumulative.push(total);
+        }
+
+        let balance = TokenService::new(Arc::clone(&self.provider))
+            .balance_of(token_address, self.signer.address())
+            .await?;
+        if balance < total {
+            let failing = cumulative.iter().position(|sum| *sum > balance).unwrap_or_default();
+            return Err(AppError::Validation(format!(
+                "insufficient funds: 代币 {:?} 余额 {}，批量总额 {}，缺口 {}，第 {} 笔将失败",
+                token_address,
+                balance,
+                total,
+                total - balance,
+                failing
+            )));
+        }
+
+        log_info!(
+            "批量 ERC20 转账预检通过: 代币 {:?}, {} 笔, 总额 {}",
+            token_address,
+            recipients.len(),
+            total
+        );
+        let report = run_batch(recipients, delay_ms, stop_on_error, |i, to, amount| {
+            let options = options.clone();
+            async move {
+                let result = self
+                    .erc20_transfer(token_address, to, amount, options)
+                    .await
+                    .map(|r| r.tx_hash);
+                if let Err(e) = &result {
+                    log_warn!("批量转账第 {} 笔失败: to={:?}, amount={}, {}", i, to, amount, e);
+                    // 失败可能发生在广播之后（如链上 revert、确认超时），链上 pending 可能已高于本地，
+                    // 只做前移；空洞修复交给带阈值的后台对账，以免覆盖其它发送方已预占的 nonce
+                    if let Err(e) = self.nonce_svc.catch_up(&*self.provider).await {
+                        log_error!("批量转账 nonce 对账失败: {}", e);
+                    }
+                }
+                result
+            }
+        })
+        .await;
+
+        log_info!(
+            "批量 ERC20 转账结束: 成功 {}，失败 {}，未发送 {}",
+            report.succeeded(),
+            report.failed(),
+            report.skipped.len()
+        );
+        Ok(report)
+    }
+
+    /// 启动后台 nonce 对账任务，定期检测并修复 nonce 空洞
+    /// `rebroadcast_filler` 为 true 时，修复后在缺失的 nonce 上发送一笔 0 ETH 自转账，
+    /// 让内存池中排在空洞之后的交易得以继续打包
+    pub fn spawn_nonce_reconciler(
+        self: Arc<Self>,
+        interval: Duration,
+        gap_threshold: Duration,
+        rebroadcast_filler: bool,
+    ) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            loop {
+                tokio::time::sleep(interval).await;
+                match self.nonce_svc.reconcile(&*self.provider, gap_threshold).await {
+                    Ok(NonceReconcile::Healed { missing_nonce, .. }) if rebroadcast_filler => {
+                        log_warn!("在缺失的 nonce {} 上发送填充交易", missing_nonce);
+                        let own = self.signer.address();
+                        if let Err(e) = self.transfer_eth(own, U256::zero(), None).await {
+                            log_error!("nonce {} 填充交易发送失败: {}", missing_nonce, e);
+                        }
+                    }
+                    Ok(_) => {}
+                    Err(e) => log_error!("nonce 对账失败: {}", e),
+                }
+            }
+        })
+    }
 
+    #[instrument(name = "tx_execute", skip_all, fields(to = ?ctx.to, value = %ctx.value, nonce, tx_hash))]
     async fn execute(&self, ctx: TxContext) -> Result<TxResult, AppError> {
+        self.ensure_recipient_allowed(&ctx)?;
+        ensure_calldata_size(&ctx, self.max_calldata_bytes)?;
+
+        let Some(key) = ctx.options.idempotency_key.clone() else {
+            return self.sign_and_send(&ctx, None).await;
+        };
+        let store = self.sent_tx_store.as_deref().ok_or_else(|| {
+            AppError::InvalidRequest("未配置 SentTxStore，无法使用 idempotency_key".to_string())
+        })?;
+
+        // 0. 幂等校验：相同 key 已发送过则返回之前的结果
+        if let Some(prior) = store.reserve(&key).await? {
+            log_info!("idempotency_key {} 已存在发送记录: {:?}", key, prior);
+            return self.prior_result(&key, prior).await;
+        }
+
+        let result = self.sign_and_send(&ctx, Some((store, key.as_str()))).await;
+        if result.is_err() {
+            // 广播前失败：释放 key，允许调用方重试
+            if let Err(e) = store.release_if_reserved(&key).await {
+                log_error!("释放 idempotency_key {} 失败: {}", key, e);
+            }
+        }
+        result
+    }
+
+    async fn sign_and_send(
+        &self,
+        ctx: &TxContext,
+        idempotency: Option<(&SentTxStore, &str)>,
+    ) -> Result<TxResult, AppError> {
         // 1. 预执行模拟
-        self.simulation.run(&ctx, &*self.provider).await?;
+        self.simulation
+            .run(ctx, &*self.provider)
+            .instrument(info_span!("tx_simulate"))
+            .await?;
 
         // 2. 获取动态费用
         let (max_fee_per_gas, priority_fee_per_gas) = self
@@ -115,12 +365,13 @@ impl TxService {
 
         // 3. 预占 nonce
         let nonce = self.nonce_svc.acquire();
+        Span::current().record("nonce", nonce);
 
         // 4. 构建交易
         let mut tx_req = Eip1559TransactionRequest::new()
             .to(ctx.to)
             .value(ctx.value)
-            .data(ctx.data)
+            .data(ctx.data.clone())
             .max_fee_per_gas(max_fee_per_gas)
             .max_priority_fee_per_gas(priority_fee_per_gas)
             .nonce(nonce);
@@ -142,28 +393,82 @@ impl TxService {
         let gas_limit = estimated_gas * ctx.options.gas_limit_buffer / 100;
         tx_req = tx_req.gas(gas_limit);
 
-        // 6. 签名
-        let typed_tx: TypedTransaction = tx_req.into();
-        let signature = self.signer.sign_tx(&typed_tx).await.map_err(|e| {
-            self.nonce_svc.rollback();
-            e
-        })?;
+        // 5.1 余额校验：余额不足时直接返回，不再签名广播
+        if ctx.options.check_balance {
+            self.ensure_sufficient_balance(ctx, gas_limit * max_fee_per_gas)
+                .await
+                .inspect_err(|_| self.nonce_svc.rollback())?;
+        }
 
-        let signed_rlp = typed_tx.rlp_signed(&signature);
+        // 6. 签名并广播；确认超时时以相同 nonce 加价重发，直到达到次数或费用上限
+        let mut attempt = 0;
+        let mut prev_hash = None;
+        let receipt_tx = loop {
+            let typed_tx: TypedTransaction = tx_req.clone().into();
+            let signature = self
+                .signer
+                .sign_tx(&typed_tx)
+                .instrument(info_span!("tx_sign"))
+                .await
+                .inspect_err(|_| {
+                    // 加价前的交易可能已在 mempool 中，nonce 不能回滚
+                    if attempt == 0 {
+                        self.nonce_svc.rollback();
+                    }
+                })?;
 
-        // 7. 广播
-        let receipt_tx = self
-            .provider
-            .send_raw_transaction(
-                signed_rlp,
-                ctx.options.timeout_secs,
-                ctx.options.confirmations as usize,
-            )
-            .await
-            .map_err(|e| {
-                self.nonce_svc.rollback();
-                e
-            })?;
+            let signed_rlp = typed_tx.rlp_signed(&signature);
+            let tx_hash = typed_tx.hash(&signature);
+            Span::current().record("tx_hash", tracing::field::debug(tx_hash));
+
+            // 广播前记录哈希，调用方重试时据此返回结果而不是重新发送
+            if let Some((store, key)) = idempotency {
+                store.record(key, tx_hash).await.inspect_err(|_| {
+                    if attempt == 0 {
+                        self.nonce_svc.rollback();
+                    }
+                })?;
+            }
+
+            // 7. 广播
+            let sent = self
+                .provider
+                .send_raw_transaction(
+                    signed_rlp,
+                    ctx.options.timeout_secs,
+                    ctx.options.resolve_confirmation(self.default_confirmation),
+                )
+                .instrument(info_span!("tx_broadcast_and_confirm"))
+                .await;
+
+            match sent {
+                Ok(receipt) => break receipt,
+                Err(AppError::ConfirmationTimeout(hash)) => {
+                    prev_hash = Some(tx_hash);
+                    tx_req = self.bump_fees(tx_req, attempt, &hash)?;
+                    attempt += 1;
+                }
+                Err(AppError::BroadcastFailed(reason)) => {
+                    if attempt == 0 {
+                        self.nonce_svc.rollback();
+                    }
+                    if let Some((store, key)) = idempotency {
+                        // 首次广播失败时交易不在链上，释放 key；加价失败时恢复为上一笔已广播的哈希
+                        let restored = match prev_hash {
+                            None => store.release(key).await,
+                            Some(hash) => store.record(key, hash).await,
+                        };
+                        if let Err(e) = restored {
+                            log_error!("更新 idempotency_key {} 失败: {}", key, e);
+                        }
+                    }
+                    return Err(AppError::BroadcastFailed(reason));
+                }
+                // 已广播之后的失败（revert、等待回执出错、被 mempool 丢弃）：交易可能已上链，
+                // 保留记录的哈希与已消耗的 nonce，调用方用相同 key 重试时返回该交易的结果而不是重发
+                Err(e) => return Err(e),
+            }
+        };
 
         // 解析所有的 Transfer 事件
         let transfers: Vec<TransferEvent> = parse_logs_from_receipt(&receipt_tx);
@@ -182,6 +487,145 @@ impl TxService {
     }
 }
 
+impl TxService {
+    /// 收款地址是否允许；签名地址自身（如 nonce 填充交易）始终允许
+    fn is_recipient_allowed(&self, to: &Address) -> bool {
+        *to == self.signer.address()
+            || self
+                .recipient_filter
+                .as_ref()
+                .is_none_or(|filter| filter.load().is_recipient_allowed(to))
+    }
+
+    /// 校验收款地址白名单；ERC20 transfer 的收款方在 calldata 中，ctx.to 只是代币合约
+    fn ensure_recipient_allowed(&self, ctx: &TxContext) -> Result<(), AppError> {
+        let recipient = erc20_transfer_args(&ctx.data)
+            .map(|(to, _)| to)
+            .unwrap_or(ctx.to);
+        if !self.is_recipient_allowed(&recipient) {
+            log_warn!("拒绝向白名单外地址发送: {:?}", recipient);
+            return Err(AppError::Unauthorized(format!(
+                "收款地址 {:?} 不在白名单中",
+                recipient
+            )));
+        }
+        Ok(())
+    }
+
+    /// 相同 idempotency_key 的重复调用：已确认返回原回执，未确认返回 TxPending（携带交易哈希）
+    async fn prior_result(&self, key: &str, prior: SentTx) -> Result<TxResult, AppError> {
+        let tx_hash = match prior {
+            SentTx::Reserved => {
+                return Err(AppError::Conflict(format!(
+                    "idempotency_key {} 对应的交易正在发送",
+                    key
+                )));
+            }
+            SentTx::Sent(hash) => hash,
+        };
+        let Some(receipt) = self.provider.get_transaction_receipt(tx_hash).await? else {
+            return Err(AppError::TxPending(format!("{:?}", tx_hash)));
+        };
+        if receipt.status == Some(0.into()) {
+            return Err(AppError::Internal(format!(
+                "Transaction reverted on-chain. Hash: {:?}",
+                tx_hash
+            )));
+        }
+        Ok(TxResult { tx_hash, receipt })
+    }
+
+    /// 计算加价后的交易；已达到次数或费用上限时返回错误，已广播的交易保持原样（nonce 不回滚）
+    fn bump_fees(
+        &self,
+        tx_req: Eip1559TransactionRequest,
+        attempt: u32,
+        tx_hash: &str,
+    ) -> Result<Eip1559TransactionRequest, AppError> {
+        let old_max_fee = tx_req.max_fee_per_gas.unwrap_or_default();
+        let old_priority_fee = tx_req.max_priority_fee_per_gas.unwrap_or_default();
+        let new_max_fee = bump(old_max_fee, self.speed_up.bump_percent);
+        let new_priority_fee = bump(old_priority_fee, self.speed_up.bump_percent);
+        let ceiling = U256::from(self.speed_up.max_bump_gwei) * U256::exp10(9);
+
+        if attempt >= self.speed_up.max_speed_up_attempts || new_max_fee > ceiling {
+            log_warn!(
+                "交易 {} 确认超时，停止加价: 已加价 {} 次（上限 {}），当前 max_fee={}，下一次 max_fee={}，上限={}",
+                tx_hash,
+                attempt,
+                self.speed_up.max_speed_up_attempts,
+                old_max_fee,
+                new_max_fee,
+                ceiling
+            );
+            return Err(AppError::Internal("fee bump ceiling reached".to_string()));
+        }
+
+        log_info!(
+            "交易 {} 确认超时，第 {} 次加价重发: max_fee {} → {}, priority_fee {} → {}",
+            tx_hash,
+            attempt + 1,
+            old_max_fee,
+            new_max_fee,
+            old_priority_fee,
+            new_priority_fee
+        );
+        Ok(tx_req
+            .max_fee_per_gas(new_max_fee)
+            .max_priority_fee_per_gas(new_priority_fee))
+    }
+
+    /// 校验钱包余额：原生余额需覆盖 value + 最大 gas 费用；
+    /// data 为 ERC20 transfer(address,uint256) 时，还需代币余额覆盖转账金额
+    async fn ensure_sufficient_balance(&self, ctx: &TxContext, max_gas_cost: U256) -> Result<(), AppError> {
+        let owner = self.signer.address();
+
+        let required = ctx.value.saturating_add(max_gas_cost);
+        let balance = self.provider.get_balance(owner).await?;
+        if balance < required {
+            return Err(AppError::Validation(format!(
+                "insufficient funds: 需要 {} wei（value {} + 最大 gas 费用 {}），余额 {}，缺口 {}",
+                required,
+                ctx.value,
+                max_gas_cost,
+                balance,
+                required - balance
+            )));
+        }
+
+        if let Some((_, amount)) = erc20_transfer_args(&ctx.data) {
+            let token_balance = self.erc20_balance_of(ctx.to, owner).await?;
+            if token_balance < amount {
+                return Err(AppError::Validation(format!(
+                    "insufficient funds: 代币 {:?} 需要 {}，余额 {}，缺口 {}",
+                    ctx.to,
+                    amount,
+                    token_balance,
+                    amount - token_balance
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    async fn erc20_balance_of(&self, token: Address, owner: Address) -> Result<U256, AppError> {
+        TokenService::new(Arc::clone(&self.provider))
+            .balance_of(token, owner)
+            .await
+    }
+}
+
+/// 从 calldata 中解析 ERC20 transfer(address,uint256) 的收款方与金额，非 transfer 调用返回 None
+fn erc20_transfer_args(data: &Bytes) -> Option<(Address, U256)> {
+    let selector = &keccak256("transfer(address,uint256)")[..4];
+    (data.len() == 68 && &data[..4] == selector).then(|| {
+        (
+            Address::from_slice(&data[16..36]),
+            U256::from_big_endian(&data[36..68]),
+        )
+    })
+}
+
 /// 通用解析函数：从 Receipt 中提取特定的事件
 pub fn parse_logs_from_receipt<T: EthEvent>(receipt: &TransactionReceipt) -> Vec<T> {
     receipt
@@ -198,3 +642,158 @@ pub fn parse_logs_from_receipt<T: EthEvent>(receipt: &TransactionReceipt) -> Vec
         })
         .collect()
 }
+
+/// 按百分比加价，至少加 1 wei，保证替换交易的费用严格更高
+fn bump(fee: U256, percent: u64) -> U256 {
+    let bumped = fee.saturating_mul(U256::from(percent)) / 100;
+    bumped.max(fee.saturating_add(U256::one()))
+}
+
+/// 校验 calldata 大小：超过上限的交易会被节点拒绝，在模拟前直接返回
+fn ensure_calldata_size(ctx: &TxContext, max_calldata_bytes: usize) -> Result<(), AppError> {
+    if ctx.data.len() > max_calldata_bytes {
+        log_warn!(
+            "拒绝发送超大交易: to={:?}, calldata {} 字节，上限 {} 字节",
+            ctx.to,
+            ctx.data.len(),
+            max_calldata_bytes
+        );
+        return Err(AppError::Validation(format!(
+            "calldata 大小 {} 字节超过上限 {} 字节",
+            ctx.data.len(),
+            max_calldata_bytes
+        )));
+    }
+    Ok(())
+}
+
+/// 按顺序逐笔调用 `send` 并收集结果：单笔失败继续发送下一笔，`stop_on_error` 为 true 时遇错停止，
+/// 剩余接收方记入 `skipped`；每笔之后按 `delay_ms` 等待
+async fn run_batch<F, Fut>(
+    recipients: Vec<(Address, U256)>,
+    delay_ms: Option<u64>,
+    stop_on_error: bool,
+    mut send: F,
+) -> BatchTransferReport
+where
+    F: FnMut(usize, Address, U256) -> Fut,
+    Fut: std::future::Future<Output = Result<H256, AppError>>,
+{
+    let mut report = BatchTransferReport::default();
+    let mut pending = recipients.into_iter().enumerate();
+    while let Some((i, (to, amount))) = pending.next() {
+        let result = send(i, to, amount).await;
+        let failed = result.is_err();
+        report.results.push((to, amount, result));
+
+        if failed && stop_on_error {
+            report.skipped = pending.map(|(_, item)| item).collect();
+            break;
+        }
+        if let Some(ms) = delay_ms {
+            tokio::time::sleep(Duration::from_millis(ms)).await;
+        }
+    }
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers_core::abi::{Token, encode};
+
+    /// disperseToken(address,address[],uint256[]) 风格的批量转账 calldata
+    fn disperse_ctx(recipients: usize) -> TxContext {
+        let addresses = (0..recipients)
+            .map(|i| Token::Address(Address::from_low_u64_be(i as u64 + 1)))
+            .collect();
+        let amounts = (0..recipients).map(|_| Token::Uint(U256::one())).collect();
+        let mut data = keccak256("disperseToken(address,address[],uint256[])")[..4].to_vec();
+        data.extend(encode(&[
+            Token::Address(Address::repeat_byte(0xaa)),
+            Token::Array(addresses),
+            Token::Array(amounts),
+        ]));
+        TxContext {
+            to: Address::repeat_byte(0xdd),
+            value: U256::zero(),
+            data: Bytes::from(data),
+            options: TxOptions::default(),
+        }
+    }
+
+    #[test]
+    fn oversized_recipient_list_is_rejected_before_simulation() {
+        let max = TxConfig::default().max_calldata_bytes;
+        // 每个接收方占 64 字节（地址 + 金额），超出上限所需的数量再多一个
+        let ctx = disperse_ctx(max / 64 + 1);
+        assert!(ctx.data.len() > max);
+        assert!(matches!(
+            ensure_calldata_size(&ctx, max),
+            Err(AppError::Validation(_))
+        ));
+    }
+
+    #[test]
+    fn calldata_within_limit_is_accepted() {
+        let ctx = disperse_ctx(3);
+        assert!(ensure_calldata_size(&ctx, ctx.data.len()).is_ok());
+        assert!(matches!(
+            ensure_calldata_size(&ctx, ctx.data.len() - 1),
+            Err(AppError::Validation(_))
+        ));
+    }
+
+    fn recipients(n: u64) -> Vec<(Address, U256)> {
+        (1..=n).map(|i| (Address::from_low_u64_be(i), U256::from(i * 10))).collect()
+    }
+
+    /// 第 `failing` 笔（从 0 计）失败，其余返回以序号构造的哈希
+    async fn fake_send(failing: &[usize], i: usize) -> Result<H256, AppError> {
+        if failing.contains(&i) {
+            Err(AppError::ProviderError(format!("第 {} 笔 revert", i)))
+        } else {
+            Ok(H256::from_low_u64_be(i as u64))
+        }
+    }
+
+    #[tokio::test]
+    async fn batch_continues_past_failures() {
+        let mut sent = Vec::new();
+        let report = run_batch(recipients(4), None, false, |i, _, _| {
+            sent.push(i);
+            fake_send(&[1, 2], i)
+        })
+        .await;
+
+        assert_eq!(sent, vec![0, 1, 2, 3]);
+        assert_eq!((report.succeeded(), report.failed()), (2, 2));
+        assert!(report.skipped.is_empty());
+        assert!(!report.is_all_succeeded());
+        assert_eq!(report.results[3].2.as_ref().unwrap(), &H256::from_low_u64_be(3));
+        // 只需重试失败的两笔
+        assert_eq!(report.retry_list(), recipients(4)[1..3].to_vec());
+    }
+
+    #[tokio::test]
+    async fn strict_batch_stops_and_reports_unsent_recipients() {
+        let mut sent = Vec::new();
+        let report = run_batch(recipients(4), None, true, |i, _, _| {
+            sent.push(i);
+            fake_send(&[1], i)
+        })
+        .await;
+
+        assert_eq!(sent, vec![0, 1]);
+        assert_eq!((report.succeeded(), report.failed()), (1, 1));
+        assert_eq!(report.skipped, recipients(4)[2..].to_vec());
+        assert_eq!(report.retry_list(), recipients(4)[1..].to_vec());
+    }
+
+    #[tokio::test]
+    async fn batch_without_failures_is_all_succeeded() {
+        let report = run_batch(recipients(3), Some(1), true, |i, _, _| fake_send(&[], i)).await;
+        assert!(report.is_all_succeeded());
+        assert!(report.retry_list().is_empty());
+    }
+}
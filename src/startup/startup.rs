@@ -1,87 +1,434 @@
+use ethers_providers::JsonRpcClient;
+use redis::aio::ConnectionManager;
+use std::future::Future;
 use std::sync::Arc;
 use std::time::Duration;
+use tokio::sync::Semaphore;
 use tracing::info;
 
-use crate::config::Config;
+use crate::api::{self, ApiState};
+use crate::config::{BlockField, Config, EthereumConfig, ServerConfig};
 use crate::config::filter_config::{FilterConfig, FilterConfigContainer};
-use crate::database::diesel::{DbService, create_async_db_pool};
+use crate::database::diesel::{DbService, create_async_db_pool, run_migrations};
+use crate::database::redis::create_redis_pool;
 use crate::errors::error::AppError;
 use crate::infrastructure::parser::EventParser;
 use crate::infrastructure::provider::ethereum_provider::EthereumProvider;
-use crate::infrastructure::provider::{ProviderTrait, RetryAdapter};
-use crate::log_info;
+use crate::infrastructure::provider::capabilities::spawn_capability_probe;
+use crate::infrastructure::provider::{BlockCache, ProviderTrait, RetryAdapter};
+use crate::{log_info, log_warn};
 use crate::repositories::block_repository::BlockRepository;
 use crate::repositories::transaction_repository::TransactionRepository;
-use crate::services::BlockService;
+use crate::repositories::block_queue_repository::BlockQueueRepository;
+use crate::infrastructure::alert::{Alerter, LogAlerter, WebhookAlerter};
+use crate::services::tx::signer::{SignerFactory, TxSigner};
+use crate::services::{
+    AlertMonitor, BlockQueueService, BlockService, ConsistencyChecker, EthereumService,
+    TransferStatusPromoter,
+};
 
-/// 应用程序启动与管理结构体（仅后台服务，无HTTP API）
+/// 应用程序启动与管理结构体（后台同步服务 + 可选的只读 HTTP 查询接口）
 pub struct Application {
-    pub block_service: Arc<BlockService>,
+    pub service: Arc<EthereumService>,
+    pub server_config: ServerConfig,
+    pub api_state: ApiState,
+    /// 开启持久化区块队列时替代单循环同步
+    pub block_queue: Option<Arc<BlockQueueService>>,
+    /// 开启告警时的阈值监控
+    pub alert_monitor: Option<Arc<AlertMonitor>>,
+    /// 开启时的跨节点一致性抽样校验
+    pub consistency_checker: Option<Arc<ConsistencyChecker>>,
+    /// 开启两阶段转账状态时的确认状态提升任务
+    pub status_promoter: Option<Arc<TransferStatusPromoter>>,
+    /// 按 `ethereum.signer` 创建的签名器，未配置时为 None
+    pub signer: Option<Arc<dyn TxSigner>>,
+    /// Redis 连接；`redis.required = false` 且初始化失败时为 None，依赖 Redis 的功能按未启用处理
+    pub redis: Option<ConnectionManager>,
 }
 pub type Result<T> = std::result::Result<T, AppError>;
 impl Application {
     /// 构建应用实例（仅初始化数据库/Redis，不启动服务）
     pub async fn build(config: Config) -> Result<Self> {
         //初始化带监听功能的配置容器
-        let filter_container = Arc::new(FilterConfigContainer::new());
+        let filter_container = FilterConfigContainer::new();
+        let startup_timeout = Duration::from_secs(config.startup_timeout_secs);
+
+        // 建表 / 升级表结构，须在同步开始前完成
+        if config.database.run_migrations {
+            let applied =
+                startup_step(startup_timeout, "数据库迁移", run_migrations(&config.database)).await?;
+            info!("Database migrations finished, {} applied", applied);
+        }
 
         // 初始化异步池
-        let db_pool = create_async_db_pool(&config.database).await?;
-        let db_service = Arc::new(DbService { pool: db_pool });
+        let db_pool = startup_step(
+            startup_timeout,
+            "创建数据库连接池",
+            create_async_db_pool(&config.database, config.database.max_connections),
+        )
+        .await?;
+        let mut db_service =
+            DbService::new(db_pool).with_commit_retry(config.database.commit_retry.clone());
+        if let Some(query_max) = config.database.query_max_connections {
+            let query_pool = startup_step(
+                startup_timeout,
+                "创建查询连接池",
+                create_async_db_pool(&config.database, query_max),
+            )
+            .await?;
+            db_service = db_service.with_query_pool(query_pool);
+            info!("Query pool initialized separately, max_connections={}", query_max);
+        }
+        startup_step(startup_timeout, "数据库连接检查", db_service.health()).await?;
+        let db_service = Arc::new(db_service);
         info!("Diesel database pool initialized successfully");
+        // Redis 只服务于可选功能，非必需时连接失败（含超时）不影响索引
+        let redis = match startup_step(
+            startup_timeout,
+            "Redis 连接与 PING",
+            create_redis_pool(&config.redis),
+        )
+        .await
+        {
+            Ok(manager) => Some(manager),
+            Err(e) if !config.redis.required => {
+                log_warn!("Redis 不可用，已禁用依赖 Redis 的功能: {}", e);
+                None
+            }
+            Err(e) => return Err(e),
+        };
+
         // 实例化 Repository (现在是无状态的)
         let block_repo = Arc::new(BlockRepository::new());
-        let tx_repo = Arc::new(TransactionRepository::new());
+        let tx_repo = Arc::new(
+            TransactionRepository::new()
+                .with_amount_precision(config.database.amount_precision.clone()),
+        );
+
+        let eth_config = Arc::new(config.ethereum);
+
+        // 签名后端在启动时创建，缺少密钥 / 文件时立即失败
+        let signer = match &config.tx.signer {
+            Some(signer_config) => {
+                let signer = startup_step(
+                    startup_timeout,
+                    "初始化签名器",
+                    SignerFactory::from_config(signer_config, eth_config.chain_id),
+                )
+                .await?;
+                info!("Signer initialized, address {:?}", signer.address());
+                Some(signer)
+            }
+            None => None,
+        };
+
+        if eth_config.block_fields.contains(&BlockField::Raw) {
+            log_warn!("已开启原始区块 JSON 存储（eth_block_raw），每个区块额外保存完整 JSON，存储量将大幅增加");
+        }
 
-        // 1. 先初始化 Provider
-        let eth_provider = Arc::new(EthereumProvider::new(&config.ethereum));
+        // 1. 先初始化 Provider（IPC 优先，失败回退 HTTP）
+        let provider =
+            startup_step(startup_timeout, "连接 RPC Provider", build_provider(&eth_config)).await?;
 
-        let provider = Arc::new(RetryAdapter::new(
-            eth_provider,
-            config.ethereum.max_retries,
-            Duration::from_secs(config.ethereum.base_delay_secs),
-        )) as Arc<dyn ProviderTrait>;
+        // 节点 chain_id 与配置不一致时拒绝启动，避免数据和签名落到错误的链上
+        let chain_id =
+            startup_step(startup_timeout, "获取节点 chain_id", provider.get_chain_id()).await?;
+        if chain_id != eth_config.chain_id.into() {
+            return Err(AppError::Validation(format!(
+                "节点 chain_id {} 与配置的 ethereum.chain_id {} 不一致",
+                chain_id, eth_config.chain_id
+            )));
+        }
+
+        // 探测节点可选能力（eth_getBlockReceipts / finalized 等），并按配置定期重新探测；
+        // 探测只影响可选能力，超时不阻止启动
+        if tokio::time::timeout(startup_timeout, provider.probe_capabilities())
+            .await
+            .is_err()
+        {
+            log_warn!("节点能力探测超过 {:?} 未完成，未探测的能力按支持处理", startup_timeout);
+        }
+        if eth_config.capability_probe_interval_secs > 0 {
+            spawn_capability_probe(
+                Arc::clone(&provider),
+                Duration::from_secs(eth_config.capability_probe_interval_secs),
+            );
+        }
 
         // 2. 将 provider 注入 EventParser
-        let event_parser = Arc::new(EventParser::new(provider.clone()));
+        let event_parser = Arc::new(EventParser::new(provider.clone(), Arc::clone(&eth_config)));
 
         // 3. 实例化 BlockService
+        let queue_config = eth_config.block_queue.clone();
+        let consistency_config = eth_config.consistency_check.clone();
+        let status_config = eth_config.transfer_status.clone();
+        let queue_mode = queue_config.enabled;
         let block_service = Arc::new(BlockService::new(
-            Arc::new(config.ethereum),
+            eth_config,
             Arc::clone(&filter_container),
+            Arc::clone(&block_repo),
+            Arc::clone(&tx_repo),
+            Arc::clone(&db_service),
+            Arc::clone(&provider),
+            event_parser,
+        ));
+
+        let api_state = ApiState {
+            db_service: Arc::clone(&db_service),
+            block_repository: Arc::clone(&block_repo),
+            transaction_repository: Arc::clone(&tx_repo),
+            provider: Arc::clone(&provider),
+            last_synced: Arc::clone(&block_service.last_synced),
+            max_ready_lag_blocks: config.server.max_ready_lag_blocks,
+        };
+
+        let block_queue = queue_config.enabled.then(|| {
+            Arc::new(BlockQueueService::new(
+                Arc::clone(&block_service),
+                Arc::new(BlockQueueRepository::new()),
+                Arc::clone(&db_service),
+                queue_config,
+            ))
+        });
+
+        let consistency_checker = consistency_config.enabled.then(|| {
+            Arc::new(ConsistencyChecker::new(
+                Arc::clone(&block_service),
+                consistency_config,
+                queue_mode,
+            ))
+        });
+
+        let status_promoter = status_config.enabled.then(|| {
+            Arc::new(TransferStatusPromoter::new(
+                Arc::clone(&block_service),
+                status_config,
+            ))
+        });
+
+        let alert_monitor = config.alert.enabled.then(|| {
+            let mut alerters: Vec<Arc<dyn Alerter>> = vec![Arc::new(LogAlerter)];
+            if let Some(url) = &config.alert.webhook_url {
+                alerters.push(Arc::new(WebhookAlerter::new(url.clone())));
+            }
+            Arc::new(AlertMonitor::new(
+                config.alert.clone(),
+                Arc::clone(&provider),
+                Arc::clone(&block_service),
+                alerters,
+            ))
+        });
+
+        // 4. 统一服务入口
+        let service = Arc::new(EthereumService::new(
+            provider,
+            db_service,
+            filter_container,
             block_repo,
             tx_repo,
-            db_service,
-            provider,
-            event_parser,
+            block_service,
+            None,
         ));
-        Ok(Self { block_service })
+        Ok(Self {
+            service,
+            server_config: config.server,
+            api_state,
+            block_queue,
+            alert_monitor,
+            consistency_checker,
+            status_promoter,
+            signer,
+            redis,
+        })
     }
 
     /// 启动应用核心服务（例如：区块同步循环）
     pub async fn run(self) -> anyhow::Result<()> {
-        let s1 = self.block_service.clone();
-        tokio::join!(async move {
+        // 只读查询接口（可选）
+        if self.server_config.enabled {
+            let server_config = self.server_config.clone();
+            let api_state = self.api_state.clone();
+            tokio::spawn(async move {
+                if let Err(e) = api::serve(&server_config, api_state).await {
+                    tracing::error!("查询接口异常退出: {:?}", e);
+                }
+            });
+        }
+
+        if let Some(alert_monitor) = self.alert_monitor.clone() {
+            alert_monitor.spawn();
+        }
+        if let Some(checker) = self.consistency_checker.clone() {
+            checker.spawn();
+        }
+        if let Some(promoter) = self.status_promoter.clone() {
+            promoter.spawn();
+        }
+
+        // 启动校验：回滚重组窗口内的孤块，避免在孤立链头上继续同步
+        if self.service.block_service.config.verify_on_startup {
+            self.service.block_service.rewind_to_last_valid().await?;
+        }
+        // 解析器版本升级：按配置重新处理最近的区块
+        self.service.block_service.reprocess_on_version_bump().await?;
+
+        let block_service = Arc::clone(&self.service.block_service);
+
+        let tracking = &block_service.config.deployment_tracking;
+        if tracking.enabled || !tracking.factories.is_empty() {
+            let restored = block_service.restore_deployed_contracts().await?;
+            if restored > 0 {
+                log_info!("已恢复工厂部署的监控合约 {} 个", restored);
+            }
+        }
+
+        let restored = block_service.restore_held_transfers().await?;
+        if restored > 0 {
+            log_info!("已恢复按合约确认数暂缓的转账 {} 笔", restored);
+        }
+
+        // 队列模式：生产者 + 多 worker，替代下面的单循环同步
+        if let Some(block_queue) = self.block_queue.clone() {
+            let handles = block_queue.spawn();
+            log_info!("✔️ Block queue workers started");
+            tokio::select! {
+                _ = wait_for_stop_height(&block_service) => {
+                    handles.iter().for_each(|h| h.abort());
+                    log_stop_completed(&block_service);
+                    return Ok(());
+                }
+                res = tokio::signal::ctrl_c() => res?,
+            }
+            log_info!("⚠️  Received shutdown signal, exiting...");
+            return Ok(());
+        }
+
+        let s1 = self.service.clone();
+        let poll_interval = Duration::from_millis(block_service.config.poll_interval_ms);
+        let sync_loop = async move {
             loop {
                 match s1.sync_blocks().await {
                     Ok(()) => {
-                        // 区块同步成功，立即尝试同步下一个
-                        // tokio::time::sleep(Duration::from_secs(1)).await;
+                        // 到达 stop_at_block：每个区块都已在各自事务中提交，直接结束
+                        if s1.block_service.stop_reached() {
+                            break;
+                        }
+                        if s1.block_service.is_idle() {
+                            // 已追上安全高度：按轮询间隔检查链头
+                            tokio::time::sleep(poll_interval).await;
+                        } else {
+                            // 本轮可能因预算用尽提前返回，让出调度以便退出信号与其他任务及时执行
+                            tokio::task::yield_now().await;
+                        }
                     }
                     Err(e) => {
-                        tracing::error!("同步区块失败: {:?}", e);
+                        tracing::error!("同步区块失败: {:#}", e);
                         // 失败后等待一段时间后重试，避免高速失败
                         tokio::time::sleep(Duration::from_secs(1)).await;
                     }
                 }
             }
-        },);
+        };
 
         log_info!("✔️ All parsing tasks started");
 
-        // 等待 Ctrl+C 退出
-        tokio::signal::ctrl_c().await?;
+        // 等待同步到 stop_at_block 或 Ctrl+C 退出
+        tokio::select! {
+            _ = sync_loop => {
+                log_stop_completed(&block_service);
+                return Ok(());
+            }
+            res = tokio::signal::ctrl_c() => res?,
+        }
         log_info!("⚠️  Received shutdown signal, exiting...");
         Ok(())
     }
 }
+
+/// 为启动阶段的网络步骤加上超时，超时返回指明步骤的 `AppError::StartupTimeout`
+async fn startup_step<T>(
+    limit: Duration,
+    step: &str,
+    fut: impl Future<Output = Result<T>>,
+) -> Result<T> {
+    tokio::time::timeout(limit, fut)
+        .await
+        .map_err(|_| AppError::StartupTimeout {
+            step: step.to_string(),
+            secs: limit.as_secs(),
+        })?
+}
+
+/// 队列模式下轮询是否已同步到 stop_at_block；未配置时永不返回
+async fn wait_for_stop_height(block_service: &BlockService) {
+    if block_service.config.stop_at_block.is_none() {
+        return std::future::pending().await;
+    }
+    while !block_service.stop_reached() {
+        tokio::time::sleep(Duration::from_secs(1)).await;
+    }
+}
+
+fn log_stop_completed(block_service: &BlockService) {
+    log_info!(
+        "✅ 已同步到 stop_at_block {}（init_height {}），正常退出",
+        block_service.config.stop_at_block.unwrap_or_default(),
+        block_service.config.init_height
+    );
+}
+
+/// 包装 RetryAdapter，并按配置挂载历史区块缓存
+fn with_retry<P: JsonRpcClient + 'static>(
+    eth_provider: Arc<EthereumProvider<P>>,
+    config: &EthereumConfig,
+) -> Arc<dyn ProviderTrait> {
+    let mut retry_adapter = RetryAdapter::new(
+        eth_provider,
+        config.max_retries,
+        Duration::from_secs(config.base_delay_secs),
+    )
+    .with_rate_limit(config.rate_limit.clone())
+    .with_receipt_concurrency(config.receipt_concurrency);
+    if config.rpc_concurrency > 0 {
+        retry_adapter = retry_adapter
+            .with_concurrency_budget(Arc::new(Semaphore::new(config.rpc_concurrency)));
+        log_info!("已启用 RPC 并发预算: {}", config.rpc_concurrency);
+    }
+    if config.singleflight_reads {
+        retry_adapter = retry_adapter.with_singleflight();
+    }
+    if config.block_cache.enabled {
+        retry_adapter =
+            retry_adapter.with_block_cache(Arc::new(BlockCache::new(&config.block_cache)));
+        log_info!(
+            "已启用历史区块缓存: capacity={}, finality_depth={}",
+            config.block_cache.capacity,
+            config.block_cache.finality_depth
+        );
+    }
+    Arc::new(retry_adapter)
+}
+
+/// 构建 Provider：配置了 IPC 端点时优先连接 IPC，连接失败则回退到 HTTP
+async fn build_provider(config: &EthereumConfig) -> Result<Arc<dyn ProviderTrait>> {
+    if let Some(ipc_path) = config.ipc_endpoint() {
+        #[cfg(feature = "ipc")]
+        match EthereumProvider::connect_ipc(ipc_path).await {
+            Ok(ipc_provider) => {
+                let ipc_provider = ipc_provider.with_slow_call_threshold(config.slow_rpc_threshold_ms);
+                return Ok(with_retry(Arc::new(ipc_provider), config));
+            }
+            Err(e) => log_warn!("{}，回退到 HTTP Provider", e),
+        }
+        #[cfg(not(feature = "ipc"))]
+        log_warn!("配置了 IPC 端点 {}，但未启用 ipc feature，使用 HTTP Provider", ipc_path);
+
+        if config.rpc_url == ipc_path {
+            return Err(AppError::InvalidUrl(format!(
+                "IPC 不可用且 rpc_url ({}) 不是 HTTP 地址，无法回退",
+                ipc_path
+            )));
+        }
+    }
+    Ok(with_retry(Arc::new(EthereumProvider::new(config)), config))
+}
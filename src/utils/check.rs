@@ -1,3 +1,4 @@
+use crate::log_warn;
 use ethers::prelude::U256;
 use ethers_core::types::{Transaction};
 
@@ -5,8 +6,11 @@ use ethers_core::types::{Transaction};
 const ERC20_TRANSFER_SIGNATURE: [u8; 4] = [0xa9, 0x05, 0x9c, 0xbb];
 
 /// 检查交易是否为 ETH 转账或 ERC-20 transfer
+///
+/// 合约创建交易不属于转账候选；需要记录监控地址的部署时开启 `deployment_tracking`，
+/// 由 `EventParser::parse_deployments` 单独写入 contract_deployment 表
 pub fn is_target_transaction(tx: &Transaction) -> bool {
-    // 交易必须有目标地址 (排除合约创建交易)
+    // 交易必须有目标地址 (排除合约创建交易，部署记录见 deployment_tracking)
     if tx.to.is_none() {
         return false;
     }
@@ -32,4 +36,93 @@ pub fn is_target_transaction(tx: &Transaction) -> bool {
     }
     // 既不是 ETH 转账，也不是 ERC-20 transfer（可能是其他合约调用）
     false
-}
\ No newline at end of file
+}
+
+/// 部分节点对历史 / pending 交易返回零地址的 from：此时从签名 (v, r, s) 恢复发送方
+/// 返回 false 表示无法恢复（未签名或系统交易），tx.from 保持零地址
+pub fn recover_sender(tx: &mut Transaction) -> bool {
+    if !tx.from.is_zero() {
+        return true;
+    }
+    match tx.recover_from() {
+        Ok(from) if !from.is_zero() => {
+            log_warn!("交易 {:?} 的 from 为空，已从签名恢复发送方 {:?}", tx.hash, from);
+            tx.from = from;
+            true
+        }
+        _ => {
+            log_warn!("交易 {:?} 的 from 为空且无法从签名恢复（未签名/系统交易），标记为发送方未知", tx.hash);
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers_core::types::TransactionRequest;
+    use ethers_core::types::transaction::eip2718::TypedTransaction;
+    use ethers_core::types::{H160, H256};
+    use ethers_signers::{LocalWallet, Signer};
+
+    /// 节点返回 from 为零地址、但签名完整的 legacy 交易
+    fn signed_tx_without_from(wallet: &LocalWallet) -> Transaction {
+        let request: TypedTransaction = TransactionRequest::new()
+            .from(wallet.address())
+            .to(H160::repeat_byte(0x02))
+            .value(1_000u64)
+            .nonce(7u64)
+            .gas(21_000u64)
+            .gas_price(1_000_000_000u64)
+            .chain_id(1u64)
+            .into();
+        let signature = wallet.sign_transaction_sync(&request).unwrap();
+        Transaction {
+            hash: H256::repeat_byte(0x55),
+            nonce: 7u64.into(),
+            from: H160::zero(),
+            to: Some(H160::repeat_byte(0x02)),
+            value: 1_000u64.into(),
+            gas: 21_000u64.into(),
+            gas_price: Some(1_000_000_000u64.into()),
+            chain_id: Some(1u64.into()),
+            v: signature.v.into(),
+            r: signature.r,
+            s: signature.s,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn zero_sender_is_recovered_from_signature() {
+        let wallet: LocalWallet =
+            "4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318"
+                .parse::<LocalWallet>()
+                .unwrap()
+                .with_chain_id(1u64);
+        let mut tx = signed_tx_without_from(&wallet);
+        assert!(recover_sender(&mut tx));
+        assert_eq!(tx.from, wallet.address());
+    }
+
+    #[test]
+    fn unsigned_zero_sender_stays_unknown() {
+        let mut tx = Transaction {
+            to: Some(H160::repeat_byte(0x02)),
+            ..Default::default()
+        };
+        assert!(!recover_sender(&mut tx));
+        assert!(tx.from.is_zero());
+    }
+
+    #[test]
+    fn known_sender_is_left_untouched() {
+        let sender = H160::repeat_byte(0x01);
+        let mut tx = Transaction {
+            from: sender,
+            ..Default::default()
+        };
+        assert!(recover_sender(&mut tx));
+        assert_eq!(tx.from, sender);
+    }
+}
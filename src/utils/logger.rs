@@ -6,7 +6,9 @@ use env_logger::{
 use log::{Level, LevelFilter, Record};
 use std::fs::{self, File};
 use std::io::{self, Write};
-use std::path::Path;
+use flate2::Compression;
+use flate2::write::GzEncoder;
+use std::path::{Path, PathBuf};
 use std::sync::Mutex;
 use std::sync::Once;
 
@@ -18,6 +20,10 @@ const DEFAULT_LOG_LEVEL: &str = "INFO";
 const LOG_FILE_NAME: &str = "eth-block-parser.log";
 const LOG_MAX_SIZE_MB: u64 = 10;
 const LOG_MAX_ROTATIONS: usize = 5;
+/// 是否 gzip 压缩轮转出的旧日志（true/1 开启）
+const LOG_COMPRESS: &str = "LOG_COMPRESS";
+/// 所有轮转文件的总大小上限（MB），超过时从最旧的开始删除；未设置或 0 表示不限制
+const LOG_MAX_TOTAL_MB: &str = "LOG_MAX_TOTAL_MB";
 
 static INIT_LOGGER: Once = Once::new();
 // 新增：全局文件写入器（替代文件 Builder 方案）
@@ -139,9 +145,17 @@ pub fn init_logger() {
     });
 }
 
-// ==================== 日志轮转（无改动） ====================
+// ==================== 日志轮转 ====================
 fn rotate_logs(log_dir: &str, log_file: &str) -> io::Result<()> {
     let log_path = Path::new(log_dir).join(log_file);
+    let compress = std::env::var(LOG_COMPRESS)
+        .map(|v| matches!(v.to_lowercase().as_str(), "1" | "true"))
+        .unwrap_or(false);
+    let max_total_bytes = std::env::var(LOG_MAX_TOTAL_MB)
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .unwrap_or(0)
+        .saturating_mul(1024 * 1024);
 
     if !log_path.exists() {
         return Ok(());
@@ -158,11 +172,19 @@ fn rotate_logs(log_dir: &str, log_file: &str) -> io::Result<()> {
         file_size_mb
     );
 
+    // 最旧的一份（压缩或未压缩）先删除，避免与改名后的文件并存
+    for oldest in rotated_paths(log_dir, log_file, LOG_MAX_ROTATIONS) {
+        if oldest.exists() {
+            fs::remove_file(&oldest)?;
+        }
+    }
     for i in (1..LOG_MAX_ROTATIONS).rev() {
-        let src = Path::new(log_dir).join(format!("{}.{}", log_file, i));
-        let dest = Path::new(log_dir).join(format!("{}.{}", log_file, i + 1));
-        if src.exists() {
-            fs::rename(&src, &dest)?;
+        for suffix in ["", ".gz"] {
+            let src = Path::new(log_dir).join(format!("{}.{}{}", log_file, i, suffix));
+            let dest = Path::new(log_dir).join(format!("{}.{}{}", log_file, i + 1, suffix));
+            if src.exists() {
+                fs::rename(&src, &dest)?;
+            }
         }
     }
 
@@ -173,6 +195,68 @@ fn rotate_logs(log_dir: &str, log_file: &str) -> io::Result<()> {
     // 轮转后更新全局文件句柄
     *FILE_WRITER.lock().unwrap() = File::create(log_path).ok();
 
+    // 压缩与总大小清理放到后台线程，不阻塞启动和日志写入
+    if compress || max_total_bytes > 0 {
+        let log_dir = log_dir.to_string();
+        let log_file = log_file.to_string();
+        std::thread::Builder::new()
+            .name("log-compress".to_string())
+            .spawn(move || {
+                if compress {
+                    if let Err(e) = compress_file(&new_log_1) {
+                        eprintln!("⚠️ 压缩日志 {} 失败: {}", new_log_1.display(), e);
+                    }
+                }
+                if max_total_bytes > 0 {
+                    if let Err(e) = enforce_total_size(&log_dir, &log_file, max_total_bytes) {
+                        eprintln!("⚠️ 清理轮转日志失败: {}", e);
+                    }
+                }
+            })?;
+    }
+
+    Ok(())
+}
+
+/// 第 n 份轮转日志可能的文件名（未压缩 / 已压缩）
+fn rotated_paths(log_dir: &str, log_file: &str, n: usize) -> [PathBuf; 2] {
+    [
+        Path::new(log_dir).join(format!("{}.{}", log_file, n)),
+        Path::new(log_dir).join(format!("{}.{}.gz", log_file, n)),
+    ]
+}
+
+/// 压缩为 `<path>.gz` 后删除原文件；先写临时文件再改名，中途退出不会留下损坏的 .gz
+fn compress_file(path: &Path) -> io::Result<()> {
+    let gz_path = PathBuf::from(format!("{}.gz", path.display()));
+    let tmp_path = PathBuf::from(format!("{}.gz.tmp", path.display()));
+    let mut input = File::open(path)?;
+    let mut encoder = GzEncoder::new(File::create(&tmp_path)?, Compression::default());
+    io::copy(&mut input, &mut encoder)?;
+    encoder.finish()?.sync_all()?;
+    fs::rename(&tmp_path, &gz_path)?;
+    fs::remove_file(path)
+}
+
+/// 轮转文件总大小超过上限时，从最旧的一份开始删除
+fn enforce_total_size(log_dir: &str, log_file: &str, max_total_bytes: u64) -> io::Result<()> {
+    let mut files: Vec<(PathBuf, u64)> = Vec::new();
+    for n in 1..=LOG_MAX_ROTATIONS {
+        for path in rotated_paths(log_dir, log_file, n) {
+            if let Ok(meta) = fs::metadata(&path) {
+                files.push((path, meta.len()));
+            }
+        }
+    }
+    let mut total: u64 = files.iter().map(|(_, len)| len).sum();
+    // files 按从新到旧排列，从末尾删除
+    while total > max_total_bytes {
+        let Some((path, len)) = files.pop() else {
+            break;
+        };
+        fs::remove_file(&path)?;
+        total -= len;
+    }
     Ok(())
 }
 
@@ -2,6 +2,7 @@ pub mod check;
 pub mod convert;
 pub mod format;
 pub mod logger;
+pub mod telemetry;
 pub mod time;
 
 pub use check::*;
@@ -0,0 +1,79 @@
+//! 链路追踪：控制台日志仍由 env_logger 负责，tracing span 在启用 `otel` feature
+//! 且设置了 OTEL_EXPORTER_OTLP_ENDPOINT 时通过 OTLP 导出，两者互不干扰
+#[cfg(feature = "otel")]
+use opentelemetry_sdk::trace::SdkTracerProvider;
+#[cfg(feature = "otel")]
+use std::sync::OnceLock;
+
+const OTLP_ENDPOINT: &str = "OTEL_EXPORTER_OTLP_ENDPOINT";
+#[cfg(feature = "otel")]
+const OTEL_SERVICE_NAME: &str = "OTEL_SERVICE_NAME";
+#[cfg(feature = "otel")]
+const DEFAULT_SERVICE_NAME: &str = "ethereum-rs";
+
+#[cfg(feature = "otel")]
+static TRACER_PROVIDER: OnceLock<SdkTracerProvider> = OnceLock::new();
+
+/// 初始化 OTLP 链路追踪（未配置导出地址时不做任何事，span 为空操作）
+pub fn init_tracing() {
+    let Ok(endpoint) = std::env::var(OTLP_ENDPOINT) else {
+        return;
+    };
+
+    #[cfg(feature = "otel")]
+    {
+        use opentelemetry::trace::TracerProvider as _;
+        use opentelemetry_sdk::Resource;
+        use tracing_subscriber::EnvFilter;
+        use tracing_subscriber::layer::SubscriberExt;
+        use tracing_subscriber::util::SubscriberInitExt;
+
+        let exporter = match opentelemetry_otlp::SpanExporter::builder()
+            .with_http()
+            .build()
+        {
+            Ok(exporter) => exporter,
+            Err(e) => {
+                crate::log_error!("OTLP exporter 初始化失败 ({}): {}", endpoint, e);
+                return;
+            }
+        };
+        let service_name =
+            std::env::var(OTEL_SERVICE_NAME).unwrap_or_else(|_| DEFAULT_SERVICE_NAME.to_string());
+        let provider = SdkTracerProvider::builder()
+            .with_batch_exporter(exporter)
+            .with_resource(Resource::builder().with_service_name(service_name).build())
+            .build();
+        let tracer = provider.tracer(DEFAULT_SERVICE_NAME);
+        opentelemetry::global::set_tracer_provider(provider.clone());
+        let _ = TRACER_PROVIDER.set(provider);
+
+        let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info"));
+        if let Err(e) = tracing_subscriber::registry()
+            .with(filter)
+            .with(tracing_opentelemetry::layer().with_tracer(tracer))
+            .try_init()
+        {
+            crate::log_error!("tracing subscriber 初始化失败: {}", e);
+            return;
+        }
+        crate::log_info!("✅ OTLP 链路追踪已启用 | endpoint: {}", endpoint);
+    }
+
+    #[cfg(not(feature = "otel"))]
+    crate::log_warn!(
+        "设置了 {}={}，但未启用 otel feature，链路追踪不会导出",
+        OTLP_ENDPOINT,
+        endpoint
+    );
+}
+
+/// 退出前刷新尚未导出的 span
+pub fn shutdown_tracing() {
+    #[cfg(feature = "otel")]
+    if let Some(provider) = TRACER_PROVIDER.get() {
+        if let Err(e) = provider.shutdown() {
+            crate::log_warn!("OTLP tracer 关闭失败: {}", e);
+        }
+    }
+}
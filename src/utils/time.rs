@@ -0,0 +1,34 @@
+use crate::errors::error::AppError;
+use chrono::DateTime;
+
+/// 解析时间参数为 unix 秒：纯数字按 unix 时间戳处理，否则按 RFC3339（如 2026-10-16T08:00:00Z）解析
+pub fn parse_timestamp(input: &str) -> Result<i64, AppError> {
+    let input = input.trim();
+    if let Ok(secs) = input.parse::<i64>() {
+        return Ok(secs);
+    }
+    DateTime::parse_from_rfc3339(input)
+        .map(|dt| dt.timestamp())
+        .map_err(|e| AppError::InvalidRequest(format!("无效的时间 {}: {}", input, e)))
+}
+
+/// 校验时间区间 from <= to
+pub fn validate_time_range(from_ts: i64, to_ts: i64) -> Result<(), AppError> {
+    if from_ts > to_ts {
+        return Err(AppError::Validation(format!(
+            "时间区间无效: from ({}) 大于 to ({})",
+            from_ts, to_ts
+        )));
+    }
+    Ok(())
+}
+
+/// 将秒数格式化为粗略的可读时长（如 "约 4 小时"），用于日志与告警
+pub fn format_duration_approx(secs: u64) -> String {
+    match secs {
+        0..60 => format!("约 {} 秒", secs),
+        60..3600 => format!("约 {} 分钟", secs / 60),
+        3600..86400 => format!("约 {:.1} 小时", secs as f64 / 3600.0),
+        _ => format!("约 {:.1} 天", secs as f64 / 86400.0),
+    }
+}